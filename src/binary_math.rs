@@ -0,0 +1,188 @@
+use libc::c_int;
+
+use mpfr_sys::*;
+
+use {BigFloat, grnd};
+use ziv::{Approx, round_with, working, exp_of};
+
+/// Functions of two arguments.
+///
+/// All of these are implemented for values and references on both sides, following
+/// the same rules of operand reuse as the arithmetic operators.
+pub trait BinaryMath<RHS=Self> {
+    type Output;
+
+    /// Returns the smaller of two numbers.
+    ///
+    /// If one of the arguments is NaN, the other one is returned; the result is NaN only
+    /// when both arguments are NaN. The minimum of `-0` and `+0` is `-0`.
+    fn min(self, rhs: RHS) -> Self::Output;
+
+    /// Returns the larger of two numbers.
+    ///
+    /// If one of the arguments is NaN, the other one is returned; the result is NaN only
+    /// when both arguments are NaN. The maximum of `-0` and `+0` is `+0`.
+    fn max(self, rhs: RHS) -> Self::Output;
+
+    /// Returns the positive difference, i.e. `self - rhs` if `self > rhs` and `+0` otherwise.
+    ///
+    /// The result is NaN if one of the arguments is NaN.
+    fn dim(self, rhs: RHS) -> Self::Output;
+
+    /// Returns a number with the absolute value of `self` and the sign of `rhs`.
+    ///
+    /// This works for NaN and signed zeros on both sides as well.
+    fn copysign(self, rhs: RHS) -> Self::Output;
+
+    /// Computes the arc tangent of `self / rhs`, using the signs of both arguments
+    /// to determine the quadrant of the result.
+    ///
+    /// Here `self` is the `y` coordinate and `rhs` is the `x` coordinate.
+    fn atan2(self, rhs: RHS) -> Self::Output;
+
+    /// Computes `sqrt(self^2 + rhs^2)` without intermediate overflow or underflow.
+    fn hypot(self, rhs: RHS) -> Self::Output;
+
+    /// Computes the arithmetic-geometric mean of two numbers.
+    ///
+    /// The result is NaN if one of the arguments is negative.
+    fn agm(self, rhs: RHS) -> Self::Output;
+
+    /// Computes the logarithm of `self` to the base `rhs`.
+    ///
+    /// The result is correctly rounded, including the cases when it is exact, like
+    /// `log_base(8, 2) = 3` or `log_base(2, 4) = 0.5`.
+    fn log_base(self, rhs: RHS) -> Self::Output;
+}
+
+unsafe fn log_base(rop: mpfr_ptr, x: mpfr_srcptr, b: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    // The logarithms are NaN, infinite or zero exactly when they are so in any precision,
+    // and then the quotient is such too
+    let mut lx = working(2);
+    let mut lb = working(2);
+    mpfr_log(&mut lx.value, x, MPFR_RNDN);
+    mpfr_log(&mut lb.value, b, MPFR_RNDN);
+    if mpfr_regular_p(&lx.value) == 0 || mpfr_regular_p(&lb.value) == 0 {
+        return mpfr_div(rop, &lx.value, &lb.value, rnd);
+    }
+
+    let prec = mpfr_get_prec(rop) as i64;
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        let mut lb = working(wp);
+        mpfr_log(&mut r.value, x, MPFR_RNDN);
+        mpfr_log(&mut lb.value, b, MPFR_RNDN);
+        mpfr_div(&mut r.value, &r.value, &lb.value, MPFR_RNDN);
+
+        // The result may be a dyadic number which cannot be rounded by looking at
+        // approximations, so the nearest candidate in the target precision is checked
+        // directly: it is exact if b raised to it gives x exactly
+        let mut c = working(prec + 1);
+        mpfr_set(&mut c.value, &r.value, MPFR_RNDN);
+        let mut t = working(mpfr_get_prec(x) as i64);
+        if mpfr_pow(&mut t.value, b, &c.value, MPFR_RNDN) == 0 && mpfr_equal_p(&t.value, x) != 0 {
+            return Approx::Exact(c);
+        }
+
+        // Three roundings with the relative error of at most 2^-wp each
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+macro_rules! impl_binary_math_all {
+    ($($meth:ident -> $mpfr:ident),+) => {
+        impl<'a> BinaryMath<&'a BigFloat> for BigFloat {
+            type Output = BigFloat;
+
+            $(
+            fn $meth(mut self, rhs: &'a BigFloat) -> BigFloat {
+                unsafe {
+                    $mpfr(&mut self.value, &self.value, &rhs.value, grnd());
+                }
+                self
+            }
+            )+
+        }
+
+        impl BinaryMath<BigFloat> for BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, rhs: BigFloat) -> BigFloat {
+                self.$meth(&rhs)
+            }
+            )+
+        }
+
+        impl<'r> BinaryMath<BigFloat> for &'r BigFloat {
+            type Output = BigFloat;
+
+            $(
+            fn $meth(self, mut rhs: BigFloat) -> BigFloat {
+                unsafe {
+                    $mpfr(&mut rhs.value, &self.value, &rhs.value, grnd());
+                }
+                rhs
+            }
+            )+
+        }
+
+        impl<'a, 'r> BinaryMath<&'a BigFloat> for &'r BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, rhs: &'a BigFloat) -> BigFloat {
+                self.clone().$meth(rhs)
+            }
+            )+
+        }
+    }
+}
+
+impl_binary_math_all! {
+    min -> mpfr_min,
+    max -> mpfr_max,
+    dim -> mpfr_dim,
+    copysign -> mpfr_copysign,
+    atan2 -> mpfr_atan2,
+    hypot -> mpfr_hypot,
+    agm -> mpfr_agm,
+    log_base -> log_base
+}
+
+// Stepping is done in place and at the precision of the number, so these are not
+// a part of the trait
+impl BigFloat {
+    /// Replaces this number with the next representable number towards `+Inf`.
+    ///
+    /// `-Inf` becomes the smallest representable negative number, zero of any sign becomes
+    /// the smallest representable positive number, and NaN and `+Inf` are left as they are.
+    pub fn nextabove(&mut self) {
+        unsafe {
+            mpfr_nextabove(&mut self.value);
+        }
+    }
+
+    /// Replaces this number with the next representable number towards `-Inf`.
+    ///
+    /// `+Inf` becomes the largest representable positive number, zero of any sign becomes
+    /// the largest representable negative number, and NaN and `-Inf` are left as they are.
+    pub fn nextbelow(&mut self) {
+        unsafe {
+            mpfr_nextbelow(&mut self.value);
+        }
+    }
+
+    /// Replaces this number with the next representable number towards `target`.
+    ///
+    /// Nothing is changed if this number is equal to `target`. If one of the numbers is NaN,
+    /// this number becomes NaN.
+    pub fn nexttoward(&mut self, target: &BigFloat) {
+        unsafe {
+            mpfr_nexttoward(&mut self.value, &target.value);
+        }
+    }
+}
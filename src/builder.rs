@@ -7,13 +7,13 @@ use Precision;
 pub struct BigFloatBuilder;
 
 macro_rules! generate_const_methods {
-    ($target:ty, $($method:ident -> $setter:ident),+) => {
+    ($target:ty, $($method:ident($($p:ident: $t:ty),*) -> $setter:ident),+) => {
         impl $target {
             $(
             #[inline]
-            pub fn $method(self) -> BigFloat {
+            pub fn $method(self $(, $p: $t)*) -> BigFloat {
                 let mut r = self.fresh();
-                r.$setter();
+                r.$setter($($p),*);
                 r
             }
             )+
@@ -38,10 +38,19 @@ macro_rules! impl_building {
         }
 
         generate_const_methods! { $target,
-            const_log2 -> set_to_const_log2,
-            const_pi -> set_to_const_pi,
-            const_euler -> set_to_const_euler,
-            const_catalan -> set_to_const_catalan
+            const_log2() -> set_to_const_log2,
+            const_pi() -> set_to_const_pi,
+            const_euler() -> set_to_const_euler,
+            const_catalan() -> set_to_const_catalan,
+            const_e() -> set_to_const_e,
+            const_ln10() -> set_to_const_ln10,
+            const_sqrt2() -> set_to_const_sqrt2,
+            const_golden_ratio() -> set_to_const_golden_ratio,
+            const_apery() -> set_to_const_apery,
+            const_glaisher() -> set_to_const_glaisher,
+            const_khinchin() -> set_to_const_khinchin,
+            factorial(n: u64) -> set_to_factorial,
+            zeta_ui(n: u64) -> set_to_zeta_ui
         }
     }
 }
@@ -0,0 +1,285 @@
+// Mathematical constants and their cache.
+//
+// Every constant is correctly rounded. A thread-local cache keeps the value of each constant
+// in the highest precision requested so far, rounded to nearest together with its ternary
+// value, so requests for the same or a lower precision only round the cached value. The cache
+// is released by `BigFloat::free_cache()`.
+
+use std::cell::RefCell;
+use std::cmp::{self, Ordering};
+use std::collections::HashMap;
+
+use libc::{c_int, c_ulong};
+
+use mpfr_sys::*;
+
+use {BigFloat, RoundingMode, ternary};
+use ziv::{Approx, round_with, working, exp_of, log2_ceil};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Constant {
+    Log2,
+    Pi,
+    Euler,
+    Catalan,
+    E,
+    Ln10,
+    Sqrt2,
+    GoldenRatio,
+    Apery,
+    Glaisher,
+    Khinchin
+}
+
+thread_local! {
+    static CACHE: RefCell<HashMap<Constant, (BigFloat, c_int)>> = RefCell::new(HashMap::new())
+}
+
+/// Sets `x` to the constant `c` rounded to the precision of `x`, using the cache.
+pub fn set_cached(x: &mut BigFloat, c: Constant, rnd: RoundingMode) -> Ordering {
+    let prec = unsafe { mpfr_get_prec(&x.value) };
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let cached = match cache.get(&c) {
+            Some(&(ref v, _)) => unsafe { mpfr_get_prec(&v.value) } >= prec,
+            None => false
+        };
+        if !cached {
+            let mut v = working(prec as i64);
+            let t = unsafe { compute(c, &mut v.value, MPFR_RNDN) };
+            cache.insert(c, (v, t));
+        }
+        let &(ref v, t) = cache.get(&c).unwrap();
+        ternary(unsafe { round_cached(&mut x.value, v, t, rnd as mpfr_rnd_t) })
+    })
+}
+
+/// Rounds the value `v` of a positive constant, which is rounded to nearest with the ternary
+/// value `t`, to the precision of `rop`, and raises the inexact flag if the result is inexact.
+///
+/// No number of the target precision lies strictly between `v` and the constant, so
+/// the rounding of `v` is that of the constant, except when `v` is itself representable or
+/// is a tie; the ternary value `t` tells the direction of the constant from `v` then.
+unsafe fn round_cached(rop: mpfr_ptr, v: &BigFloat, t: c_int, rnd: mpfr_rnd_t) -> c_int {
+    let r = mpfr_set(rop, &v.value, rnd);
+    if t == 0 {
+        return r;
+    }
+    if r == 0 {
+        mpfr_set_inexflag();
+        return match rnd {
+            MPFR_RNDD | MPFR_RNDZ if t > 0 => {
+                mpfr_nextbelow(rop);
+                -1
+            }
+            MPFR_RNDU | MPFR_RNDA if t < 0 => {
+                mpfr_nextabove(rop);
+                1
+            }
+            _ => t
+        };
+    }
+    if rnd == MPFR_RNDN && (r > 0) == (t > 0) {
+        // A tie is rounded towards the constant instead of to even
+        let mut m = working(mpfr_get_prec(rop) as i64 + 1);
+        if mpfr_set(&mut m.value, &v.value, MPFR_RNDN) == 0 {
+            if r > 0 {
+                mpfr_nextbelow(rop);
+            } else {
+                mpfr_nextabove(rop);
+            }
+            return -r;
+        }
+    }
+    r
+}
+
+/// Drops all cached values.
+pub fn clear_cache() {
+    CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+unsafe fn compute(c: Constant, rop: mpfr_ptr, rnd: mpfr_rnd_t) -> c_int {
+    match c {
+        Constant::Log2 => mpfr_const_log2(rop, rnd),
+        Constant::Pi => mpfr_const_pi(rop, rnd),
+        Constant::Euler => mpfr_const_euler(rop, rnd),
+        Constant::Catalan => mpfr_const_catalan(rop, rnd),
+        Constant::E => mpfr_exp(rop, &integer(1).value, rnd),
+        Constant::Ln10 => mpfr_log(rop, &integer(10).value, rnd),
+        Constant::Sqrt2 => mpfr_sqrt_ui(rop, 2, rnd),
+        Constant::GoldenRatio => golden_ratio(rop, rnd),
+        Constant::Apery => mpfr_zeta_ui(rop, 3, rnd),
+        Constant::Glaisher => glaisher(rop, rnd),
+        Constant::Khinchin => khinchin(rop, rnd)
+    }
+}
+
+unsafe fn integer(n: c_ulong) -> BigFloat {
+    let mut r = working(64);
+    mpfr_set_ui(&mut r.value, n, MPFR_RNDN);
+    r
+}
+
+/// `(1 + sqrt(5)) / 2`.
+unsafe fn golden_ratio(rop: mpfr_ptr, rnd: mpfr_rnd_t) -> c_int {
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        mpfr_sqrt_ui(&mut r.value, 5, MPFR_RNDN);
+        mpfr_add_ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+        mpfr_div_2ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+        // Two roundings of positive numbers
+        let err = exp_of(&r) + 2 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// The Glaisher-Kinkelin constant `A`, computed from
+/// `ln A = (γ + ln 2π) / 12 + S / 2π²` where `S = -ζ'(2) = Σ ln(n) / n²`.
+///
+/// The series is summed directly up to `N - 1` and the rest is approximated with
+/// the Euler-Maclaurin formula with `K` correction terms,
+///
+/// `Σ_{n >= N} f(n) = (ln N + 1) / N + f(N) / 2 + Σ_{k=1..K} B_2k (ln N + 1 - H_2k) / N^(2k+1) + R`
+///
+/// where the Bernoulli numbers are `B_2k = (-1)^(k+1) 2 (2k)! ζ(2k) / (2π)^2k`. For
+/// `N >= 2K + 5` the derivatives of `f(x) = ln(x) / x²` of orders `2K + 2` and `2K + 4` keep
+/// the same sign on `[N, ∞)`, so `|R|` is not greater than the first omitted term.
+unsafe fn glaisher(rop: mpfr_ptr, rnd: mpfr_rnd_t) -> c_int {
+    round_with(rop, rnd, |wp| {
+        let k_max = (wp / 8 + 2) as c_ulong;
+        let n = 2 * k_max + 5;
+
+        // The direct part
+        let mut s = working(wp);
+        let mut t = working(wp);
+        mpfr_set_ui(&mut s.value, 0, MPFR_RNDN);
+        for i in 2..n {
+            mpfr_log(&mut t.value, &integer(i).value, MPFR_RNDN);
+            mpfr_div_ui(&mut t.value, &t.value, i, MPFR_RNDN);
+            mpfr_div_ui(&mut t.value, &t.value, i, MPFR_RNDN);
+            mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+        }
+
+        // The integral and the half of the first term of the rest
+        let mut ln_n = working(wp);
+        mpfr_log(&mut ln_n.value, &integer(n).value, MPFR_RNDN);
+        mpfr_add_ui(&mut t.value, &ln_n.value, 1, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &t.value, n, MPFR_RNDN);
+        mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &ln_n.value, 2 * n, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &t.value, n, MPFR_RNDN);
+        mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+
+        // The corrections; c = (2k)! / (2πN)^2k and h = H_2k are updated incrementally
+        let mut q = working(wp);
+        mpfr_const_pi(&mut q.value, MPFR_RNDN);
+        mpfr_mul_ui(&mut q.value, &q.value, 2 * n, MPFR_RNDN);
+        mpfr_sqr(&mut q.value, &q.value, MPFR_RNDN);
+        let mut c = working(wp);
+        mpfr_set_ui(&mut c.value, 1, MPFR_RNDN);
+        let mut h = working(wp);
+        mpfr_set_ui(&mut h.value, 0, MPFR_RNDN);
+        let mut z = working(wp);
+        let mut k = 1;
+        loop {
+            mpfr_mul_ui(&mut c.value, &c.value, (2 * k - 1) * (2 * k), MPFR_RNDN);
+            mpfr_div(&mut c.value, &c.value, &q.value, MPFR_RNDN);
+            mpfr_add(&mut h.value, &h.value, &recip(2 * k - 1, wp).value, MPFR_RNDN);
+            mpfr_add(&mut h.value, &h.value, &recip(2 * k, wp).value, MPFR_RNDN);
+
+            // 2 ζ(2k) c (ln N + 1 - H_2k) / N
+            mpfr_add_ui(&mut t.value, &ln_n.value, 1, MPFR_RNDN);
+            mpfr_sub(&mut t.value, &t.value, &h.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &c.value, MPFR_RNDN);
+            mpfr_zeta_ui(&mut z.value, 2 * k, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &z.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            mpfr_div_ui(&mut t.value, &t.value, n, MPFR_RNDN);
+
+            if k > k_max {
+                break;
+            }
+            if k % 2 == 1 {
+                mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+            } else {
+                mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+            }
+            k += 1;
+        }
+        // Now t is the first omitted term
+        let tail = exp_of(&t) + 1;
+
+        // ln A
+        let mut a = working(wp);
+        mpfr_const_pi(&mut a.value, MPFR_RNDN);
+        mpfr_sqr(&mut t.value, &a.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        mpfr_div(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut a.value, &a.value, 1, MPFR_RNDN);
+        mpfr_log(&mut a.value, &a.value, MPFR_RNDN);
+        mpfr_const_euler(&mut t.value, MPFR_RNDN);
+        mpfr_add(&mut a.value, &a.value, &t.value, MPFR_RNDN);
+        mpfr_div_ui(&mut a.value, &a.value, 12, MPFR_RNDN);
+        mpfr_add(&mut a.value, &a.value, &s.value, MPFR_RNDN);
+        mpfr_exp(&mut a.value, &a.value, MPFR_RNDN);
+
+        // All parts of S are below 1 in magnitude, and their relative errors are bounded by
+        // N roundings for the direct part and by 5k + 5 + 2.4 (2k + 2) (ln N + 1) roundings
+        // for the correction terms (ln N + 1 - H_2k >= 0.42 for k <= K); ln N + 1 < 64,
+        // and the rest of the computation adds less than 20 roundings. The relative error
+        // of A is its absolute error in ln A
+        let n_errors = (n + 313 * k_max + 360) as u64;
+        let err = cmp::max(log2_ceil(n_errors) - wp, tail) + exp_of(&a) + 2;
+        Approx::Within(a, err)
+    })
+}
+
+/// Khinchin's constant `K`, computed from the series
+/// `ln K ln 2 = Σ_{n>=1} (ζ(2n) - 1) / n (1 - 1/2 + 1/3 - ... + 1/(2n - 1))`.
+///
+/// Since `ζ(2n) - 1 < 3 / 4^n`, the rest of the series after `N` terms is less than `4^-N`.
+unsafe fn khinchin(rop: mpfr_ptr, rnd: mpfr_rnd_t) -> c_int {
+    round_with(rop, rnd, |wp| {
+        let n_max = (wp / 2 + 2) as c_ulong;
+        let mut s = working(wp);
+        mpfr_set_ui(&mut s.value, 0, MPFR_RNDN);
+        // alternating harmonic numbers
+        let mut h = working(wp);
+        mpfr_set_ui(&mut h.value, 1, MPFR_RNDN);
+        let mut t = working(wp);
+        for n in 1..(n_max + 1) {
+            if n > 1 {
+                mpfr_sub(&mut h.value, &h.value, &recip(2 * n - 2, wp).value, MPFR_RNDN);
+                mpfr_add(&mut h.value, &h.value, &recip(2 * n - 1, wp).value, MPFR_RNDN);
+            }
+            // ζ(2n) - 1 loses 2n bits to cancellation, so ζ(2n) is computed with that much
+            // more precision, and the subtraction is exact
+            let mut z = working(wp + 2 * n as i64 + 4);
+            mpfr_zeta_ui(&mut z.value, 2 * n, MPFR_RNDN);
+            mpfr_sub_ui(&mut z.value, &z.value, 1, MPFR_RNDN);
+            mpfr_div_ui(&mut t.value, &z.value, n, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &h.value, MPFR_RNDN);
+            mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+        }
+        mpfr_const_log2(&mut t.value, MPFR_RNDN);
+        mpfr_div(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+        mpfr_exp(&mut s.value, &s.value, MPFR_RNDN);
+
+        // Each term has the relative error of at most 2n + 4 roundings (the alternating
+        // harmonic number is in [1/2, 1]) and the sum adds N more; the rest of the series,
+        // ln 2 and exp add less than four roundings and 2^-wp. The relative error of K is
+        // the absolute error of ln K < 1
+        let n_errors = (3 * n_max + 9) as u64;
+        let err = exp_of(&s) + log2_ceil(n_errors) + 1 - wp;
+        Approx::Within(s, err)
+    })
+}
+
+/// Returns `1/n` rounded to the working precision.
+unsafe fn recip(n: c_ulong, wp: i64) -> BigFloat {
+    let mut r = working(wp);
+    mpfr_set_ui(&mut r.value, 1, MPFR_RNDN);
+    mpfr_div_ui(&mut r.value, &r.value, n, MPFR_RNDN);
+    r
+}
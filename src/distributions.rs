@@ -0,0 +1,1449 @@
+// Probability distributions: densities, distribution functions and quantiles, correctly
+// rounded to the precision of the argument.
+//
+// The densities are computed as exponentials of sums of logarithms, and the distribution
+// functions from the smaller of the two tails, the other one being its complement, so that
+// tail probabilities far below the range of `f64` keep their relative accuracy. All of them
+// are evaluated in the widest exponent range and rounded to the current one at the end. Where
+// an argument has to be rounded first, the error is bounded with the density. The quantiles
+// are the roots of `ln T(x) = ln q` for the smaller tail `T`, found with Newton's iteration
+// and enclosed in an interval whose ends give certain signs of `T(x) - q` (see `ziv.rs`).
+//
+// The Beta distributions with a small integer parameter have rational values up to a power
+// of the argument, which are computed exactly whenever that power is dyadic. Otherwise none of
+// these functions has a dyadic value at a dyadic argument, except for the ones handled
+// separately.
+
+use std::cmp::{self, Ordering};
+
+use libc::{c_int, c_long, c_ulong};
+
+use mpfr_sys::*;
+
+use {BigFloat, RoundingMode, global_rounding_mode, ternary};
+use ext::mpfr_gamma_inc;
+use special::{erfc_inv, gamma_upper_expansion};
+use ziv::{Approx, Sum, round_with, extended, with_extended_range, retry, working, exp_of};
+use ziv::{exact_add, exact_sub, exact_mul, exp_approx, integer, copy, set_nan};
+use ziv::{newton, enclose, certain_sign, split, mul_approx};
+
+/// A continuous probability distribution.
+///
+/// The results have the precision of the argument and are correctly rounded. Invalid
+/// parameters of a distribution give NaN.
+pub trait Continuous {
+    /// The probability density function.
+    #[inline]
+    fn pdf(&self, x: &BigFloat) -> BigFloat {
+        self.pdf_round(x, global_rounding_mode::get()).0
+    }
+
+    /// The cumulative distribution function `P(X <= x)`.
+    #[inline]
+    fn cdf(&self, x: &BigFloat) -> BigFloat {
+        self.cdf_round(x, global_rounding_mode::get()).0
+    }
+
+    /// The survival function `P(X > x)`, which is accurate in the upper tail, unlike
+    /// `1 - cdf(x)`.
+    #[inline]
+    fn sf(&self, x: &BigFloat) -> BigFloat {
+        self.sf_round(x, global_rounding_mode::get()).0
+    }
+
+    /// The quantile function, the least `x` such that `P(X <= x) >= p` for `0 <= p <= 1`.
+    #[inline]
+    fn quantile(&self, p: &BigFloat) -> BigFloat {
+        self.quantile_round(p, global_rounding_mode::get()).0
+    }
+
+    // The same functions with an explicit rounding mode; they also return the ternary value
+    fn pdf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering);
+    fn cdf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering);
+    fn sf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering);
+    fn quantile_round(&self, p: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering);
+}
+
+/// A probability distribution over the non-negative integers.
+///
+/// The results have the precision of the parameters of the distribution and are correctly
+/// rounded. Invalid parameters give NaN.
+pub trait Discrete {
+    /// The probability mass function `P(X = k)`.
+    #[inline]
+    fn pmf(&self, k: u64) -> BigFloat {
+        self.pmf_round(k, global_rounding_mode::get()).0
+    }
+
+    /// The cumulative distribution function `P(X <= k)`.
+    #[inline]
+    fn cdf(&self, k: u64) -> BigFloat {
+        self.cdf_round(k, global_rounding_mode::get()).0
+    }
+
+    /// The survival function `P(X > k)`.
+    #[inline]
+    fn sf(&self, k: u64) -> BigFloat {
+        self.sf_round(k, global_rounding_mode::get()).0
+    }
+
+    /// The quantile function, the least `k` such that `P(X <= k) >= p`. Returns `None` for
+    /// invalid parameters, for NaN and for `p` outside of `[0, 1)`.
+    fn quantile(&self, p: &BigFloat) -> Option<u64>;
+
+    // The same functions with an explicit rounding mode; they also return the ternary value
+    fn pmf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering);
+    fn cdf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering);
+    fn sf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering);
+}
+
+/// The normal distribution with the mean `μ` and the standard deviation `σ > 0`.
+#[derive(Clone, Debug)]
+pub struct Normal {
+    pub mean: BigFloat,
+    pub sd: BigFloat
+}
+
+impl Normal {
+    pub fn new(mean: BigFloat, sd: BigFloat) -> Normal {
+        Normal { mean: mean, sd: sd }
+    }
+}
+
+/// The gamma distribution with the shape `k > 0` and the scale `θ > 0`.
+#[derive(Clone, Debug)]
+pub struct Gamma {
+    pub shape: BigFloat,
+    pub scale: BigFloat
+}
+
+impl Gamma {
+    pub fn new(shape: BigFloat, scale: BigFloat) -> Gamma {
+        Gamma { shape: shape, scale: scale }
+    }
+}
+
+/// The Beta distribution with the shapes `α > 0` and `β > 0`.
+#[derive(Clone, Debug)]
+pub struct Beta {
+    pub alpha: BigFloat,
+    pub beta: BigFloat
+}
+
+impl Beta {
+    pub fn new(alpha: BigFloat, beta: BigFloat) -> Beta {
+        Beta { alpha: alpha, beta: beta }
+    }
+}
+
+/// Student's t-distribution with `ν > 0` degrees of freedom.
+#[derive(Clone, Debug)]
+pub struct StudentT {
+    pub df: BigFloat
+}
+
+impl StudentT {
+    pub fn new(df: BigFloat) -> StudentT {
+        StudentT { df: df }
+    }
+}
+
+/// The chi-squared distribution with `k > 0` degrees of freedom, which is the gamma
+/// distribution with the shape `k / 2` and the scale 2.
+#[derive(Clone, Debug)]
+pub struct ChiSquared {
+    pub df: BigFloat
+}
+
+impl ChiSquared {
+    pub fn new(df: BigFloat) -> ChiSquared {
+        ChiSquared { df: df }
+    }
+
+    fn gamma(&self) -> Gamma {
+        unsafe {
+            let mut shape = copy(&self.df.value);
+            mpfr_div_2ui(&mut shape.value, &shape.value, 1, MPFR_RNDN);
+            Gamma::new(shape, integer(2))
+        }
+    }
+}
+
+/// The Poisson distribution with the mean `λ > 0`.
+#[derive(Clone, Debug)]
+pub struct Poisson {
+    pub lambda: BigFloat
+}
+
+impl Poisson {
+    pub fn new(lambda: BigFloat) -> Poisson {
+        Poisson { lambda: lambda }
+    }
+}
+
+macro_rules! impl_continuous {
+    ($($t:ident: $pdf:ident, $dist:ident, $quantile:ident);+) => {
+        $(
+        impl Continuous for $t {
+            fn pdf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(x.prec());
+                let t = unsafe { $pdf(&mut r.value, self, &x.value, rnd as mpfr_rnd_t) };
+                (r, ternary(t))
+            }
+
+            fn cdf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(x.prec());
+                let t = unsafe { $dist(&mut r.value, self, &x.value, false, rnd as mpfr_rnd_t) };
+                (r, ternary(t))
+            }
+
+            fn sf_round(&self, x: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(x.prec());
+                let t = unsafe { $dist(&mut r.value, self, &x.value, true, rnd as mpfr_rnd_t) };
+                (r, ternary(t))
+            }
+
+            fn quantile_round(&self, p: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(p.prec());
+                let t = unsafe { $quantile(&mut r.value, self, &p.value, rnd as mpfr_rnd_t) };
+                (r, ternary(t))
+            }
+        }
+        )+
+    }
+}
+
+impl_continuous! {
+    Normal: normal_pdf, normal_dist, normal_quantile;
+    Gamma: gamma_pdf, gamma_dist, gamma_quantile;
+    Beta: beta_pdf, beta_dist, beta_quantile;
+    StudentT: student_pdf, student_dist, student_quantile;
+    ChiSquared: chi_squared_pdf, chi_squared_dist, chi_squared_quantile
+}
+
+impl Discrete for Poisson {
+    fn quantile(&self, p: &BigFloat) -> Option<u64> {
+        unsafe { poisson_quantile(self, &p.value) }
+    }
+
+    fn pmf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(self.lambda.prec());
+        let t = unsafe { poisson_pmf(&mut r.value, self, k, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+
+    fn cdf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(self.lambda.prec());
+        let t = unsafe { poisson_dist(&mut r.value, self, k, false, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+
+    fn sf_round(&self, k: u64, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(self.lambda.prec());
+        let t = unsafe { poisson_dist(&mut r.value, self, k, true, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+}
+
+/// Returns whether `x` is a finite positive number.
+unsafe fn positive(x: mpfr_srcptr) -> bool {
+    mpfr_number_p(x) != 0 && mpfr_sgn(x) > 0
+}
+
+/// Returns whether `x` is a probability, i.e. a number in `[0, 1]`.
+unsafe fn probability(x: mpfr_srcptr) -> bool {
+    mpfr_nan_p(x) == 0 && mpfr_sgn(x) >= 0 && mpfr_cmp_ui(x, 1) <= 0
+}
+
+/// Returns whether `x` is 1/2.
+unsafe fn is_half(x: mpfr_srcptr) -> bool {
+    mpfr_cmp_ui_2exp(x, 1, -1) == 0
+}
+
+/// `1 - a` for an approximation of a probability. The result is below 1 by less than
+/// `2^(1 - wp)` if it is `Approx::Below`.
+unsafe fn complement(a: Approx, wp: i64) -> Approx {
+    let (v, e) = match a {
+        Approx::Exact(v) => return Approx::Exact(exact_sub(&integer(1).value, &v.value)),
+        // The complement is then between 1 - 2^(emin - 2) and 1
+        Approx::Underflow => return Approx::Below(integer(1)),
+        Approx::Within(v, e) => (v, e),
+        a => return a
+    };
+    if e < -wp && exp_of(&v) < -wp {
+        // The complement is within 2^(1 - wp) below 1, and the working precision always
+        // exceeds the target one by more than two bits
+        if mpfr_sgn(&v.value) > 0 {
+            return Approx::Below(integer(1));
+        }
+    }
+    let mut r = working(wp);
+    mpfr_ui_sub(&mut r.value, 1, &v.value, MPFR_RNDN);
+    let err = cmp::max(e, exp_of(&r) - wp) + 1;
+    Approx::Within(r, err)
+}
+
+/// `a / 2` for an approximation.
+unsafe fn halve(a: Approx) -> Approx {
+    match a {
+        Approx::Exact(mut v) => {
+            mpfr_div_2ui(&mut v.value, &v.value, 1, MPFR_RNDN);
+            Approx::Exact(v)
+        }
+        Approx::Within(mut v, e) => {
+            mpfr_div_2ui(&mut v.value, &v.value, 1, MPFR_RNDN);
+            Approx::Within(v, e - 1)
+        }
+        Approx::Below(mut v) => {
+            mpfr_div_2ui(&mut v.value, &v.value, 1, MPFR_RNDN);
+            Approx::Below(v)
+        }
+        a => a
+    }
+}
+
+/// `-a` for an approximation.
+unsafe fn negate(a: Approx) -> Approx {
+    match a {
+        Approx::Within(mut v, e) => {
+            mpfr_neg(&mut v.value, &v.value, MPFR_RNDN);
+            Approx::Within(v, e)
+        }
+        a => a
+    }
+}
+
+/// Adds an error below `2^err` to an approximation.
+fn widen(a: Approx, err: i64) -> Approx {
+    match a {
+        Approx::Within(v, e) => Approx::Within(v, cmp::max(e, err) + 1),
+        Approx::Exact(v) => Approx::Within(v, err),
+        a => a
+    }
+}
+
+// Normal distribution
+
+unsafe fn normal_valid(d: &Normal) -> bool {
+    mpfr_number_p(&d.mean.value) != 0 && positive(&d.sd.value)
+}
+
+unsafe fn normal_pdf(rop: mpfr_ptr, d: &Normal, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !normal_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    let dx = exact_sub(x, &d.mean.value);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        // ln φ = -z²/2 - ln σ - ln(2π)/2
+        let mut l = Sum::new(wp);
+        let mut t = working(wp);
+        mpfr_div(&mut t.value, &dx.value, &d.sd.value, MPFR_RNDN);
+        mpfr_sqr(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        l.sub(&t, 3);
+        mpfr_log(&mut t.value, &d.sd.value, MPFR_RNDN);
+        l.sub(&t, 1);
+        mpfr_const_pi(&mut t.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        l.sub(&t, 2);
+        exp_approx(l, wp)
+    }))
+}
+
+/// The upper tail `Q(z) = erfc(z / √2) / 2` for `z > 0`. If `rounded` is set, `z` has the
+/// relative error of at most `2^-wp`.
+unsafe fn normal_tail(z: &BigFloat, rounded: bool, wp: i64) -> Approx {
+    let (mut t, mut r) = (working(wp), working(wp));
+    mpfr_set_ui(&mut r.value, 2, MPFR_RNDN);
+    mpfr_sqrt(&mut r.value, &r.value, MPFR_RNDN);
+    mpfr_div(&mut t.value, &z.value, &r.value, MPFR_RNDN);
+    // The relative error δ < 2^(1 - wp) of t changes erfc(t) by the relative error of about
+    // δ t |erfc'(t)| / erfc(t) < δ t (t + √(t² + 2)) <= δ (2t² + 2) < 2^(c + 1 - wp)
+    let c = if exp_of(&t) > 0 { 2 * exp_of(&t) + 2 } else { 2 };
+    if c + 4 - wp >= -2 {
+        return retry(wp);
+    }
+    mpfr_erfc(&mut r.value, &t.value, MPFR_RNDN);
+    if mpfr_zero_p(&r.value) != 0 {
+        return Approx::Underflow;
+    }
+    mpfr_div_2ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+    // Twice the first order term and the rounding of erfc
+    let mut err = exp_of(&r) + c + 3 - wp;
+    if rounded {
+        // |Q(z) - Q(z')| <= φ(ξ) |z - z'| < 2 φ(z) z 2^-wp < e^(-z²/2) z 2^-wp, since φ changes
+        // by less than twice while z² 2^-wp is small
+        let mut g = working(64);
+        mpfr_sqr(&mut g.value, &z.value, MPFR_RNDD);
+        mpfr_div_2ui(&mut g.value, &g.value, 1, MPFR_RNDN);
+        mpfr_neg(&mut g.value, &g.value, MPFR_RNDN);
+        mpfr_exp(&mut g.value, &g.value, MPFR_RNDU);
+        err = cmp::max(err, exp_of(&g) + exp_of(z) - wp) + 1;
+    }
+    Approx::Within(r, err)
+}
+
+unsafe fn normal_dist(rop: mpfr_ptr, d: &Normal, x: mpfr_srcptr, upper: bool,
+                      rnd: mpfr_rnd_t) -> c_int {
+    if !normal_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return mpfr_set_ui(rop, if (mpfr_sgn(x) > 0) != upper { 1 } else { 0 }, rnd);
+    }
+    let dx = exact_sub(x, &d.mean.value);
+    if mpfr_zero_p(&dx.value) != 0 {
+        return mpfr_set_ui_2exp(rop, 1, -1, rnd);
+    }
+    // The lower tail at z is the upper one at -z
+    let direct = upper == (mpfr_sgn(&dx.value) > 0);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        let mut z = working(wp);
+        let rounded = mpfr_div(&mut z.value, &dx.value, &d.sd.value, MPFR_RNDN) != 0;
+        mpfr_abs(&mut z.value, &z.value, MPFR_RNDN);
+        let r = normal_tail(&z, rounded, wp);
+        if direct { r } else { complement(r, wp) }
+    }))
+}
+
+unsafe fn normal_quantile(rop: mpfr_ptr, d: &Normal, p: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !normal_valid(d) || !probability(p) {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(p) != 0 || mpfr_cmp_ui(p, 1) == 0 {
+        mpfr_set_inf(rop, if mpfr_zero_p(p) != 0 { -1 } else { 1 });
+        return 0;
+    }
+    if is_half(p) {
+        return mpfr_set(rop, &d.mean.value, rnd);
+    }
+    let mut p2 = copy(p);
+    mpfr_mul_2ui(&mut p2.value, &p2.value, 1, MPFR_RNDN);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        // x = μ - σ √2 erfc⁻¹(2p), where the product has four roundings
+        let (mut m, mut t) = (working(wp), working(wp));
+        erfc_inv(&mut m.value, &p2.value, MPFR_RNDN);
+        mpfr_set_ui(&mut t.value, 2, MPFR_RNDN);
+        mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_mul(&mut m.value, &m.value, &t.value, MPFR_RNDN);
+        mpfr_mul(&mut m.value, &m.value, &d.sd.value, MPFR_RNDN);
+        let mut s = Sum::new(wp);
+        s.add(&d.mean, 0);
+        s.sub(&m, 4);
+        s.approx()
+    }))
+}
+
+// Gamma and chi-squared distributions
+
+unsafe fn gamma_valid(d: &Gamma) -> bool {
+    positive(&d.shape.value) && positive(&d.scale.value)
+}
+
+/// The logarithm of the density of the gamma distribution at `x > 0`,
+/// `(k - 1) ln x - x / θ - ln Γ(k) - k ln θ`.
+unsafe fn gamma_log_density(k: &BigFloat, theta: &BigFloat, x: &BigFloat, wp: i64) -> Sum {
+    let k1 = exact_sub(&k.value, &integer(1).value);
+    let mut l = Sum::new(wp);
+    let mut t = working(wp);
+    mpfr_log(&mut t.value, &x.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &k1.value, MPFR_RNDN);
+    l.add(&t, 2);
+    mpfr_div(&mut t.value, &x.value, &theta.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    mpfr_lngamma(&mut t.value, &k.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    mpfr_log(&mut t.value, &theta.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &k.value, MPFR_RNDN);
+    l.sub(&t, 2);
+    l
+}
+
+unsafe fn gamma_pdf(rop: mpfr_ptr, d: &Gamma, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !gamma_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_sgn(x) < 0 || mpfr_inf_p(x) != 0 {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    if mpfr_zero_p(x) != 0 {
+        return match mpfr_cmp_ui(&d.shape.value, 1) {
+            c if c < 0 => {
+                mpfr_set_inf(rop, 1);
+                0
+            }
+            0 => mpfr_ui_div(rop, 1, &d.scale.value, rnd),
+            _ => mpfr_set_ui(rop, 0, rnd)
+        };
+    }
+    let xb = copy(x);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        exp_approx(gamma_log_density(&d.shape, &d.scale, &xb, wp), wp)
+    }))
+}
+
+/// The lower regularized incomplete gamma function `P(a, y)` for `0 < y < a + 1` by the
+/// series `y^a e^-y / Γ(a + 1) Σ y^n / ((a + 1) ... (a + n))`. The ratios `y / (a + n + 1)`
+/// of the terms decrease and are below 1, so the rest after the `n`-th term is below it times
+/// `ρ / (1 - ρ) = y / (a + n + 1 - y)`. For large `a` and `y` this takes `O(√(a wp))` terms.
+unsafe fn gamma_lower(a: &BigFloat, y: &BigFloat, wp: i64) -> Approx {
+    let a1 = exact_add(&a.value, &integer(1).value);
+    let mut l = Sum::new(wp);
+    let mut t = working(wp);
+    mpfr_log(&mut t.value, &y.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &a.value, MPFR_RNDN);
+    l.add(&t, 2);
+    l.sub(y, 0);
+    mpfr_lngamma(&mut t.value, &a1.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    let (pref, ep) = match split(exp_approx(l, wp)) {
+        Ok(v) => v,
+        Err(r) => return r
+    };
+
+    let mut s = Sum::new(wp);
+    let (mut term, mut d, mut r) = (working(wp), working(wp), working(wp));
+    mpfr_set_ui(&mut term.value, 1, MPFR_RNDN);
+    s.add(&term, 0);
+    let mut n: u64 = 0;
+    loop {
+        // Each term has the relative error of at most 3n roundings
+        n += 1;
+        mpfr_add_ui(&mut d.value, &a.value, n as c_ulong, MPFR_RNDN);
+        mpfr_mul(&mut term.value, &term.value, &y.value, MPFR_RNDN);
+        mpfr_div(&mut term.value, &term.value, &d.value, MPFR_RNDN);
+        s.add(&term, 3 * n);
+        if exp_of(&term) < exp_of(&s.value) - wp {
+            // An upper bound of y / (a + n + 1 - y)
+            mpfr_add_ui(&mut r.value, &a.value, n as c_ulong + 1, MPFR_RNDD);
+            mpfr_sub(&mut r.value, &r.value, &y.value, MPFR_RNDD);
+            if mpfr_sgn(&r.value) > 0 {
+                mpfr_div(&mut r.value, &y.value, &r.value, MPFR_RNDU);
+                let rest = exp_of(&term) + exp_of(&r) + 1;
+                if rest < exp_of(&s.value) - wp {
+                    s.add_error(rest);
+                    break;
+                }
+            }
+        }
+    }
+    let es = s.err();
+    let (r, e) = mul_approx(&pref, ep, &s.value, es, wp);
+    Approx::Within(r, e)
+}
+
+/// The upper regularized incomplete gamma function `Q(a, y) = Γ(a, y) / Γ(a)` for
+/// `y >= a + 1`, by the expansion above where it converges, and otherwise (for small `y`)
+/// as `e^(ln Γ(a, y) - ln Γ(a))`.
+unsafe fn gamma_upper(a: &BigFloat, y: &BigFloat, wp: i64) -> Approx {
+    if let Some(r) = gamma_upper_expansion(a, y, true, wp) {
+        return r;
+    }
+    let mut g = working(wp);
+    mpfr_gamma_inc(&mut g.value, &a.value, &y.value, MPFR_RNDN);
+    if mpfr_regular_p(&g.value) == 0 {
+        // Out of the extended exponent range
+        mpfr_set_nan(&mut g.value);
+        return Approx::Within(g, 0);
+    }
+    let mut l = Sum::new(wp);
+    mpfr_log(&mut g.value, &g.value, MPFR_RNDN);
+    // The relative error of Γ(a, y) is the absolute one of its logarithm
+    l.add(&g, 1);
+    l.add_error(1 - wp);
+    mpfr_lngamma(&mut g.value, &a.value, MPFR_RNDN);
+    l.sub(&g, 1);
+    exp_approx(l, wp)
+}
+
+/// `P(a, y)`, or `Q(a, y)` if `upper` is set, for `a > 0` and `y > 0`. If `rounded` is set,
+/// `y` has the relative error of at most `2^-wp`.
+unsafe fn gamma_tail(a: &BigFloat, y: &BigFloat, rounded: bool, upper: bool, wp: i64) -> Approx {
+    let a1 = exact_add(&a.value, &integer(1).value);
+    let series = mpfr_cmp(&y.value, &a1.value) < 0;
+    let mut r = if series { gamma_lower(a, y, wp) } else { gamma_upper(a, y, wp) };
+    if rounded {
+        // |P(a, y) - P(a, y')| <= ξ^(a - 1) e^-ξ / Γ(a) |y - y'| < 2 D 2^-wp for D = y^a e^-y / Γ(a),
+        // since ξ^a e^-ξ changes by less than twice while (a + y) 2^-wp < 1/4
+        if cmp::max(exp_of(a), exp_of(y)) + 3 - wp >= 0 {
+            return retry(wp);
+        }
+        let mut l = Sum::new(wp);
+        let mut t = working(wp);
+        mpfr_log(&mut t.value, &y.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &a.value, MPFR_RNDN);
+        l.add(&t, 2);
+        l.sub(y, 0);
+        mpfr_lngamma(&mut t.value, &a.value, MPFR_RNDN);
+        l.sub(&t, 1);
+        r = match split(exp_approx(l, wp)) {
+            Ok((dd, ed)) => widen(r, cmp::max(exp_of(&dd), ed) + 2 - wp),
+            // The perturbation is then negligible as well
+            Err(Approx::Underflow) => r,
+            Err(_) => return retry(wp)
+        };
+    }
+    if series == upper { complement(r, wp) } else { r }
+}
+
+unsafe fn gamma_dist(rop: mpfr_ptr, d: &Gamma, x: mpfr_srcptr, upper: bool,
+                     rnd: mpfr_rnd_t) -> c_int {
+    if !gamma_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_sgn(x) <= 0 || mpfr_inf_p(x) != 0 {
+        let one = (mpfr_sgn(x) > 0) != upper;
+        return mpfr_set_ui(rop, if one { 1 } else { 0 }, rnd);
+    }
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        let mut y = working(wp);
+        let rounded = mpfr_div(&mut y.value, x, &d.scale.value, MPFR_RNDN) != 0;
+        gamma_tail(&d.shape, &y, rounded, upper, wp)
+    }))
+}
+
+unsafe fn gamma_quantile(rop: mpfr_ptr, d: &Gamma, p: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !gamma_valid(d) || !probability(p) {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(p) != 0 {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    if mpfr_cmp_ui(p, 1) == 0 {
+        mpfr_set_inf(rop, 1);
+        return 0;
+    }
+    let (q, kind) = tail_target(p);
+    let (k, one) = (&d.shape, integer(1));
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        // The root y of the standard distribution, on the log scale for the lower tail
+        let y0 = gamma_start(k, &q, kind);
+        let r = solve(&q, kind, None, kind == Tail::Lower, &y0, wp,
+                      |y: &BigFloat, wp: i64| gamma_tail(k, y, false, kind == Tail::Upper, wp),
+                      |y: &BigFloat, wp: i64| density_of(gamma_log_density(k, &one, y, wp)));
+        match split(r) {
+            Ok((y, e)) => {
+                let (x, err) = mul_approx(&d.scale, ::std::i64::MIN / 2, &y, e, wp);
+                Approx::Within(x, err)
+            }
+            Err(r) => r
+        }
+    }))
+}
+
+/// The starting point for the root of a tail of the standard gamma distribution: from
+/// `P(k, y) ≈ y^k / Γ(k + 1)` for the lower one and `Q(k, y) ≈ e^(k - y)` for the upper one.
+unsafe fn gamma_start(k: &BigFloat, q: &BigFloat, kind: Tail) -> BigFloat {
+    let (mut y, mut t) = (working(64), working(64));
+    mpfr_log(&mut y.value, &q.value, MPFR_RNDN);
+    if kind == Tail::Lower {
+        mpfr_add_ui(&mut t.value, &k.value, 1, MPFR_RNDN);
+        mpfr_lngamma(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_add(&mut y.value, &y.value, &t.value, MPFR_RNDN);
+        mpfr_div(&mut y.value, &y.value, &k.value, MPFR_RNDN);
+        mpfr_exp(&mut y.value, &y.value, MPFR_RNDN);
+        if mpfr_cmp(&y.value, &k.value) > 0 {
+            mpfr_set(&mut y.value, &k.value, MPFR_RNDN);
+        }
+    } else {
+        mpfr_sub(&mut y.value, &k.value, &y.value, MPFR_RNDN);
+    }
+    y
+}
+
+unsafe fn chi_squared_pdf(rop: mpfr_ptr, d: &ChiSquared, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    gamma_pdf(rop, &d.gamma(), x, rnd)
+}
+
+unsafe fn chi_squared_dist(rop: mpfr_ptr, d: &ChiSquared, x: mpfr_srcptr, upper: bool,
+                           rnd: mpfr_rnd_t) -> c_int {
+    gamma_dist(rop, &d.gamma(), x, upper, rnd)
+}
+
+unsafe fn chi_squared_quantile(rop: mpfr_ptr, d: &ChiSquared, p: mpfr_srcptr,
+                               rnd: mpfr_rnd_t) -> c_int {
+    gamma_quantile(rop, &d.gamma(), p, rnd)
+}
+
+// Beta distribution
+
+unsafe fn beta_valid(d: &Beta) -> bool {
+    positive(&d.alpha.value) && positive(&d.beta.value)
+}
+
+/// The logarithm of the density of the Beta distribution at `0 < x < 1`,
+/// `(a - 1) ln x + (b - 1) ln(1 - x) - ln Γ(a) - ln Γ(b) + ln Γ(a + b)`.
+unsafe fn beta_log_density(a: &BigFloat, b: &BigFloat, x: &BigFloat, wp: i64) -> Sum {
+    let one = integer(1);
+    let (a1, b1) = (exact_sub(&a.value, &one.value), exact_sub(&b.value, &one.value));
+    let y = exact_sub(&one.value, &x.value);
+    let ab = exact_add(&a.value, &b.value);
+    let mut l = Sum::new(wp);
+    let mut t = working(wp);
+    mpfr_log(&mut t.value, &x.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &a1.value, MPFR_RNDN);
+    l.add(&t, 2);
+    mpfr_log(&mut t.value, &y.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &b1.value, MPFR_RNDN);
+    l.add(&t, 2);
+    mpfr_lngamma(&mut t.value, &a.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    mpfr_lngamma(&mut t.value, &b.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    mpfr_lngamma(&mut t.value, &ab.value, MPFR_RNDN);
+    l.add(&t, 1);
+    l
+}
+
+// The Beta distributions with an integer parameter are computed exactly only for the
+// parameters up to this one, beyond which the exact values get too long
+const BETA_INTEGER_MAX: c_ulong = 256;
+
+/// Returns `x` if it is a positive integer not greater than `BETA_INTEGER_MAX`.
+unsafe fn small_integer(x: &BigFloat) -> Option<c_ulong> {
+    if mpfr_integer_p(&x.value) != 0 && mpfr_sgn(&x.value) > 0 &&
+       mpfr_cmp_ui(&x.value, BETA_INTEGER_MAX) <= 0 {
+        Some(mpfr_get_ui(&x.value, MPFR_RNDN))
+    } else {
+        None
+    }
+}
+
+/// Returns `y^c` for `y > 0` and `c > 0` if it is dyadic. With `y = o 2^e` for an odd `o`,
+/// `y^c` is dyadic only if `o^c` is an integer, which then has at most `c` times as many bits
+/// as `o`; powers longer than `2^24` bits are not considered. For `c = m / 2^k` with an odd `m`,
+/// `o^c` is an integer only if `o^(1 / 2^k)` is, which is checked first with square roots.
+unsafe fn dyadic_power(y: &BigFloat, c: &BigFloat) -> Option<BigFloat> {
+    let k = mpfr_min_prec(&c.value) as i64 - mpfr_get_exp(&c.value) as i64;
+    let mut r = copy(&y.value);
+    for _ in 0..k {
+        if mpfr_sqrt(&mut r.value, &r.value, MPFR_RNDN) != 0 {
+            return None;
+        }
+    }
+    let bits = mpfr_min_prec(&y.value) as f64;
+    let need = if bits <= 1.0 { 2.0 } else { (mpfr_get_d(&c.value, MPFR_RNDU) * bits).ceil() + 2.0 };
+    if !(need <= (1u64 << 24) as f64) {
+        return None;
+    }
+    let mut w = working(need as i64);
+    if mpfr_pow(&mut w.value, &y.value, &c.value, MPFR_RNDN) == 0 {
+        Some(w)
+    } else {
+        None
+    }
+}
+
+/// A function of the Beta distribution: the density or one of the tails.
+#[derive(Copy, PartialEq, Eq)]
+enum Function {
+    Density,
+    Lower,
+    Upper
+}
+
+/// The exact value of a function of the Beta distribution at `0 < x < 1` as a quotient of
+/// dyadic numbers, if one of the parameters is a small integer and the value is rational.
+/// For the integer `a = n` (the other one follows from the symmetry),
+///
+/// ```text
+/// f(x) = (b)_n / (n - 1)! x^(n - 1) (1 - x)^(b - 1)
+/// 1 - I_x(n, b) = (1 - x)^b Σ_(j < n) (b)_j / j! x^j
+/// ```
+unsafe fn beta_exact(x: &BigFloat, a: &BigFloat, b: &BigFloat, f: Function)
+                     -> Option<(BigFloat, BigFloat)> {
+    let one = integer(1);
+    let y = exact_sub(&one.value, &x.value);
+    let (x, y, n, b, f) = match (small_integer(a), small_integer(b)) {
+        (Some(n), _) => (x, &y, n, b, f),
+        (None, Some(n)) => {
+            let f = match f {
+                Function::Lower => Function::Upper,
+                Function::Upper => Function::Lower,
+                f => f
+            };
+            (&y, x, n, a, f)
+        }
+        (None, None) => return None
+    };
+    // (n - 1)! has less than 8 bits per factor
+    let mut fact = working(8 * n as i64 + 64);
+    mpfr_fac_ui(&mut fact.value, n - 1, MPFR_RNDN);
+    if f == Function::Density {
+        let c = exact_sub(&b.value, &one.value);
+        let mut num = one.clone();
+        for i in 0..n {
+            let bi = exact_add(&b.value, &integer(i as c_long).value);
+            num = exact_mul(&num.value, &bi.value);
+            if i + 1 < n {
+                num = exact_mul(&num.value, &x.value);
+            }
+        }
+        return match mpfr_sgn(&c.value) {
+            0 => Some((num, fact)),
+            s if s > 0 => dyadic_power(y, &c).map(|w| (exact_mul(&num.value, &w.value), fact)),
+            _ => {
+                let c = exact_sub(&one.value, &b.value);
+                dyadic_power(y, &c).map(|w| (num, exact_mul(&fact.value, &w.value)))
+            }
+        };
+    }
+    let w = match dyadic_power(y, b) {
+        Some(w) => w,
+        None => return None
+    };
+    // Σ (b)_j x^j (n - 1)! / j!, with the integer factors from the largest j
+    let mut terms = Vec::with_capacity(n as usize);
+    let mut t = one.clone();
+    for j in 0..n {
+        terms.push(t.clone());
+        let bj = exact_add(&b.value, &integer(j as c_long).value);
+        t = exact_mul(&t.value, &bj.value);
+        t = exact_mul(&t.value, &x.value);
+    }
+    let (mut s, mut m) = (integer(0), one.clone());
+    for j in (0..n).rev() {
+        s = exact_add(&s.value, &exact_mul(&terms[j as usize].value, &m.value).value);
+        if j > 0 {
+            m = exact_mul(&m.value, &integer(j as c_long).value);
+        }
+    }
+    let num = exact_mul(&w.value, &s.value);
+    if f == Function::Upper {
+        Some((num, fact))
+    } else {
+        Some((exact_sub(&fact.value, &num.value), fact))
+    }
+}
+
+/// The regularized incomplete beta function `I_s(p, q)` for `0 < s < 1` by the series
+/// `s^p (1 - s)^q / (p B(p, q)) Σ t_n` with `t_0 = 1` and `t_(n + 1) = t_n s (p + q + n) /
+/// (p + n + 1)`. The ratios of the terms tend to `s` monotonically, so the rest after the term
+/// `t_n` is below `t_n ρ / (1 - ρ)` for `ρ = max(t_(n + 1) / t_n, s)`. Also returns the exponent
+/// of a bound of the prefactor.
+unsafe fn beta_series(s: &BigFloat, p: &BigFloat, q: &BigFloat, wp: i64) -> (Approx, i64) {
+    let one = integer(1);
+    let c = exact_sub(&one.value, &s.value);
+    let p1 = exact_add(&p.value, &one.value);
+    let pq = exact_add(&p.value, &q.value);
+    let mut l = Sum::new(wp);
+    let mut t = working(wp);
+    mpfr_log(&mut t.value, &s.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &p.value, MPFR_RNDN);
+    l.add(&t, 2);
+    mpfr_log(&mut t.value, &c.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &q.value, MPFR_RNDN);
+    l.add(&t, 2);
+    mpfr_lngamma(&mut t.value, &pq.value, MPFR_RNDN);
+    l.add(&t, 1);
+    mpfr_lngamma(&mut t.value, &p1.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    mpfr_lngamma(&mut t.value, &q.value, MPFR_RNDN);
+    l.sub(&t, 1);
+    let (pref, ep) = match split(exp_approx(l, wp)) {
+        Ok(v) => v,
+        Err(r) => return (r, ::std::i64::MIN / 2)
+    };
+
+    let mut sum = Sum::new(wp);
+    let (mut term, mut u, mut v) = (working(wp), working(wp), working(wp));
+    let (mut rho, mut w) = (working(64), working(64));
+    mpfr_set_ui(&mut term.value, 1, MPFR_RNDN);
+    sum.add(&term, 0);
+    let mut n: u64 = 0;
+    loop {
+        // An upper bound of ρ
+        mpfr_add_ui(&mut rho.value, &pq.value, n as c_ulong, MPFR_RNDU);
+        mpfr_mul(&mut rho.value, &rho.value, &s.value, MPFR_RNDU);
+        mpfr_add_ui(&mut w.value, &p1.value, n as c_ulong, MPFR_RNDD);
+        mpfr_div(&mut rho.value, &rho.value, &w.value, MPFR_RNDU);
+        if mpfr_cmp(&rho.value, &s.value) < 0 {
+            mpfr_set(&mut rho.value, &s.value, MPFR_RNDU);
+        }
+        mpfr_ui_sub(&mut w.value, 1, &rho.value, MPFR_RNDD);
+        if mpfr_sgn(&w.value) > 0 {
+            let rest = exp_of(&term) + exp_of(&rho) - exp_of(&w) + 1;
+            if rest < exp_of(&sum.value) - wp {
+                sum.add_error(rest);
+                break;
+            }
+        }
+        // Each term has the relative error of at most 5n roundings
+        mpfr_add_ui(&mut u.value, &pq.value, n as c_ulong, MPFR_RNDN);
+        mpfr_add_ui(&mut v.value, &p1.value, n as c_ulong, MPFR_RNDN);
+        mpfr_mul(&mut term.value, &term.value, &s.value, MPFR_RNDN);
+        mpfr_mul(&mut term.value, &term.value, &u.value, MPFR_RNDN);
+        mpfr_div(&mut term.value, &term.value, &v.value, MPFR_RNDN);
+        n += 1;
+        sum.add(&term, 5 * n);
+    }
+    let es = sum.err();
+    let (r, e) = mul_approx(&pref, ep, &sum.value, es, wp);
+    (Approx::Within(r, e), cmp::max(exp_of(&pref), ep) + 1)
+}
+
+/// `I_x(a, b)`, or `1 - I_x(a, b)` if `upper` is set, for `0 < x < 1` and `y = 1 - x`. If
+/// `rounded` is set, `x` and `y` have the relative errors of at most `2^(2 - wp)`.
+unsafe fn beta_tail(x: &BigFloat, y: &BigFloat, rounded: bool, a: &BigFloat, b: &BigFloat,
+                    upper: bool, wp: i64) -> Approx {
+    // The series converges fast for x < (a + 1) / (a + b + 2), otherwise it is used for
+    // 1 - I_x(a, b) = I_y(b, a)
+    let (mut t, mut u) = (working(64), working(64));
+    mpfr_add(&mut t.value, &a.value, &b.value, MPFR_RNDN);
+    mpfr_add_ui(&mut t.value, &t.value, 2, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &x.value, MPFR_RNDN);
+    mpfr_add_ui(&mut u.value, &a.value, 1, MPFR_RNDN);
+    let flipped = mpfr_cmp(&t.value, &u.value) >= 0;
+    let (s, c, p, q) = if flipped { (y, x, b, a) } else { (x, y, a, b) };
+    let (mut r, ep) = beta_series(s, p, q, wp);
+    if rounded {
+        // |I_s - I_s'| <= ξ^(p - 1) (1 - ξ)^(q - 1) / B(p, q) |s - s'| < 2 pref p / (1 - s) 2^(2 - wp),
+        // since the density changes by less than twice while (|p - 1| + |q - 1| s / (1 - s))
+        // 2^(2 - wp) < 1/2
+        let k = cmp::max(cmp::max(exp_of(p), exp_of(q)), 1);
+        if k + 5 - exp_of(c) - wp >= -1 {
+            return retry(wp);
+        }
+        r = widen(r, ep + exp_of(p) - exp_of(c) + 4 - wp);
+    }
+    if flipped != upper { complement(r, wp) } else { r }
+}
+
+unsafe fn beta_pdf(rop: mpfr_ptr, d: &Beta, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !beta_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_sgn(x) < 0 || mpfr_cmp_ui(x, 1) > 0 {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    if mpfr_zero_p(x) != 0 || mpfr_cmp_ui(x, 1) == 0 {
+        // The density at the ends depends on the exponent of x (or 1 - x) there
+        let (p, q) = if mpfr_zero_p(x) != 0 { (&d.alpha, &d.beta) } else { (&d.beta, &d.alpha) };
+        return match mpfr_cmp_ui(&p.value, 1) {
+            c if c < 0 => {
+                mpfr_set_inf(rop, 1);
+                0
+            }
+            0 => mpfr_set(rop, &q.value, rnd),
+            _ => mpfr_set_ui(rop, 0, rnd)
+        };
+    }
+    let xb = copy(x);
+    extended(rop, rnd, || {
+        if let Some((num, den)) = beta_exact(&xb, &d.alpha, &d.beta, Function::Density) {
+            return mpfr_div(rop, &num.value, &den.value, rnd);
+        }
+        round_with(rop, rnd, |wp| exp_approx(beta_log_density(&d.alpha, &d.beta, &xb, wp), wp))
+    })
+}
+
+unsafe fn beta_dist(rop: mpfr_ptr, d: &Beta, x: mpfr_srcptr, upper: bool,
+                    rnd: mpfr_rnd_t) -> c_int {
+    if !beta_valid(d) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_sgn(x) <= 0 || mpfr_cmp_ui(x, 1) >= 0 {
+        let one = (mpfr_sgn(x) > 0) != upper;
+        return mpfr_set_ui(rop, if one { 1 } else { 0 }, rnd);
+    }
+    if is_half(x) && mpfr_equal_p(&d.alpha.value, &d.beta.value) != 0 {
+        return mpfr_set_ui_2exp(rop, 1, -1, rnd);
+    }
+    let xb = copy(x);
+    let y = exact_sub(&integer(1).value, x);
+    extended(rop, rnd, || {
+        let f = if upper { Function::Upper } else { Function::Lower };
+        if let Some((num, den)) = beta_exact(&xb, &d.alpha, &d.beta, f) {
+            return mpfr_div(rop, &num.value, &den.value, rnd);
+        }
+        round_with(rop, rnd, |wp| beta_tail(&xb, &y, false, &d.alpha, &d.beta, upper, wp))
+    })
+}
+
+unsafe fn beta_quantile(rop: mpfr_ptr, d: &Beta, p: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !beta_valid(d) || !probability(p) {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(p) != 0 || mpfr_cmp_ui(p, 1) == 0 {
+        return mpfr_set(rop, p, rnd);
+    }
+    if is_half(p) && mpfr_equal_p(&d.alpha.value, &d.beta.value) != 0 {
+        return mpfr_set_ui_2exp(rop, 1, -1, rnd);
+    }
+    let (q, kind) = tail_target(p);
+    // The upper tail of Beta(a, b) at x is the lower one of Beta(b, a) at 1 - x
+    let (a, b) = if kind == Tail::Lower { (&d.alpha, &d.beta) } else { (&d.beta, &d.alpha) };
+    let (pb, one) = (copy(p), integer(1));
+    let prec = mpfr_get_prec(rop) as i64 + 1;
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        let x0 = beta_start(a, b, &q);
+        let r = solve(&q, Tail::Lower, Some(1), true, &x0, wp,
+                      |x: &BigFloat, wp: i64| {
+                          let y = exact_sub(&one.value, &x.value);
+                          beta_tail(x, &y, false, a, b, false, wp)
+                      },
+                      |x: &BigFloat, wp: i64| density_of(beta_log_density(a, b, x, wp)));
+        let r = if kind == Tail::Lower { r } else { complement(r, wp) };
+        if let Approx::Within(ref x, _) = r {
+            // The root may be exact if the distribution function is rational there
+            let mut c = working(prec);
+            mpfr_set(&mut c.value, &x.value, MPFR_RNDN);
+            if mpfr_sgn(&c.value) > 0 && mpfr_cmp_ui(&c.value, 1) < 0 {
+                if let Some((num, den)) = beta_exact(&c, &d.alpha, &d.beta, Function::Lower) {
+                    if mpfr_equal_p(&num.value, &exact_mul(&pb.value, &den.value).value) != 0 {
+                        return Approx::Exact(c);
+                    }
+                }
+            }
+        }
+        r
+    }))
+}
+
+/// The starting point for the root of `I_x(a, b) = q`, from `I_x(a, b) ≈ x^a / (a B(a, b))`.
+unsafe fn beta_start(a: &BigFloat, b: &BigFloat, q: &BigFloat) -> BigFloat {
+    let (mut x, mut t) = (working(64), working(64));
+    mpfr_log(&mut x.value, &q.value, MPFR_RNDN);
+    mpfr_log(&mut t.value, &a.value, MPFR_RNDN);
+    mpfr_add(&mut x.value, &x.value, &t.value, MPFR_RNDN);
+    mpfr_lngamma(&mut t.value, &a.value, MPFR_RNDN);
+    mpfr_add(&mut x.value, &x.value, &t.value, MPFR_RNDN);
+    mpfr_lngamma(&mut t.value, &b.value, MPFR_RNDN);
+    mpfr_add(&mut x.value, &x.value, &t.value, MPFR_RNDN);
+    mpfr_add(&mut t.value, &a.value, &b.value, MPFR_RNDN);
+    mpfr_lngamma(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_sub(&mut x.value, &x.value, &t.value, MPFR_RNDN);
+    mpfr_div(&mut x.value, &x.value, &a.value, MPFR_RNDN);
+    mpfr_exp(&mut x.value, &x.value, MPFR_RNDN);
+    // Not beyond the mean
+    mpfr_add(&mut t.value, &a.value, &b.value, MPFR_RNDN);
+    mpfr_div(&mut t.value, &a.value, &t.value, MPFR_RNDN);
+    if mpfr_cmp(&x.value, &t.value) > 0 {
+        mpfr_set(&mut x.value, &t.value, MPFR_RNDN);
+    }
+    x
+}
+
+// Student's t-distribution
+
+/// The logarithm of the density of Student's t-distribution,
+/// `ln Γ((ν + 1)/2) - ln Γ(ν/2) - ln(νπ)/2 - (ν + 1)/2 ln(1 + t²/ν)`.
+unsafe fn student_log_density(nu: &BigFloat, t: &BigFloat, wp: i64) -> Sum {
+    let mut n1 = exact_add(&nu.value, &integer(1).value);
+    mpfr_div_2ui(&mut n1.value, &n1.value, 1, MPFR_RNDN);
+    let mut h = copy(&nu.value);
+    mpfr_div_2ui(&mut h.value, &h.value, 1, MPFR_RNDN);
+    let mut l = Sum::new(wp);
+    let mut s = working(wp);
+    mpfr_lngamma(&mut s.value, &n1.value, MPFR_RNDN);
+    l.add(&s, 1);
+    mpfr_lngamma(&mut s.value, &h.value, MPFR_RNDN);
+    l.sub(&s, 1);
+    // The relative error of νπ is the absolute one of its logarithm
+    mpfr_const_pi(&mut s.value, MPFR_RNDN);
+    mpfr_mul(&mut s.value, &s.value, &nu.value, MPFR_RNDN);
+    mpfr_log(&mut s.value, &s.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut s.value, &s.value, 1, MPFR_RNDN);
+    l.sub(&s, 1);
+    l.add_error(1 - wp);
+    mpfr_sqr(&mut s.value, &t.value, MPFR_RNDN);
+    mpfr_div(&mut s.value, &s.value, &nu.value, MPFR_RNDN);
+    mpfr_log1p(&mut s.value, &s.value, MPFR_RNDN);
+    mpfr_mul(&mut s.value, &s.value, &n1.value, MPFR_RNDN);
+    l.sub(&s, 4);
+    l
+}
+
+unsafe fn student_pdf(rop: mpfr_ptr, d: &StudentT, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if !positive(&d.df.value) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    let xb = copy(x);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        exp_approx(student_log_density(&d.df, &xb, wp), wp)
+    }))
+}
+
+/// The upper tail `P(T > t) = I_x(ν/2, 1/2) / 2` for `t > 0` and `x = ν / (ν + t²)`.
+unsafe fn student_tail(nu: &BigFloat, t: &BigFloat, wp: i64) -> Approx {
+    // Both x and 1 - x = t² / (ν + t²) have four roundings
+    let (mut t2, mut s, mut x, mut y) = (working(wp), working(wp), working(wp), working(wp));
+    mpfr_sqr(&mut t2.value, &t.value, MPFR_RNDN);
+    mpfr_add(&mut s.value, &nu.value, &t2.value, MPFR_RNDN);
+    mpfr_div(&mut x.value, &nu.value, &s.value, MPFR_RNDN);
+    mpfr_div(&mut y.value, &t2.value, &s.value, MPFR_RNDN);
+    let mut a = copy(&nu.value);
+    mpfr_div_2ui(&mut a.value, &a.value, 1, MPFR_RNDN);
+    let mut b = integer(1);
+    mpfr_div_2ui(&mut b.value, &b.value, 1, MPFR_RNDN);
+    halve(beta_tail(&x, &y, true, &a, &b, false, wp))
+}
+
+unsafe fn student_dist(rop: mpfr_ptr, d: &StudentT, x: mpfr_srcptr, upper: bool,
+                       rnd: mpfr_rnd_t) -> c_int {
+    if !positive(&d.df.value) || mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return mpfr_set_ui(rop, if (mpfr_sgn(x) > 0) != upper { 1 } else { 0 }, rnd);
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set_ui_2exp(rop, 1, -1, rnd);
+    }
+    // The quartiles of the Cauchy distribution
+    if mpfr_cmp_ui(&d.df.value, 1) == 0 && mpfr_cmpabs(x, &integer(1).value) == 0 {
+        let n = if (mpfr_sgn(x) > 0) != upper { 3 } else { 1 };
+        return mpfr_set_ui_2exp(rop, n, -2, rnd);
+    }
+    // The lower tail at t is the upper one at -t
+    let direct = upper == (mpfr_sgn(x) > 0);
+    let mut t = copy(x);
+    mpfr_abs(&mut t.value, &t.value, MPFR_RNDN);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        let r = student_tail(&d.df, &t, wp);
+        if direct { r } else { complement(r, wp) }
+    }))
+}
+
+unsafe fn student_quantile(rop: mpfr_ptr, d: &StudentT, p: mpfr_srcptr,
+                           rnd: mpfr_rnd_t) -> c_int {
+    let nu = &d.df;
+    if !positive(&nu.value) || !probability(p) {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(p) != 0 || mpfr_cmp_ui(p, 1) == 0 {
+        mpfr_set_inf(rop, if mpfr_zero_p(p) != 0 { -1 } else { 1 });
+        return 0;
+    }
+    if is_half(p) {
+        return mpfr_set_ui(rop, 0, rnd);
+    }
+    if mpfr_cmp_ui(&nu.value, 1) == 0 {
+        if mpfr_cmp_ui_2exp(p, 1, -2) == 0 {
+            return mpfr_set_si(rop, -1, rnd);
+        }
+        if mpfr_cmp_ui_2exp(p, 3, -2) == 0 {
+            return mpfr_set_si(rop, 1, rnd);
+        }
+    }
+    let (q, kind) = tail_target(p);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        // The root of P(T > t) = q for t > 0, from P(T > t) ≈ t^-ν
+        let mut t0 = working(64);
+        mpfr_log(&mut t0.value, &q.value, MPFR_RNDN);
+        mpfr_div(&mut t0.value, &t0.value, &nu.value, MPFR_RNDN);
+        mpfr_neg(&mut t0.value, &t0.value, MPFR_RNDN);
+        mpfr_exp(&mut t0.value, &t0.value, MPFR_RNDN);
+        if mpfr_cmp_ui(&t0.value, 1) < 0 {
+            mpfr_set_ui(&mut t0.value, 1, MPFR_RNDN);
+        }
+        let r = solve(&q, Tail::Upper, None, true, &t0, wp,
+                      |t: &BigFloat, wp: i64| student_tail(nu, t, wp),
+                      |t: &BigFloat, wp: i64| density_of(student_log_density(nu, t, wp)));
+        if kind == Tail::Lower { negate(r) } else { r }
+    }))
+}
+
+// Poisson distribution
+
+unsafe fn poisson_pmf(rop: mpfr_ptr, d: &Poisson, k: u64, rnd: mpfr_rnd_t) -> c_int {
+    let lambda = &d.lambda;
+    if !positive(&lambda.value) {
+        return set_nan(rop);
+    }
+    let mut k1 = working(65);
+    mpfr_set_ui(&mut k1.value, k as c_ulong, MPFR_RNDN);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        // ln p = k ln λ - λ - ln Γ(k + 1)
+        let mut l = Sum::new(wp);
+        let mut t = working(wp);
+        mpfr_log(&mut t.value, &lambda.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &k1.value, MPFR_RNDN);
+        l.add(&t, 2);
+        l.sub(lambda, 0);
+        mpfr_add_ui(&mut t.value, &k1.value, 1, MPFR_RNDN);
+        mpfr_lngamma(&mut t.value, &t.value, MPFR_RNDN);
+        l.sub(&t, 2);
+        exp_approx(l, wp)
+    }))
+}
+
+unsafe fn poisson_dist(rop: mpfr_ptr, d: &Poisson, k: u64, upper: bool, rnd: mpfr_rnd_t) -> c_int {
+    if !positive(&d.lambda.value) {
+        return set_nan(rop);
+    }
+    // P(X <= k) = Q(k + 1, λ)
+    let mut k1 = working(65);
+    mpfr_set_ui(&mut k1.value, k as c_ulong, MPFR_RNDN);
+    mpfr_add_ui(&mut k1.value, &k1.value, 1, MPFR_RNDN);
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        gamma_tail(&k1, &d.lambda, false, !upper, wp)
+    }))
+}
+
+/// Compares `P(X <= k)` with `p`, increasing the working precision until the comparison is
+/// certain. The distribution function has no dyadic values, so the loop terminates.
+unsafe fn poisson_cdf_cmp(d: &Poisson, k: u64, p: mpfr_srcptr) -> Ordering {
+    let mut k1 = working(65);
+    mpfr_set_ui(&mut k1.value, k as c_ulong, MPFR_RNDN);
+    mpfr_add_ui(&mut k1.value, &k1.value, 1, MPFR_RNDN);
+    let mut wp = mpfr_get_prec(p) as i64 + 32;
+    with_extended_range(|| loop {
+        match gamma_tail(&k1, &d.lambda, false, true, wp) {
+            Approx::Exact(v) => return mpfr_cmp(&v.value, p).cmp(&0),
+            Approx::Within(v, err) => {
+                if let Some(o) = certain_sign(&v, err, p, ::std::i64::MIN / 2, wp) {
+                    return o;
+                }
+            }
+            Approx::Underflow => return Ordering::Less,
+            Approx::Below(v) => {
+                let err = exp_of(&v) + 1 - wp;
+                if let Some(o) = certain_sign(&v, err, p, ::std::i64::MIN / 2, wp) {
+                    return o;
+                }
+            }
+        }
+        wp += wp / 2;
+    })
+}
+
+unsafe fn poisson_quantile(d: &Poisson, p: mpfr_srcptr) -> Option<u64> {
+    if !positive(&d.lambda.value) || !probability(p) || mpfr_cmp_ui(p, 1) == 0 {
+        return None;
+    }
+    if mpfr_zero_p(p) != 0 {
+        return Some(0);
+    }
+    let enough = |k: u64| poisson_cdf_cmp(d, k, p) != Ordering::Less;
+
+    // Start from the normal approximation λ - √(2λ) erfc⁻¹(2p)
+    let (mut g, mut t) = (working(64), working(64));
+    mpfr_mul_2ui(&mut t.value, p, 1, MPFR_RNDN);
+    erfc_inv(&mut g.value, &t.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut t.value, &d.lambda.value, 1, MPFR_RNDN);
+    mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut g.value, &g.value, &t.value, MPFR_RNDN);
+    mpfr_sub(&mut g.value, &d.lambda.value, &g.value, MPFR_RNDN);
+    let k = if mpfr_sgn(&g.value) <= 0 {
+        0
+    } else if mpfr_cmp_ui(&g.value, ::std::u64::MAX as c_ulong) >= 0 {
+        ::std::u64::MAX
+    } else {
+        mpfr_get_ui(&g.value, MPFR_RNDD) as u64
+    };
+
+    // Find lo < hi such that lo is not enough and hi is, with steps growing twice each
+    let mut lo;
+    let mut hi;
+    let mut step = 1u64;
+    if enough(k) {
+        hi = k;
+        loop {
+            if hi == 0 {
+                return Some(0);
+            }
+            lo = hi.saturating_sub(step);
+            if !enough(lo) {
+                break;
+            }
+            hi = lo;
+            step = step.saturating_mul(2);
+        }
+    } else {
+        lo = k;
+        loop {
+            if lo == ::std::u64::MAX {
+                return None;
+            }
+            hi = lo.saturating_add(step);
+            if enough(hi) {
+                break;
+            }
+            lo = hi;
+            step = step.saturating_mul(2);
+        }
+    }
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if enough(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Some(hi)
+}
+
+// Quantiles
+
+/// A tail of a distribution as a function of its positive variable.
+#[derive(Copy, PartialEq, Eq)]
+enum Tail {
+    /// The increasing `P(X <= x)`.
+    Lower,
+    /// The decreasing `P(X > x)`.
+    Upper
+}
+
+/// Returns the smaller of `p` and `1 - p` for a probability `0 < p < 1`, with the tail of
+/// the distribution it belongs to.
+unsafe fn tail_target(p: mpfr_srcptr) -> (BigFloat, Tail) {
+    if mpfr_cmp_ui_2exp(p, 1, -1) <= 0 {
+        (copy(p), Tail::Lower)
+    } else {
+        (exact_sub(&integer(1).value, p), Tail::Upper)
+    }
+}
+
+/// The value of a density from its logarithm, for Newton's iteration.
+unsafe fn density_of(l: Sum) -> BigFloat {
+    let mut r = l.value;
+    mpfr_exp(&mut r.value, &r.value, MPFR_RNDN);
+    r
+}
+
+/// Sets `t` to a point inside the bracket `(lo, up)` of a positive root, where a missing
+/// lower end is 0 and a missing upper one is +∞; `n` counts the steps towards a missing end.
+unsafe fn bisect(t: &mut BigFloat, lo: &Option<BigFloat>, up: &Option<BigFloat>,
+                 log_scale: bool, n: u32) {
+    let big = 1 << cmp::min(n, 20);
+    match (lo, up) {
+        (&Some(ref a), &Some(ref b)) => {
+            if log_scale {
+                mpfr_mul(&mut t.value, &a.value, &b.value, MPFR_RNDN);
+                mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+            } else {
+                mpfr_add(&mut t.value, &a.value, &b.value, MPFR_RNDN);
+                mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            }
+        }
+        (&None, &Some(ref b)) => {
+            mpfr_div_2ui(&mut t.value, &b.value, if log_scale { big } else { 1 }, MPFR_RNDN);
+        }
+        (&Some(ref a), &None) => {
+            if log_scale {
+                mpfr_mul_2ui(&mut t.value, &a.value, big, MPFR_RNDN);
+            } else {
+                mpfr_mul_2ui(&mut t.value, &a.value, 1, MPFR_RNDN);
+                mpfr_add_ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            }
+        }
+        (&None, &None) => unreachable!()
+    }
+}
+
+/// Newton's iteration for `ln T(x) = ln q` at the precision `p`, starting from `x`, or for
+/// `ln x` if `log_scale` is set. The iterates are kept inside the bracket of the root found
+/// so far, where `hi` bounds the support. Returns the last correction.
+unsafe fn bracketed_newton<T, D>(x: &mut BigFloat, q: &BigFloat, kind: Tail, hi: Option<c_long>,
+                                 log_scale: bool, p: i64, tail: &mut T, density: &mut D)
+                                 -> Option<BigFloat>
+    where T: FnMut(&BigFloat, i64) -> Approx, D: FnMut(&BigFloat, i64) -> BigFloat
+{
+    let (mut lo, mut up): (Option<BigFloat>, Option<BigFloat>) = (None, hi.map(|b| integer(b)));
+    let mut lq = working(p);
+    mpfr_log(&mut lq.value, &q.value, MPFR_RNDN);
+    let mut expansions = 0;
+    let zero = integer(0);
+    newton(x, p, Some(&zero), None, |x| {
+        let v = match tail(x, p) {
+            Approx::Exact(v) | Approx::Within(v, _) | Approx::Below(v) => v,
+            Approx::Underflow => integer(0)
+        };
+        if (kind == Tail::Lower) == (mpfr_cmp(&v.value, &q.value) < 0) {
+            lo = Some(x.clone());
+        } else {
+            up = Some(x.clone());
+        }
+        // The correction (ln T(x) - ln q) T(x) / T'(x)
+        let (mut t, mut d) = (working(p), working(p));
+        let f = density(x, p);
+        let step = mpfr_regular_p(&v.value) != 0 && mpfr_regular_p(&f.value) != 0;
+        if step {
+            mpfr_log(&mut d.value, &v.value, MPFR_RNDN);
+            mpfr_sub(&mut d.value, &d.value, &lq.value, MPFR_RNDN);
+            mpfr_mul(&mut d.value, &d.value, &v.value, MPFR_RNDN);
+            mpfr_div(&mut d.value, &d.value, &f.value, MPFR_RNDN);
+            if kind == Tail::Upper {
+                mpfr_neg(&mut d.value, &d.value, MPFR_RNDN);
+            }
+            if log_scale {
+                mpfr_div(&mut t.value, &d.value, &x.value, MPFR_RNDN);
+                mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+                mpfr_exp(&mut t.value, &t.value, MPFR_RNDN);
+                mpfr_mul(&mut t.value, &t.value, &x.value, MPFR_RNDN);
+            } else {
+                mpfr_sub(&mut t.value, &x.value, &d.value, MPFR_RNDN);
+            }
+        }
+        let inside = step && mpfr_number_p(&t.value) != 0 && mpfr_sgn(&t.value) > 0 &&
+            lo.as_ref().map_or(true, |a| mpfr_cmp(&t.value, &a.value) > 0) &&
+            up.as_ref().map_or(true, |b| mpfr_cmp(&t.value, &b.value) < 0);
+        if !inside {
+            bisect(&mut t, &lo, &up, log_scale, expansions);
+            if lo.is_none() || up.is_none() {
+                expansions += 1;
+            }
+        }
+        mpfr_sub(&mut d.value, &x.value, &t.value, MPFR_RNDN);
+        Some(d)
+    })
+}
+
+/// Approximates the positive root of `T(x) = q` for a tail `T` of a distribution and
+/// `0 < q <= 1/2`, where `tail(x, wp)` approximates `T(x)` and `density(x, wp)` approximates
+/// `|T'(x)|`. A rough root is found at a low precision from `x0` and then refined at the
+/// working precision.
+unsafe fn solve<T, D>(q: &BigFloat, kind: Tail, hi: Option<c_long>, log_scale: bool,
+                      x0: &BigFloat, wp: i64, mut tail: T, mut density: D) -> Approx
+    where T: FnMut(&BigFloat, i64) -> Approx, D: FnMut(&BigFloat, i64) -> BigFloat
+{
+    let mut x = working(64);
+    mpfr_set(&mut x.value, &x0.value, MPFR_RNDN);
+    bracketed_newton(&mut x, q, kind, hi, log_scale, 64, &mut tail, &mut density);
+    let mut w = working(wp);
+    mpfr_set(&mut w.value, &x.value, MPFR_RNDN);
+    let d = match bracketed_newton(&mut w, q, kind, hi, log_scale, wp, &mut tail, &mut density) {
+        Some(d) => d,
+        None => return retry(wp)
+    };
+    let err = enclose(&w, &d, wp, |a| {
+        // Out of the support, the lower tail is 0 below it and 1 above it
+        let end = if mpfr_sgn(&a.value) <= 0 {
+            Some(false)
+        } else if hi.map_or(false, |b| mpfr_cmp_si(&a.value, b) >= 0) {
+            Some(true)
+        } else {
+            None
+        };
+        if let Some(above) = end {
+            let one = above == (kind == Tail::Lower);
+            return Some(if one { Ordering::Greater } else { Ordering::Less });
+        }
+        match tail(a, wp) {
+            Approx::Exact(v) => match mpfr_cmp(&v.value, &q.value) {
+                0 => None,
+                c => Some(c.cmp(&0))
+            },
+            Approx::Within(v, err) => certain_sign(&v, err, &q.value, ::std::i64::MIN / 2, wp),
+            Approx::Underflow => Some(Ordering::Less),
+            Approx::Below(v) => {
+                let err = exp_of(&v) + 1 - wp;
+                certain_sign(&v, err, &q.value, ::std::i64::MIN / 2, wp)
+            }
+        }
+    });
+    match err {
+        Some(err) => Approx::Within(w, err),
+        None => retry(wp)
+    }
+}
@@ -0,0 +1,838 @@
+// Elliptic integrals and Jacobi elliptic functions of the parameter `m = k²`, with the same
+// signatures as MPFR functions.
+//
+// The complete integrals are computed with the arithmetic-geometric mean, the incomplete ones
+// with Carlson's symmetric integrals `RF` and `RD`, whose duplication algorithm has simple
+// bounds of the relative error. The amplitude `θ` of the Jacobi functions is the root of
+// `F(θ, m) = u` (after the reduction of `u` by the period), which is enclosed in an interval
+// whose ends give certain signs of `F(θ, m) - u` (see `ziv.rs`). None of these
+// functions has a dyadic value at a dyadic argument, except for the ones handled separately.
+
+use std::cmp::{self, Ordering};
+use std::mem;
+
+use libc::{c_int, c_ulong};
+
+use mpfr_sys::*;
+
+use BigFloat;
+use ziv::{Approx, Sum, round_with, retry, working, exp_of, log2_ceil, exact_sub};
+use ziv::{integer, copy, set_nan, newton, enclose, certain_sign};
+
+/// Returns the exponent of the bound of the relative error of `n` roundings.
+fn rounding(n: u64, wp: i64) -> i64 {
+    log2_ceil(n) - wp
+}
+
+/// Adds a term whose relative error is below `2^rel` to the sum.
+unsafe fn add_term(sum: &mut Sum, t: &BigFloat, rel: i64) {
+    sum.add(t, 0);
+    sum.add_error(exp_of(t) + rel);
+}
+
+/// Sets `v` to the arguments, rounded to the working precision.
+unsafe fn arguments(x: &BigFloat, y: &BigFloat, z: &BigFloat, wp: i64) -> [BigFloat; 3] {
+    let mut v = [working(wp), working(wp), working(wp)];
+    mpfr_set(&mut v[0].value, &x.value, MPFR_RNDN);
+    mpfr_set(&mut v[1].value, &y.value, MPFR_RNDN);
+    mpfr_set(&mut v[2].value, &z.value, MPFR_RNDN);
+    v
+}
+
+/// Computes `X_i = 1 - v_i / a` and returns the largest exponent of them.
+unsafe fn deviations(d: &mut [BigFloat; 3], v: &[BigFloat; 3], a: &BigFloat) -> i64 {
+    let mut r = ::std::i64::MIN / 2;
+    for i in 0..3 {
+        mpfr_div(&mut d[i].value, &v[i].value, &a.value, MPFR_RNDN);
+        mpfr_ui_sub(&mut d[i].value, 1, &d[i].value, MPFR_RNDN);
+        r = cmp::max(r, exp_of(&d[i]));
+    }
+    r
+}
+
+/// Replaces `v` with `(v + λ) / 4`, where `λ = √x √y + √y √z + √z √x`.
+unsafe fn duplicate(v: &mut [BigFloat; 3], wp: i64) {
+    let mut sq = [working(wp), working(wp), working(wp)];
+    for i in 0..3 {
+        mpfr_sqrt(&mut sq[i].value, &v[i].value, MPFR_RNDN);
+    }
+    let (mut lambda, mut t) = (working(wp), working(wp));
+    mpfr_mul(&mut lambda.value, &sq[0].value, &sq[1].value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &sq[1].value, &sq[2].value, MPFR_RNDN);
+    mpfr_add(&mut lambda.value, &lambda.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &sq[2].value, &sq[0].value, MPFR_RNDN);
+    mpfr_add(&mut lambda.value, &lambda.value, &t.value, MPFR_RNDN);
+    for i in 0..3 {
+        mpfr_add(&mut v[i].value, &v[i].value, &lambda.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut v[i].value, &v[i].value, 2, MPFR_RNDN);
+    }
+}
+
+/// Carlson's symmetric integral of the first kind
+/// `RF(x, y, z) = 1/2 ∫ dt / √((t + x) (t + y) (t + z))` from 0 to ∞ for nonnegative `x`,
+/// `y` and `z` at most one of which is zero. Returns the approximation and `e` such that its
+/// relative error is below `2^e`.
+///
+/// The duplication `RF(x, y, z) = RF((x + λ) / 4, (y + λ) / 4, (z + λ) / 4)` is repeated until
+/// `r = max |1 - x / A|` is small enough, where `A = (x + y + z) / 3`, and then the series of
+/// B. C. Carlson (Numerical computation of real or complex elliptic integrals, 1995) has the
+/// relative error below `r^6 / (4 (1 - r))`. Each duplication adds at most 6 roundings to the
+/// relative errors of the arguments, which are halved in the result.
+unsafe fn carlson_rf(x: &BigFloat, y: &BigFloat, z: &BigFloat, wp: i64) -> (BigFloat, i64) {
+    let mut v = arguments(x, y, z, wp);
+    let mut d = [working(wp), working(wp), working(wp)];
+    let mut a = working(wp);
+    let mut n: u64 = 0;
+    let mut r;
+    loop {
+        mpfr_add(&mut a.value, &v[0].value, &v[1].value, MPFR_RNDN);
+        mpfr_add(&mut a.value, &a.value, &v[2].value, MPFR_RNDN);
+        mpfr_div_ui(&mut a.value, &a.value, 3, MPFR_RNDN);
+        r = deviations(&mut d, &v, &a);
+        if 6 * r <= -wp - 4 || n as i64 > wp {
+            break;
+        }
+        duplicate(&mut v, wp);
+        n += 1;
+    }
+    if r > -2 {
+        return (a, 1);
+    }
+
+    // 1 - E2 / 10 + E3 / 14 + E2² / 24 - 3 E2 E3 / 44 with E2 = XY - Z², E3 = XYZ
+    let (mut e2, mut e3, mut s, mut t) = (working(wp), working(wp), working(wp), working(wp));
+    mpfr_mul(&mut e2.value, &d[0].value, &d[1].value, MPFR_RNDN);
+    mpfr_mul(&mut e3.value, &e2.value, &d[2].value, MPFR_RNDN);
+    mpfr_sqr(&mut t.value, &d[2].value, MPFR_RNDN);
+    mpfr_sub(&mut e2.value, &e2.value, &t.value, MPFR_RNDN);
+    mpfr_set_ui(&mut s.value, 1, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &e2.value, 10, MPFR_RNDN);
+    mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &e3.value, 14, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_sqr(&mut t.value, &e2.value, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 24, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &e2.value, &e3.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &t.value, 3, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 44, MPFR_RNDN);
+    mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+
+    mpfr_sqrt(&mut a.value, &a.value, MPFR_RNDN);
+    mpfr_div(&mut s.value, &s.value, &a.value, MPFR_RNDN);
+    (s, cmp::max(rounding(12 * n + 32, wp), 6 * r - 1) + 1)
+}
+
+/// Carlson's symmetric integral of the second kind
+/// `RD(x, y, z) = 3/2 ∫ dt / √((t + x) (t + y) (t + z)³)` from 0 to ∞ for nonnegative `x` and
+/// `y` at most one of which is zero and positive `z`, with the same result as `carlson_rf`.
+///
+/// The duplication `RD(x, y, z) = 3 / (√z (z + λ)) + RD(x + λ, y + λ, z + λ) / 4` is repeated
+/// until `r` is small enough for `A = (x + y + 3z) / 5`, and then Carlson's series has the
+/// relative error below `3 r^6 / (1 - r)^(3/2)`. The relative errors of the arguments are
+/// amplified at most `3/2` times in the result.
+unsafe fn carlson_rd(x: &BigFloat, y: &BigFloat, z: &BigFloat, wp: i64) -> (BigFloat, i64) {
+    let mut v = arguments(x, y, z, wp);
+    let mut d = [working(wp), working(wp), working(wp)];
+    let (mut a, mut t, mut sz) = (working(wp), working(wp), working(wp));
+    let mut sum = working(wp);
+    mpfr_set_ui(&mut sum.value, 0, MPFR_RNDN);
+    let mut n: u64 = 0;
+    let mut r;
+    loop {
+        mpfr_mul_ui(&mut a.value, &v[2].value, 3, MPFR_RNDN);
+        mpfr_add(&mut a.value, &a.value, &v[0].value, MPFR_RNDN);
+        mpfr_add(&mut a.value, &a.value, &v[1].value, MPFR_RNDN);
+        mpfr_div_ui(&mut a.value, &a.value, 5, MPFR_RNDN);
+        r = deviations(&mut d, &v, &a);
+        if 6 * r + 3 <= -wp - 4 || n as i64 > wp {
+            break;
+        }
+        // 3 / 4^n / (√z (z + λ)), where z + λ is four times the next z
+        mpfr_sqrt(&mut sz.value, &v[2].value, MPFR_RNDN);
+        duplicate(&mut v, wp);
+        mpfr_mul_2ui(&mut t.value, &v[2].value, 2, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &sz.value, MPFR_RNDN);
+        mpfr_ui_div(&mut t.value, 3, &t.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut t.value, &t.value, 2 * n as c_ulong, MPFR_RNDN);
+        mpfr_add(&mut sum.value, &sum.value, &t.value, MPFR_RNDN);
+        n += 1;
+    }
+    if r > -2 {
+        return (a, 1);
+    }
+
+    // 1 - 3 E2 / 14 + E3 / 6 + 9 E2² / 88 - 3 E4 / 22 - 9 E2 E3 / 52 + 3 E5 / 26 with
+    // E2 = XY - 6Z², E3 = (3XY - 8Z²) Z, E4 = 3 (XY - Z²) Z², E5 = XYZ³
+    let (mut xy, mut z2) = (working(wp), working(wp));
+    mpfr_mul(&mut xy.value, &d[0].value, &d[1].value, MPFR_RNDN);
+    mpfr_sqr(&mut z2.value, &d[2].value, MPFR_RNDN);
+    let (mut e2, mut e3, mut e4, mut e5) = (working(wp), working(wp), working(wp), working(wp));
+    mpfr_mul_ui(&mut t.value, &z2.value, 6, MPFR_RNDN);
+    mpfr_sub(&mut e2.value, &xy.value, &t.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut e3.value, &xy.value, 3, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &z2.value, 8, MPFR_RNDN);
+    mpfr_sub(&mut e3.value, &e3.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut e3.value, &e3.value, &d[2].value, MPFR_RNDN);
+    mpfr_sub(&mut e4.value, &xy.value, &z2.value, MPFR_RNDN);
+    mpfr_mul(&mut e4.value, &e4.value, &z2.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut e4.value, &e4.value, 3, MPFR_RNDN);
+    mpfr_mul(&mut e5.value, &xy.value, &z2.value, MPFR_RNDN);
+    mpfr_mul(&mut e5.value, &e5.value, &d[2].value, MPFR_RNDN);
+
+    let mut s = working(wp);
+    mpfr_set_ui(&mut s.value, 1, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &e2.value, 3, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 14, MPFR_RNDN);
+    mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &e3.value, 6, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_sqr(&mut t.value, &e2.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &t.value, 9, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 88, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &e4.value, 3, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 22, MPFR_RNDN);
+    mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &e2.value, &e3.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &t.value, 9, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 52, MPFR_RNDN);
+    mpfr_sub(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &e5.value, 3, MPFR_RNDN);
+    mpfr_div_ui(&mut t.value, &t.value, 26, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+
+    // 4^-n A^(-3/2) s
+    mpfr_sqrt(&mut t.value, &a.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &a.value, MPFR_RNDN);
+    mpfr_div(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut s.value, &s.value, 2 * n as c_ulong, MPFR_RNDN);
+    mpfr_add(&mut s.value, &s.value, &sum.value, MPFR_RNDN);
+    (s, cmp::max(rounding(18 * n + 64, wp), 6 * r + 3) + 1)
+}
+
+/// `K(m) = π / (2 M(1, √(1 - m)))` for `m < 1`, with the exponent of the bound of the error.
+/// The AGM is monotonic and homogeneous, so the relative error of its argument is not
+/// amplified.
+unsafe fn agm_k(m: mpfr_srcptr, wp: i64) -> (BigFloat, i64) {
+    let one = integer(1);
+    let mut g = working(wp);
+    mpfr_sqrt(&mut g.value, &exact_sub(&one.value, m).value, MPFR_RNDN);
+    mpfr_agm(&mut g.value, &one.value, &g.value, MPFR_RNDN);
+    let mut k = working(wp);
+    mpfr_const_pi(&mut k.value, MPFR_RNDN);
+    mpfr_div(&mut k.value, &k.value, &g.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut k.value, &k.value, 1, MPFR_RNDN);
+    let err = exp_of(&k) + rounding(5, wp);
+    (k, err)
+}
+
+/// `E(m) = K(m) (1 - Σ_{n>=0} 2^(n-1) c_n²)` for `m <= 1`, with the exponent of the bound of
+/// the error, where `c_0² = m` and `c_n = (a_{n-1} - b_{n-1}) / 2 = c_{n-1}² / (4 a_n)` along
+/// the AGM iteration starting from `a_0 = 1`, `b_0 = √(1 - m)`.
+///
+/// The means stay between `1` and `b_0`, so `a_n >= β = min(1, b_0)` and
+/// `c_{n+1}² <= c_n² c_n² / (16 β²)`. Once a term is below `β²`, `c_n² <= β²` as well, each
+/// following term is at most `1/8` of the previous one, and the rest of the series is below
+/// `8/7` of its first term. For `m` close to `1` (and for `m < 0` at the first terms) the
+/// ratio is larger, up to `1/2`, before the quadratic convergence begins.
+unsafe fn agm_e(m: mpfr_srcptr, wp: i64) -> (BigFloat, i64) {
+    let one = integer(1);
+    if mpfr_cmp_ui(m, 1) == 0 {
+        return (one, ::std::i64::MIN / 2);
+    }
+    let mut sum = Sum::new(wp);
+    sum.add(&one, 0);
+    let (mut a, mut b, mut a1) = (working(wp), working(wp), working(wp));
+    let (mut c, mut sq, mut t) = (working(wp), working(wp), working(wp));
+    mpfr_set_ui(&mut a.value, 1, MPFR_RNDN);
+    let one_minus_m = exact_sub(&one.value, m);
+    mpfr_sqrt(&mut b.value, &one_minus_m.value, MPFR_RNDN);
+    mpfr_set(&mut sq.value, m, MPFR_RNDN);
+    // The exponent of β² = min(1, 1 - m)
+    let e_beta = cmp::min(1, exp_of(&one_minus_m));
+    mpfr_div_2ui(&mut t.value, &sq.value, 1, MPFR_RNDN);
+    sum.sub(&t, 1);
+
+    // The relative errors of a_n and b_n, and of c_n², in roundings
+    let (mut ra, mut rsq): (u64, u64) = (1, 1);
+    let mut n: u64 = 1;
+    loop {
+        mpfr_add(&mut a1.value, &a.value, &b.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut a1.value, &a1.value, 1, MPFR_RNDN);
+        mpfr_mul(&mut b.value, &a.value, &b.value, MPFR_RNDN);
+        mpfr_sqrt(&mut b.value, &b.value, MPFR_RNDN);
+        mem::swap(&mut a, &mut a1);
+        ra += 2;
+
+        mpfr_div(&mut c.value, &sq.value, &a.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut c.value, &c.value, 2, MPFR_RNDN);
+        mpfr_sqr(&mut sq.value, &c.value, MPFR_RNDN);
+        rsq = rsq.saturating_add(ra + 1).saturating_mul(2).saturating_add(1);
+        mpfr_mul_2ui(&mut t.value, &sq.value, (n - 1) as c_ulong, MPFR_RNDN);
+        if exp_of(&t) < exp_of(&sum.value) - wp - 4 && exp_of(&t) < e_beta - 1 {
+            sum.add_error(exp_of(&t) + 1);
+            break;
+        }
+        if n as i64 > wp {
+            // Not converged, so that the working precision is increased
+            sum.add_error(exp_of(&sum.value) + 1);
+            break;
+        }
+        sum.sub(&t, rsq);
+        n += 1;
+    }
+
+    let err = sum.err();
+    let v = sum.value;
+    let (k, ek) = agm_k(m, wp);
+    let mut r = working(wp);
+    mpfr_mul(&mut r.value, &v.value, &k.value, MPFR_RNDN);
+    let err = cmp::max(cmp::max(err + exp_of(&k) + 1, exp_of(&v) + ek + 1), exp_of(&r) - wp) + 2;
+    (r, err)
+}
+
+/// The complete elliptic integral of the first kind `K(m) = F(π/2, m)`.
+pub unsafe fn elliptic_k(rop: mpfr_ptr, m: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(m) != 0 || mpfr_cmp_ui(m, 1) > 0 {
+        return set_nan(rop);
+    }
+    if mpfr_cmp_ui(m, 1) == 0 {
+        mpfr_set_inf(rop, 1);
+        return 0;
+    }
+    if mpfr_inf_p(m) != 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    round_with(rop, rnd, |wp| {
+        let (k, err) = agm_k(m, wp);
+        Approx::Within(k, err)
+    })
+}
+
+/// The complete elliptic integral of the second kind `E(m) = E(π/2, m)`.
+pub unsafe fn elliptic_e(rop: mpfr_ptr, m: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(m) != 0 || mpfr_cmp_ui(m, 1) > 0 {
+        return set_nan(rop);
+    }
+    if mpfr_cmp_ui(m, 1) == 0 {
+        return mpfr_set_ui(rop, 1, rnd);
+    }
+    if mpfr_inf_p(m) != 0 {
+        mpfr_set_inf(rop, 1);
+        return 0;
+    }
+    round_with(rop, rnd, |wp| {
+        let (e, err) = agm_e(m, wp);
+        Approx::Within(e, err)
+    })
+}
+
+/// `sin θ`, `cos θ`, `x = cos² θ` and `y = 1 - m sin² θ`, where `rel` is the exponent of the
+/// bound of the relative errors of `x` and `y`.
+struct Amplitude {
+    s: BigFloat,
+    c: BigFloat,
+    x: BigFloat,
+    y: BigFloat,
+    rel: i64
+}
+
+/// Returns `None` if `y` is not certainly positive or too inaccurate.
+unsafe fn amplitude(theta: mpfr_srcptr, m: mpfr_srcptr, wp: i64) -> Option<Amplitude> {
+    let (mut s, mut c) = (working(wp), working(wp));
+    let (mut x, mut y, mut t) = (working(wp), working(wp), working(wp));
+    mpfr_sin_cos(&mut s.value, &mut c.value, theta, MPFR_RNDN);
+    mpfr_sqr(&mut x.value, &c.value, MPFR_RNDN);
+    mpfr_sqr(&mut t.value, &s.value, MPFR_RNDN);
+    let rel = if mpfr_cmp_ui(m, 1) <= 0 {
+        // y = cos² θ + (1 - m) sin² θ is a sum of nonnegative terms
+        mpfr_mul(&mut t.value, &t.value, &exact_sub(&integer(1).value, m).value, MPFR_RNDN);
+        mpfr_add(&mut y.value, &x.value, &t.value, MPFR_RNDN);
+        rounding(6, wp)
+    } else {
+        // The absolute error of m sin² θ is below 4 roundings
+        mpfr_mul(&mut t.value, &t.value, m, MPFR_RNDN);
+        mpfr_ui_sub(&mut y.value, 1, &t.value, MPFR_RNDN);
+        if mpfr_sgn(&y.value) <= 0 {
+            return None;
+        }
+        let rel = cmp::max(exp_of(&t) + 3 - wp - exp_of(&y), -wp) + 1;
+        if rel > -4 {
+            return None;
+        }
+        cmp::max(rel, rounding(3, wp))
+    };
+    Some(Amplitude { s: s, c: c, x: x, y: y, rel: rel })
+}
+
+/// `F(θ, m) = sin θ RF(cos² θ, 1 - m sin² θ, 1)` for `|θ| <= π/2`, with the exponent of the
+/// bound of the error.
+unsafe fn incomplete_f(a: &Amplitude, wp: i64) -> (BigFloat, i64) {
+    let (rf, err) = carlson_rf(&a.x, &a.y, &integer(1), wp);
+    let mut f = working(wp);
+    mpfr_mul(&mut f.value, &a.s.value, &rf.value, MPFR_RNDN);
+    let rel = cmp::max(err, cmp::max(a.rel - 1, rounding(2, wp))) + 2;
+    let err = exp_of(&f) + rel;
+    (f, err)
+}
+
+/// `E(θ, m)` for `|θ| <= π/2`, with the exponent of the bound of the error, from the sum of
+/// terms of the same sign for `m <= 1`:
+///
+/// - `sin θ RF(x, y, 1) - m/3 sin³ θ RD(x, y, 1)` for `m <= 0`;
+/// - `(1 - m) sin θ RF(x, y, 1) + m (1 - m) / 3 sin³ θ RD(x, 1, y) + m sin θ |cos θ| / √y`
+///   for `0 < m <= 1`,
+///
+/// where `x = cos² θ` and `y = 1 - m sin² θ`. The first form is used for `m > 1` as well.
+unsafe fn incomplete_e(a: &Amplitude, m: mpfr_srcptr, wp: i64) -> (BigFloat, i64) {
+    let one = integer(1);
+    let mut sum = Sum::new(wp);
+    let (rf, ef) = carlson_rf(&a.x, &a.y, &one, wp);
+    let (mut s3, mut t, mut u) = (working(wp), working(wp), working(wp));
+    mpfr_sqr(&mut s3.value, &a.s.value, MPFR_RNDN);
+    mpfr_mul(&mut s3.value, &s3.value, &a.s.value, MPFR_RNDN);
+    if mpfr_sgn(m) > 0 && mpfr_cmp_ui(m, 1) <= 0 {
+        let h = exact_sub(&one.value, m);
+        mpfr_mul(&mut t.value, &a.s.value, &rf.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &h.value, MPFR_RNDN);
+        add_term(&mut sum, &t, cmp::max(ef, cmp::max(a.rel - 1, rounding(3, wp))) + 2);
+        let (rd, ed) = carlson_rd(&a.x, &one, &a.y, wp);
+        mpfr_mul(&mut t.value, &s3.value, &rd.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &h.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, m, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &t.value, 3, MPFR_RNDN);
+        add_term(&mut sum, &t, cmp::max(ed, cmp::max(a.rel + 1, rounding(9, wp))) + 2);
+        mpfr_sqrt(&mut u.value, &a.y.value, MPFR_RNDN);
+        mpfr_abs(&mut t.value, &a.c.value, MPFR_RNDN);
+        mpfr_div(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &a.s.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, m, MPFR_RNDN);
+        add_term(&mut sum, &t, cmp::max(a.rel - 1, rounding(6, wp)) + 2);
+    } else {
+        mpfr_mul(&mut t.value, &a.s.value, &rf.value, MPFR_RNDN);
+        add_term(&mut sum, &t, cmp::max(ef, cmp::max(a.rel - 1, rounding(2, wp))) + 2);
+        let (rd, ed) = carlson_rd(&a.x, &a.y, &one, wp);
+        mpfr_mul(&mut t.value, &s3.value, &rd.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, m, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &t.value, 3, MPFR_RNDN);
+        mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+        add_term(&mut sum, &t, cmp::max(ed, cmp::max(a.rel + 1, rounding(8, wp))) + 2);
+    }
+    let err = sum.err();
+    (sum.value, err)
+}
+
+/// Reduces `φ` to `φ - kπ`, which is about `[-π/2, π/2]`. Returns the reduced value, `k` and
+/// the exponent of the bound of the error of the reduced value.
+unsafe fn reduce(phi: mpfr_srcptr, wp: i64) -> (BigFloat, BigFloat, i64) {
+    if mpfr_cmpabs(phi, &integer(1).value) <= 0 {
+        return (copy(phi), integer(0), ::std::i64::MIN / 2);
+    }
+    let wq = wp + cmp::max(mpfr_get_exp(phi) as i64, 0) + 8;
+    let (mut pi, mut k, mut r) = (working(wq), working(wq), working(wq));
+    mpfr_const_pi(&mut pi.value, MPFR_RNDN);
+    mpfr_div(&mut k.value, phi, &pi.value, MPFR_RNDN);
+    mpfr_rint(&mut k.value, &k.value, MPFR_RNDN);
+    if mpfr_zero_p(&k.value) != 0 {
+        return (copy(phi), k, ::std::i64::MIN / 2);
+    }
+    mpfr_mul(&mut pi.value, &pi.value, &k.value, MPFR_RNDN);
+    mpfr_sub(&mut r.value, phi, &pi.value, MPFR_RNDN);
+    let err = exp_of(&pi) + 2 - wq;
+    (r, k, err)
+}
+
+/// Checks that `1 - m sin² θ > 0` for all `θ` between 0 and `φ`, for `m >= 1`. The sine of
+/// a nonzero dyadic number is transcendental, so it is never `1/√m`.
+unsafe fn in_domain(phi: mpfr_srcptr, m: mpfr_srcptr) -> bool {
+    // The bound of the domain is below π/2 < 2
+    if mpfr_cmpabs(phi, &integer(2).value) >= 0 {
+        return false;
+    }
+    let mut p = mpfr_get_prec(phi) as i64 + mpfr_get_prec(m) as i64 + 16;
+    loop {
+        match sin_squared_cmp(phi, m, p) {
+            Some(o) => return o == Ordering::Less,
+            None => p *= 2
+        }
+    }
+}
+
+/// Compares `m sin² θ` with 1 for `|θ| < 2` if it is certain at the precision `p`,
+/// or `Greater` if `|θ| >= π/2`.
+unsafe fn sin_squared_cmp(theta: mpfr_srcptr, m: mpfr_srcptr, p: i64) -> Option<Ordering> {
+    let (mut lo, mut hi) = (working(p), working(p));
+    // The sign of the correctly rounded cosine is exact
+    mpfr_cos(&mut lo.value, theta, MPFR_RNDN);
+    if mpfr_sgn(&lo.value) <= 0 {
+        return Some(Ordering::Greater);
+    }
+    mpfr_sin(&mut lo.value, theta, MPFR_RNDZ);
+    mpfr_sin(&mut hi.value, theta, MPFR_RNDA);
+    mpfr_sqr(&mut lo.value, &lo.value, MPFR_RNDD);
+    mpfr_sqr(&mut hi.value, &hi.value, MPFR_RNDU);
+    mpfr_mul(&mut lo.value, &lo.value, m, MPFR_RNDD);
+    mpfr_mul(&mut hi.value, &hi.value, m, MPFR_RNDU);
+    if mpfr_cmp_ui(&hi.value, 1) < 0 {
+        Some(Ordering::Less)
+    } else if mpfr_cmp_ui(&lo.value, 1) >= 0 {
+        Some(Ordering::Greater)
+    } else {
+        None
+    }
+}
+
+/// The incomplete elliptic integral of the first kind `F(φ, m) = ∫ dθ / √(1 - m sin² θ)`
+/// from 0 to `φ`. For `m >= 1` it is real only while `m sin² φ < 1`.
+///
+/// For `m < 1`, `F(φ + kπ, m) = F(φ, m) + 2k K(m)`, and the error of the reduced `φ` is
+/// amplified at most `1 / √(1 - m)` times.
+pub unsafe fn elliptic_f(rop: mpfr_ptr, phi: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t)
+                         -> c_int {
+    if mpfr_nan_p(phi) != 0 || mpfr_nan_p(m) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(phi) != 0 || mpfr_zero_p(m) != 0 {
+        return mpfr_set(rop, phi, rnd);
+    }
+    if mpfr_inf_p(m) != 0 {
+        if mpfr_sgn(m) < 0 && mpfr_inf_p(phi) == 0 {
+            mpfr_set_zero(rop, mpfr_sgn(phi));
+            return 0;
+        }
+        return set_nan(rop);
+    }
+    let large = mpfr_cmp_ui(m, 1) >= 0;
+    if large && (mpfr_inf_p(phi) != 0 || !in_domain(phi, m)) {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(phi) != 0 {
+        mpfr_set_inf(rop, mpfr_sgn(phi));
+        return 0;
+    }
+    round_with(rop, rnd, |wp| {
+        if large {
+            return match amplitude(phi, m, wp) {
+                Some(a) => {
+                    let (f, err) = incomplete_f(&a, wp);
+                    Approx::Within(f, err)
+                }
+                None => retry(wp)
+            };
+        }
+        let (r, k, er) = reduce(phi, wp);
+        let a = match amplitude(&r.value, m, wp) {
+            Some(a) => a,
+            None => return retry(wp)
+        };
+        let (f, ef) = incomplete_f(&a, wp);
+        let mut sum = Sum::new(wp);
+        sum.add(&f, 0);
+        sum.add_error(ef);
+        if mpfr_zero_p(&k.value) == 0 {
+            let (kk, ek) = agm_k(m, wp);
+            let mut t = working(wp);
+            mpfr_mul(&mut t.value, &kk.value, &k.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            sum.add(&t, 1);
+            sum.add_error(ek + exp_of(&k) + 1);
+            // 1 / √(1 - m), and twice as much for the reduced value slightly beyond π/2
+            let slope = if mpfr_sgn(m) > 0 {
+                (2 - exp_of(&exact_sub(&integer(1).value, m))) / 2
+            } else {
+                0
+            };
+            sum.add_error(er + slope + 1);
+        }
+        sum.approx()
+    })
+}
+
+/// The incomplete elliptic integral of the second kind `E(φ, m) = ∫ √(1 - m sin² θ) dθ`
+/// from 0 to `φ`. For `m > 1` it is real only while `m sin² φ <= 1`.
+///
+/// For `m <= 1`, `E(φ + kπ, m) = E(φ, m) + 2k E(m)`, and the error of the reduced `φ` is
+/// amplified at most `√(1 - m)` times for `m < 0`.
+pub unsafe fn elliptic_e_inc(rop: mpfr_ptr, phi: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t)
+                             -> c_int {
+    if mpfr_nan_p(phi) != 0 || mpfr_nan_p(m) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(phi) != 0 || mpfr_zero_p(m) != 0 {
+        return mpfr_set(rop, phi, rnd);
+    }
+    if mpfr_inf_p(m) != 0 {
+        if mpfr_sgn(m) < 0 && mpfr_inf_p(phi) == 0 {
+            mpfr_set_inf(rop, mpfr_sgn(phi));
+            return 0;
+        }
+        return set_nan(rop);
+    }
+    let large = mpfr_cmp_ui(m, 1) > 0;
+    if large && (mpfr_inf_p(phi) != 0 || !in_domain(phi, m)) {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(phi) != 0 {
+        mpfr_set_inf(rop, mpfr_sgn(phi));
+        return 0;
+    }
+    round_with(rop, rnd, |wp| {
+        if large {
+            return match amplitude(phi, m, wp) {
+                Some(a) => {
+                    let (e, err) = incomplete_e(&a, m, wp);
+                    Approx::Within(e, err)
+                }
+                None => retry(wp)
+            };
+        }
+        let (r, k, er) = reduce(phi, wp);
+        let a = match amplitude(&r.value, m, wp) {
+            Some(a) => a,
+            None => return retry(wp)
+        };
+        let (e, ee) = incomplete_e(&a, m, wp);
+        let mut sum = Sum::new(wp);
+        sum.add(&e, 0);
+        sum.add_error(ee);
+        if mpfr_zero_p(&k.value) == 0 {
+            let (ce, ec) = agm_e(m, wp);
+            let mut t = working(wp);
+            mpfr_mul(&mut t.value, &ce.value, &k.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            sum.add(&t, 1);
+            sum.add_error(ec + exp_of(&k) + 1);
+            // √(1 - m), and twice as much for the reduced value slightly beyond π/2
+            let slope = if mpfr_sgn(m) < 0 {
+                (exp_of(&exact_sub(&integer(1).value, m)) + 1) / 2
+            } else {
+                0
+            };
+            sum.add_error(er + slope + 1);
+        }
+        sum.approx()
+    })
+}
+
+#[derive(Copy, PartialEq, Eq)]
+enum Jacobi {
+    Sn,
+    Cn,
+    Dn
+}
+
+/// The Jacobi elliptic functions `sn(u, m) = sin θ`, `cn(u, m) = cos θ` and
+/// `dn(u, m) = √(1 - m sin² θ)`, where `θ = am(u, m)` is the amplitude, `F(θ, m) = u`.
+///
+/// The argument is reduced by the half period `2K`, where `K = RF(0, 1 - m, 1)` for `m < 1`
+/// and `K = F(θmax, m) = RF(m - 1, 0, m)` for `m > 1`, `sin² θmax = 1/m`; then `θ` is in
+/// `[-θmax, θmax]` (with `θmax = π/2` for `m < 1`). The shift by `2K` changes the signs of
+/// `sn` and `cn` for `m < 1`, and of `sn` and `dn` for `m > 1`.
+unsafe fn jacobi(rop: mpfr_ptr, u: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t, f: Jacobi)
+                 -> c_int {
+    if mpfr_nan_p(u) != 0 || mpfr_nan_p(m) != 0 || mpfr_inf_p(m) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(m) != 0 {
+        return match f {
+            Jacobi::Sn => mpfr_sin(rop, u, rnd),
+            Jacobi::Cn => mpfr_cos(rop, u, rnd),
+            Jacobi::Dn => mpfr_set_ui(rop, 1, rnd)
+        };
+    }
+    if mpfr_cmp_ui(m, 1) == 0 {
+        return match f {
+            Jacobi::Sn => mpfr_tanh(rop, u, rnd),
+            _ => mpfr_sech(rop, u, rnd)
+        };
+    }
+    if mpfr_zero_p(u) != 0 {
+        return match f {
+            Jacobi::Sn => mpfr_set(rop, u, rnd),
+            _ => mpfr_set_ui(rop, 1, rnd)
+        };
+    }
+    if mpfr_inf_p(u) != 0 {
+        return set_nan(rop);
+    }
+
+    let one = integer(1);
+    let large = mpfr_cmp_ui(m, 1) > 0;
+    let quarter = if large {
+        [exact_sub(m, &one.value), integer(0), copy(m)]
+    } else {
+        [integer(0), exact_sub(&one.value, m), integer(1)]
+    };
+    round_with(rop, rnd, |wp| {
+        let (kk, _) = carlson_rf(&quarter[0], &quarter[1], &quarter[2], 32);
+        let wq = wp + cmp::max(mpfr_get_exp(u) as i64 - exp_of(&kk), 0) + 8;
+        let (kk, ek) = carlson_rf(&quarter[0], &quarter[1], &quarter[2], wq);
+        // The absolute error of K
+        let ek = exp_of(&kk) + ek;
+
+        // u - 2kK, where k = round(u / 2K)
+        let mut k = working(wq);
+        mpfr_div(&mut k.value, u, &kk.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut k.value, &k.value, 1, MPFR_RNDN);
+        mpfr_rint(&mut k.value, &k.value, MPFR_RNDN);
+        let (ur, eu, odd) = if mpfr_zero_p(&k.value) != 0 {
+            (copy(u), ::std::i64::MIN / 2, false)
+        } else {
+            let (mut t, mut ur) = (working(wq), working(wq));
+            mpfr_mul(&mut t.value, &kk.value, &k.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            mpfr_sub(&mut ur.value, u, &t.value, MPFR_RNDN);
+            let eu = cmp::max(ek + exp_of(&k) + 1, cmp::max(exp_of(&t), exp_of(&ur)) - wq) + 2;
+            mpfr_div_2ui(&mut k.value, &k.value, 1, MPFR_RNDN);
+            (ur, eu, mpfr_integer_p(&k.value) == 0)
+        };
+
+        // θmax rounded down, so that the points strictly inside are in the domain
+        let mut bound = working(wp);
+        if large {
+            mpfr_rec_sqrt(&mut bound.value, m, MPFR_RNDD);
+            mpfr_asin(&mut bound.value, &bound.value, MPFR_RNDD);
+        } else {
+            mpfr_const_pi(&mut bound.value, MPFR_RNDD);
+            mpfr_div_2ui(&mut bound.value, &bound.value, 1, MPFR_RNDD);
+        }
+        let theta = match amplitude_of(&ur, &kk, m, &bound, wp) {
+            Some(theta) => theta,
+            None => return retry(wp)
+        };
+        let (theta, d) = theta;
+
+        // The sign of F(a, m) - u, extending F by ±K beyond the domain
+        let sign = |a: &BigFloat| -> Option<Ordering> {
+            let inside = if mpfr_cmpabs(&a.value, &integer(2).value) >= 0 {
+                Some(Ordering::Greater)
+            } else if large {
+                sin_squared_cmp(&a.value, m, wp)
+            } else {
+                // For m < 1 the domain is (-π/2, π/2)
+                let mut c = working(16);
+                mpfr_cos(&mut c.value, &a.value, MPFR_RNDN);
+                Some(if mpfr_sgn(&c.value) > 0 { Ordering::Less } else { Ordering::Greater })
+            };
+            match inside {
+                Some(Ordering::Less) => match amplitude(&a.value, m, wp) {
+                    Some(am) => {
+                        let (f, ef) = incomplete_f(&am, wp);
+                        certain_sign(&f, ef, &ur.value, eu, wp)
+                    }
+                    None => None
+                },
+                Some(_) => {
+                    let mut b = working(wp);
+                    mpfr_set(&mut b.value, &kk.value, MPFR_RNDN);
+                    if mpfr_sgn(&a.value) < 0 {
+                        mpfr_neg(&mut b.value, &b.value, MPFR_RNDN);
+                    }
+                    certain_sign(&b, ek + 1, &ur.value, eu, wp)
+                }
+                None => None
+            }
+        };
+        let et = match enclose(&theta, &d, wp, sign) {
+            Some(et) => et,
+            None => return retry(wp)
+        };
+
+        let (mut s, mut c) = (working(wp), working(wp));
+        mpfr_sin_cos(&mut s.value, &mut c.value, &theta.value, MPFR_RNDN);
+        let (mut r, err, negate) = match f {
+            Jacobi::Sn => {
+                let err = cmp::max(et, exp_of(&s) - wp) + 1;
+                (s, err, odd)
+            }
+            Jacobi::Cn => {
+                let err = cmp::max(et, exp_of(&c) - wp) + 1;
+                (c, err, odd && !large)
+            }
+            Jacobi::Dn => {
+                let (r, err) = delta(&s, &c, m, et, wp);
+                (r, err, odd && large)
+            }
+        };
+        if negate {
+            mpfr_neg(&mut r.value, &r.value, MPFR_RNDN);
+        }
+        Approx::Within(r, err)
+    })
+}
+
+/// Finds an approximation of the root `θ` of `F(θ, m) = u` in `(-bound, bound)` with
+/// Newton's iteration, starting from `u / K bound`. Returns the root and the last correction.
+unsafe fn amplitude_of(u: &BigFloat, kk: &BigFloat, m: mpfr_srcptr, bound: &BigFloat, wp: i64)
+                       -> Option<(BigFloat, BigFloat)> {
+    let (mut theta, mut t) = (working(wp), working(wp));
+    mpfr_mul(&mut theta.value, &u.value, &bound.value, MPFR_RNDN);
+    mpfr_div(&mut theta.value, &theta.value, &kk.value, MPFR_RNDN);
+    if mpfr_cmpabs(&theta.value, &bound.value) >= 0 {
+        mpfr_copysign(&mut theta.value, &bound.value, &theta.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut theta.value, &theta.value, 1, MPFR_RNDN);
+    }
+    let mut lo = working(wp);
+    mpfr_neg(&mut lo.value, &bound.value, MPFR_RNDN);
+    let d = newton(&mut theta, wp, Some(&lo), Some(bound), |theta| {
+        // The correction (F(θ) - u) √(1 - m sin² θ)
+        let a = match amplitude(&theta.value, m, wp) {
+            Some(a) => a,
+            None => return None
+        };
+        let (f, _) = incomplete_f(&a, wp);
+        let mut d = working(wp);
+        mpfr_sub(&mut d.value, &f.value, &u.value, MPFR_RNDN);
+        mpfr_sqrt(&mut t.value, &a.y.value, MPFR_RNDN);
+        mpfr_mul(&mut d.value, &d.value, &t.value, MPFR_RNDN);
+        Some(d)
+    });
+    d.map(|d| (theta, d))
+}
+
+/// `√(1 - m sin² θ)` from the approximations of `sin θ` and `cos θ` for `θ` within `2^et`,
+/// with the exponent of the bound of the error.
+///
+/// The square `y` is known within `2^ey`, then `|√y - √y'| <= |y - y'| / √y` and also
+/// `<= √|y - y'|`, which is used close to the zeros.
+unsafe fn delta(s: &BigFloat, c: &BigFloat, m: mpfr_srcptr, et: i64, wp: i64)
+                -> (BigFloat, i64) {
+    let (mut y, mut t) = (working(wp), working(wp));
+    mpfr_sqr(&mut t.value, &s.value, MPFR_RNDN);
+    let ey = if mpfr_cmp_ui(m, 1) <= 0 {
+        mpfr_sqr(&mut y.value, &c.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &exact_sub(&integer(1).value, m).value, MPFR_RNDN);
+        mpfr_add(&mut y.value, &y.value, &t.value, MPFR_RNDN);
+        exp_of(&y) + 3 - wp
+    } else {
+        mpfr_mul(&mut t.value, &t.value, m, MPFR_RNDN);
+        mpfr_ui_sub(&mut y.value, 1, &t.value, MPFR_RNDN);
+        exp_of(&t) + 3 - wp
+    };
+    // |sin² θ - sin² θ'| <= |θ - θ'|
+    let ey = cmp::max(ey, et + mpfr_get_exp(m) as i64) + 1;
+    if mpfr_sgn(&y.value) <= 0 {
+        mpfr_set_ui(&mut y.value, 0, MPFR_RNDN);
+    }
+    mpfr_sqrt(&mut y.value, &y.value, MPFR_RNDN);
+    let err = cmp::min(ey - exp_of(&y) + 2, (ey + 1) / 2 + 1);
+    let err = cmp::max(err, exp_of(&y) - wp) + 1;
+    (y, err)
+}
+
+/// The Jacobi elliptic function `sn(u, m)`.
+pub unsafe fn jacobi_sn(rop: mpfr_ptr, u: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    jacobi(rop, u, m, rnd, Jacobi::Sn)
+}
+
+/// The Jacobi elliptic function `cn(u, m)`.
+pub unsafe fn jacobi_cn(rop: mpfr_ptr, u: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    jacobi(rop, u, m, rnd, Jacobi::Cn)
+}
+
+/// The Jacobi elliptic function `dn(u, m)`.
+pub unsafe fn jacobi_dn(rop: mpfr_ptr, u: mpfr_srcptr, m: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    jacobi(rop, u, m, rnd, Jacobi::Dn)
+}
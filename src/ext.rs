@@ -0,0 +1,749 @@
+// MPFR functions which are not available in the bindings or in older versions of MPFR.
+//
+// When the `mpfr4` (`mpfr4_1`, `mpfr4_2`) feature is enabled, functions introduced in
+// MPFR 4.0 (4.1, 4.2) are taken from the linked library directly. Otherwise they are
+// emulated here with the same signatures and the same rounding guarantees using only
+// the functions available in MPFR 3.
+
+#[cfg(not(feature = "mpfr4"))]
+use std::cmp;
+#[cfg(not(feature = "mpfr4"))]
+use std::ptr;
+
+use libc::{c_int, c_ulong};
+
+use mpfr_sys::*;
+use gmp_sys::gmp_randstate_ptr;
+#[cfg(feature = "gmp")]
+use gmp_sys::mpq_ptr;
+#[cfg(all(feature = "gmp", not(feature = "mpfr4")))]
+use gmp_sys::mpz_ptr;
+#[cfg(all(feature = "gmp", not(feature = "mpfr4")))]
+use gmp_sys::{__gmpz_set_ui, __gmpz_mul_2exp, __gmpq_set_ui, __gmpq_canonicalize};
+
+#[cfg(not(feature = "mpfr4_2"))]
+use BigFloat;
+#[cfg(not(feature = "mpfr4"))]
+use precision::ToPrecision;
+#[cfg(not(feature = "mpfr4_1"))]
+use ziv::{exact_mul, extended};
+#[cfg(not(feature = "mpfr4"))]
+use ziv::{Sum, log2_ceil, exact_add, flip, exp_approx, integer, copy};
+#[cfg(not(feature = "mpfr4"))]
+use special::gamma_upper_expansion;
+#[cfg(not(feature = "mpfr4_2"))]
+use ziv::{Approx, round_with, working, exp_of, exact_sub};
+
+#[cfg(feature = "mpfr4")]
+extern "C" {
+    pub fn mpfr_fmma(rop: mpfr_ptr, a: mpfr_srcptr, b: mpfr_srcptr,
+                     c: mpfr_srcptr, d: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_fmms(rop: mpfr_ptr, a: mpfr_srcptr, b: mpfr_srcptr,
+                     c: mpfr_srcptr, d: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_rint_roundeven(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_beta(rop: mpfr_ptr, x: mpfr_srcptr, y: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_gamma_inc(rop: mpfr_ptr, a: mpfr_srcptr, x: mpfr_srcptr,
+                          rnd: mpfr_rnd_t) -> c_int;
+}
+
+// The nearest integer to a number is always representable in the precision of that number,
+// so it is computed exactly first and then rounded only once
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_rint_roundeven(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    let mut n = BigFloat::fresh_with_prec(mpfr_get_prec(op).bits());
+    mpfr_rint(&mut n.value, op, MPFR_RNDN);
+    mpfr_set(rop, &n.value, rnd)
+}
+
+#[cfg(feature = "mpfr4_1")]
+extern "C" {
+    pub fn mpfr_dot(rop: mpfr_ptr, a: *const mpfr_ptr, b: *const mpfr_ptr,
+                    n: c_ulong, rnd: mpfr_rnd_t) -> c_int;
+}
+
+// Both products are computed exactly in the widest exponent range, where they can neither
+// overflow nor underflow, so the only rounding happens in the final addition
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_fmma(rop: mpfr_ptr, a: mpfr_srcptr, b: mpfr_srcptr,
+                        c: mpfr_srcptr, d: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    extended(rop, rnd, || {
+        let ab = exact_mul(a, b);
+        let cd = exact_mul(c, d);
+        mpfr_add(rop, &ab.value, &cd.value, rnd)
+    })
+}
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_fmms(rop: mpfr_ptr, a: mpfr_srcptr, b: mpfr_srcptr,
+                        c: mpfr_srcptr, d: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    extended(rop, rnd, || {
+        let ab = exact_mul(a, b);
+        let cd = exact_mul(c, d);
+        mpfr_sub(rop, &ab.value, &cd.value, rnd)
+    })
+}
+
+// All products are computed exactly in the widest exponent range and then added with a single
+// rounding by mpfr_sum
+
+#[cfg(not(feature = "mpfr4_1"))]
+pub unsafe fn mpfr_dot(rop: mpfr_ptr, a: *const mpfr_ptr, b: *const mpfr_ptr,
+                       n: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    extended(rop, rnd, || {
+        let products: Vec<BigFloat> = (0..n as isize)
+            .map(|i| exact_mul(*a.offset(i), *b.offset(i)))
+            .collect();
+        let mut ptrs: Vec<mpfr_ptr> = products.iter()
+            .map(|p| &p.value as *const __mpfr_struct as mpfr_ptr)
+            .collect();
+        mpfr_sum(rop, ptrs.as_mut_ptr(), n, rnd)
+    })
+}
+
+// B(x, n) = (n - 1)! / (x (x + 1) ... (x + n - 1)) for a positive integer n, which is
+// computed exactly when n is not greater than this
+#[cfg(not(feature = "mpfr4"))]
+const BETA_RATIONAL_MAX: c_ulong = 256;
+
+/// Returns `x` as an integer if it is a positive integer not greater than `max`.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn small_positive_integer(x: mpfr_srcptr, max: c_ulong) -> Option<c_ulong> {
+    if mpfr_integer_p(x) != 0 && mpfr_sgn(x) > 0 && mpfr_cmp_ui(x, max) <= 0 {
+        Some(mpfr_get_ui(x, MPFR_RNDN))
+    } else {
+        None
+    }
+}
+
+/// Returns whether an integer `n` is odd.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn odd(n: mpfr_srcptr) -> bool {
+    let mut h = BigFloat::fresh_with_prec(mpfr_get_prec(n).bits());
+    mpfr_div_2ui(&mut h.value, n, 1, MPFR_RNDN);
+    mpfr_integer_p(&h.value) == 0
+}
+
+/// Returns whether `x` is a negative number whose floor is odd, i.e. whether `Γ(x) < 0`
+/// for a negative `x` which is not an integer.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn odd_floor(x: mpfr_srcptr) -> bool {
+    if mpfr_sgn(x) >= 0 {
+        return false;
+    }
+    let mut n = BigFloat::fresh_with_prec(mpfr_get_prec(x).bits());
+    mpfr_rint_floor(&mut n.value, x, MPFR_RNDN);
+    odd(&n.value)
+}
+
+/// Returns whether `x` is a negative integer.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn negative_integer(x: mpfr_srcptr) -> bool {
+    mpfr_integer_p(x) != 0 && mpfr_sgn(x) < 0
+}
+
+/// B(z, w) for a zero or infinite `z`, with the same values as MPFR 4.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn beta_singular(rop: mpfr_ptr, z: mpfr_srcptr, w: mpfr_srcptr) -> c_int {
+    let sign = |negative: bool| if negative { -1 } else { 1 };
+    if mpfr_zero_p(z) != 0 {
+        // B(±0, w) = ±∞ unless w is a pole or a zero of the other sign
+        if mpfr_inf_p(w) != 0 || negative_integer(w) ||
+           mpfr_zero_p(w) != 0 && mpfr_signbit(w) != mpfr_signbit(z) {
+            mpfr_set_nan(rop);
+        } else {
+            mpfr_set_inf(rop, sign(mpfr_signbit(z) != 0));
+            mpfr_set_divby0();
+        }
+    } else if mpfr_sgn(z) > 0 {
+        // B(+∞, w) = +0 for w > 0, and ±∞ with the sign of Γ(w) for w < 0 otherwise
+        if mpfr_sgn(w) > 0 {
+            mpfr_set_zero(rop, 1);
+        } else if mpfr_inf_p(w) != 0 || negative_integer(w) {
+            mpfr_set_nan(rop);
+        } else {
+            mpfr_set_inf(rop, sign(!odd_floor(w)));
+        }
+    } else {
+        // B(-∞, n) = (-1)^n 0 for a positive integer n
+        if mpfr_number_p(w) != 0 && mpfr_integer_p(w) != 0 && mpfr_sgn(w) > 0 {
+            mpfr_set_zero(rop, sign(odd(w)));
+        } else {
+            mpfr_set_nan(rop);
+        }
+    }
+    0
+}
+
+/// B(-m, w) for a positive integer `m`, which MPFR 4 defines as the limit of
+/// `Γ(z) Γ(w) / Γ(z + w)` at `z = -m` for a positive integer `w <= m`, that is
+/// `(-1)^w B(w, m - w + 1)` for `w < m` and `1/m` for `w = m`.
+#[cfg(not(feature = "mpfr4"))]
+unsafe fn beta_pole(rop: mpfr_ptr, z: mpfr_srcptr, w: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_integer_p(w) == 0 || mpfr_sgn(w) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    let s = exact_add(z, w);
+    match mpfr_sgn(&s.value) {
+        0 => mpfr_si_div(rop, -1, z, rnd),
+        c if c < 0 => {
+            let mut one = BigFloat::fresh_with_prec(2.bits());
+            mpfr_set_ui(&mut one.value, 1, MPFR_RNDN);
+            let v = exact_sub(&one.value, &s.value);
+            if odd(w) {
+                let t = mpfr_beta(rop, w, &v.value, flip(rnd));
+                mpfr_neg(rop, rop, MPFR_RNDN);
+                -t
+            } else {
+                mpfr_beta(rop, w, &v.value, rnd)
+            }
+        }
+        _ => {
+            mpfr_set_nan(rop);
+            0
+        }
+    }
+}
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_beta(rop: mpfr_ptr, x: mpfr_srcptr, y: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_nan_p(y) != 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 || mpfr_zero_p(y) == 0 && mpfr_inf_p(x) != 0 {
+        return beta_singular(rop, x, y);
+    }
+    if mpfr_regular_p(y) == 0 {
+        return beta_singular(rop, y, x);
+    }
+
+    // Poles of the Gamma function in the numerator
+    if negative_integer(x) {
+        return beta_pole(rop, x, y, rnd);
+    }
+    if negative_integer(y) {
+        return beta_pole(rop, y, x, rnd);
+    }
+
+    // A pole in the denominator
+    let s = exact_add(x, y);
+    if mpfr_integer_p(&s.value) != 0 && mpfr_sgn(&s.value) <= 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+
+    // The rational case, the only rounding happens in the final division
+    let rational = small_positive_integer(y, BETA_RATIONAL_MAX).map(|n| (x, n))
+        .or_else(|| small_positive_integer(x, BETA_RATIONAL_MAX).map(|n| (y, n)));
+    if let Some((z, n)) = rational {
+        let mut num = working(cmp::max(2, (n as i64 - 1) * log2_ceil(n as u64) + 1));
+        mpfr_fac_ui(&mut num.value, n - 1, MPFR_RNDN);
+        let mut den = working(mpfr_get_prec(z) as i64);
+        mpfr_set(&mut den.value, z, MPFR_RNDN);
+        let mut i_f = working(64);
+        for i in 1..n {
+            mpfr_set_ui(&mut i_f.value, i, MPFR_RNDN);
+            let k = exact_add(z, &i_f.value);
+            den = exact_mul(&den.value, &k.value);
+        }
+        return mpfr_div(rop, &num.value, &den.value, rnd);
+    }
+
+    // |B| = exp(ln |Γ(x)| + ln |Γ(y)| - ln |Γ(x + y)|) is computed in the widest exponent
+    // range, where the logarithms do not overflow and B is representable whenever it is
+    // in the current range
+    let negative = (odd_floor(x) != odd_floor(y)) != odd_floor(&s.value);
+    let r = if negative { flip(rnd) } else { rnd };
+    let t = extended(rop, r, || round_with(rop, r, |wp| {
+        let mut l = Sum::new(wp);
+        let mut g = working(wp);
+        let mut sign: c_int = 0;
+        mpfr_lgamma(&mut g.value, &mut sign, x, MPFR_RNDN);
+        l.add(&g, 1);
+        mpfr_lgamma(&mut g.value, &mut sign, y, MPFR_RNDN);
+        l.add(&g, 1);
+        mpfr_lgamma(&mut g.value, &mut sign, &s.value, MPFR_RNDN);
+        l.sub(&g, 1);
+        exp_approx(l, wp)
+    }));
+    if negative {
+        mpfr_neg(rop, rop, MPFR_RNDN);
+        -t
+    } else {
+        t
+    }
+}
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_gamma_inc(rop: mpfr_ptr, a: mpfr_srcptr, x: mpfr_srcptr,
+                             rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(a) != 0 || mpfr_nan_p(x) != 0 || mpfr_sgn(a) <= 0 || mpfr_sgn(x) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_gamma(rop, a, rnd);
+    }
+    if mpfr_inf_p(x) != 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    if mpfr_inf_p(a) != 0 {
+        mpfr_set_inf(rop, 1);
+        return 0;
+    }
+
+    // For x >= a + 1, Γ(a, x) is computed directly by its expansion wherever it converges,
+    // i.e. unless x is small compared to the working precision. Otherwise
+    // Γ(a, x) = Γ(a) - γ(a, x), where the lower incomplete Gamma function is computed
+    // with the series γ(a, x) = x^a e^-x Σ x^n / (a (a + 1) ... (a + n)); all terms
+    // are positive since a > 0 and x > 0. The bits lost in the difference are added to
+    // the working precision of the following iterations
+    let (ab, xb) = (copy(a), copy(x));
+    let large = mpfr_cmp(x, &exact_add(a, &integer(1).value).value) >= 0;
+    let mut neg_x = working(mpfr_get_prec(x) as i64);
+    mpfr_neg(&mut neg_x.value, x, MPFR_RNDN);
+    let mut lost = 0;
+
+    round_with(rop, rnd, |wp| {
+        if large {
+            if let Some(r) = gamma_upper_expansion(&ab, &xb, false, wp) {
+                return r;
+            }
+        }
+        let wp = wp + lost;
+        let mut sum = working(wp);
+        let mut term = working(wp);
+        let mut d = working(wp);
+        let mut q = working(wp);
+        mpfr_ui_div(&mut term.value, 1, a, MPFR_RNDN);
+        mpfr_set(&mut sum.value, &term.value, MPFR_RNDN);
+        let mut n: u64 = 0;
+        loop {
+            n += 1;
+            mpfr_add_ui(&mut d.value, a, n as c_ulong, MPFR_RNDN);
+            mpfr_mul(&mut term.value, &term.value, x, MPFR_RNDN);
+            mpfr_div(&mut term.value, &term.value, &d.value, MPFR_RNDN);
+            mpfr_add(&mut sum.value, &sum.value, &term.value, MPFR_RNDN);
+            // Once a + n + 1 > x, the ratios x / (a + n + 1) of the following terms decrease
+            // and are below 1, so the rest is below the current term times
+            // x / (a + n + 1 - x)
+            if exp_of(&term) < exp_of(&sum) - wp {
+                mpfr_add_ui(&mut q.value, a, n as c_ulong + 1, MPFR_RNDD);
+                mpfr_sub(&mut q.value, &q.value, x, MPFR_RNDD);
+                if mpfr_sgn(&q.value) > 0 {
+                    mpfr_div(&mut q.value, x, &q.value, MPFR_RNDU);
+                    if exp_of(&term) + exp_of(&q) < exp_of(&sum) - wp {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut lower = working(wp);
+        let mut e = working(wp);
+        let mut r = working(wp);
+        mpfr_pow(&mut lower.value, x, a, MPFR_RNDN);
+        mpfr_exp(&mut e.value, &neg_x.value, MPFR_RNDN);
+        mpfr_mul(&mut lower.value, &lower.value, &e.value, MPFR_RNDN);
+        mpfr_mul(&mut lower.value, &lower.value, &sum.value, MPFR_RNDN);
+        let mut g = working(wp);
+        mpfr_gamma(&mut g.value, a, MPFR_RNDN);
+        if mpfr_number_p(&lower.value) == 0 || mpfr_number_p(&g.value) == 0 {
+            // Internal overflow
+            mpfr_set_nan(&mut r.value);
+            return Approx::Within(r, 0);
+        }
+        mpfr_sub(&mut r.value, &g.value, &lower.value, MPFR_RNDN);
+
+        // Each term has the relative error of at most 3n + 1 roundings and the sum adds
+        // n more, plus the truncation error; x^a, e^-x and the products add four more.
+        // Doubling this bound covers the higher order terms
+        let c = log2_ceil(8 * n + 16);
+        let m = cmp::max(cmp::max(exp_of(&g), exp_of(&lower) + c), exp_of(&r));
+        lost = cmp::max(lost, if mpfr_sgn(&r.value) > 0 { m - exp_of(&r) } else { wp });
+        Approx::Within(r, m + 2 - wp)
+    })
+}
+
+#[cfg(feature = "mpfr4_2")]
+extern "C" {
+    pub fn mpfr_sinu(rop: mpfr_ptr, op: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_cosu(rop: mpfr_ptr, op: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_tanu(rop: mpfr_ptr, op: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_sinpi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_cospi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_tanpi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_asinpi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_acospi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_atanpi(rop: mpfr_ptr, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int;
+}
+
+// Trigonometric functions of 2πx/u. The argument is reduced exactly, modulo the period
+// and then to [-u/4, u/4] by the symmetries of sine, so the approximations keep their relative
+// accuracy. By Niven's theorem the result is rational (and then exact) only when x is
+// a multiple of u/24, and such arguments are handled separately, with the signs of zeros
+// and infinities following IEEE 754 sinPi, cosPi and tanPi.
+
+/// Returns `u`, `u/2` and `u/4` as exact numbers.
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn periods(u: c_ulong) -> (BigFloat, BigFloat, BigFloat) {
+    let mut full = working(64);
+    mpfr_set_ui(&mut full.value, u, MPFR_RNDN);
+    let mut half = working(64);
+    mpfr_div_2ui(&mut half.value, &full.value, 1, MPFR_RNDN);
+    let mut quarter = working(64);
+    mpfr_div_2ui(&mut quarter.value, &full.value, 2, MPFR_RNDN);
+    (full, half, quarter)
+}
+
+/// Returns `x - n p` for the integer `n` nearest to `x / p` and a regular `p`, computed
+/// exactly.
+///
+/// The result is a multiple of the smaller of the ulps of `x` and `p`, and it is at most
+/// `|p| / 2` in absolute value, so its precision does not depend on the exponent of `x`
+/// (mpfr_remainder itself reduces huge arguments by modular exponentiation).
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn reduce(x: mpfr_srcptr, p: mpfr_srcptr) -> BigFloat {
+    let (px, pp) = (mpfr_get_prec(x) as i64, mpfr_get_prec(p) as i64);
+    let ep = mpfr_get_exp(p) as i64;
+    let prec = if mpfr_regular_p(x) != 0 {
+        let ulp = cmp::min(mpfr_get_exp(x) as i64 - px, ep - pp);
+        cmp::max(ep - ulp, 2)
+    } else {
+        cmp::max(px, pp)
+    };
+    let mut r = working(prec);
+    mpfr_remainder(&mut r.value, x, p, MPFR_RNDN);
+    r
+}
+
+/// Returns `k` if `r = k u / 24` for an integer `k`, which is in [-12, 12] for reduced `r`.
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn twenty_fourths(r: &BigFloat, u: &BigFloat) -> Option<i64> {
+    let mut k = working(mpfr_get_prec(&r.value) as i64 + 5);
+    mpfr_mul_ui(&mut k.value, &r.value, 24, MPFR_RNDN);
+    let mut q = working(8);
+    if mpfr_div(&mut q.value, &k.value, &u.value, MPFR_RNDN) == 0 &&
+       mpfr_integer_p(&q.value) != 0 {
+        Some(mpfr_get_si(&q.value, MPFR_RNDN) as i64)
+    } else {
+        None
+    }
+}
+
+/// Approximates `sin(2πr/u)` for `|r| <= u/4` with the relative error less than `2^(3 - wp)`.
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn sinu_reduced(r: &BigFloat, u: &BigFloat, wp: i64) -> BigFloat {
+    // The angle has the relative error of three roundings, which is amplified by at most π/2
+    // in the sine since |sin t| >= 2|t|/π on [-π/2, π/2]; the sine itself adds one more
+    let mut t = working(wp);
+    mpfr_const_pi(&mut t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &r.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+    mpfr_div(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+    mpfr_sin(&mut t.value, &t.value, MPFR_RNDN);
+    t
+}
+
+/// Stores `±1/2^e` into `rop`.
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn set_unit(rop: mpfr_ptr, negative: bool, e: mpfr_exp_t, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_set_si_2exp(rop, if negative { -1 } else { 1 }, -e, rnd)
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_sinu(rop: mpfr_ptr, x: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_number_p(x) == 0 || u == 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    let (full, half, quarter) = periods(u);
+    let r = reduce(x, &full.value);
+    match twenty_fourths(&r, &full) {
+        Some(0) | Some(12) | Some(-12) => {
+            mpfr_set_zero(rop, if mpfr_signbit(x) != 0 { -1 } else { 1 });
+            return 0;
+        }
+        Some(k) if k.abs() == 6 => return set_unit(rop, k < 0, 0, rnd),
+        Some(k) if k.abs() == 2 || k.abs() == 10 => return set_unit(rop, k < 0, 1, rnd),
+        _ => {}
+    }
+
+    // sin(π - t) = sin(t)
+    let r = if mpfr_cmpabs(&r.value, &quarter.value) > 0 {
+        if mpfr_sgn(&r.value) > 0 {
+            exact_sub(&half.value, &r.value)
+        } else {
+            let mut h = working(64);
+            mpfr_neg(&mut h.value, &half.value, MPFR_RNDN);
+            exact_sub(&h.value, &r.value)
+        }
+    } else {
+        r
+    };
+    round_with(rop, rnd, |wp| {
+        let s = sinu_reduced(&r, &full, wp);
+        let err = exp_of(&s) + 4 - wp;
+        Approx::Within(s, err)
+    })
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_cosu(rop: mpfr_ptr, x: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_number_p(x) == 0 || u == 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    let (full, _, quarter) = periods(u);
+    let mut r = reduce(x, &full.value);
+    match twenty_fourths(&r, &full) {
+        Some(6) | Some(-6) => {
+            mpfr_set_zero(rop, 1);
+            return 0;
+        }
+        Some(0) => return set_unit(rop, false, 0, rnd),
+        Some(12) | Some(-12) => return set_unit(rop, true, 0, rnd),
+        Some(k) if k.abs() == 4 || k.abs() == 8 => return set_unit(rop, k.abs() == 8, 1, rnd),
+        _ => {}
+    }
+
+    // cos(t) = sin(π/2 - |t|)
+    mpfr_abs(&mut r.value, &r.value, MPFR_RNDN);
+    let r = exact_sub(&quarter.value, &r.value);
+    round_with(rop, rnd, |wp| {
+        let c = sinu_reduced(&r, &full, wp);
+        let err = exp_of(&c) + 4 - wp;
+        Approx::Within(c, err)
+    })
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_tanu(rop: mpfr_ptr, x: mpfr_srcptr, u: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_number_p(x) == 0 || u == 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    let (full, half, quarter) = periods(u);
+    match twenty_fourths(&reduce(x, &full.value), &full) {
+        Some(k) if k % 12 == 0 => {
+            // Negative zero for odd multiples of π in the positive direction and even ones
+            // in the negative direction
+            let negative = (mpfr_signbit(x) != 0) != (k != 0);
+            mpfr_set_zero(rop, if negative { -1 } else { 1 });
+            return 0;
+        }
+        Some(k) if k.abs() == 6 => {
+            mpfr_set_inf(rop, if k < 0 { -1 } else { 1 });
+            return 0;
+        }
+        Some(k) if k.abs() == 3 || k.abs() == 9 => {
+            return set_unit(rop, (k < 0) != (k.abs() == 9), 0, rnd);
+        }
+        _ => {}
+    }
+
+    // tan(t) = sin(t) / sin(π/2 - |t|) for the argument reduced modulo π
+    let rs = reduce(x, &half.value);
+    let mut rc = working(mpfr_get_prec(&rs.value) as i64);
+    mpfr_abs(&mut rc.value, &rs.value, MPFR_RNDN);
+    let rc = exact_sub(&quarter.value, &rc.value);
+    round_with(rop, rnd, |wp| {
+        let mut t = sinu_reduced(&rs, &full, wp);
+        let c = sinu_reduced(&rc, &full, wp);
+        mpfr_div(&mut t.value, &t.value, &c.value, MPFR_RNDN);
+        let err = exp_of(&t) + 6 - wp;
+        Approx::Within(t, err)
+    })
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_sinpi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_sinu(rop, x, 2, rnd)
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_cospi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_cosu(rop, x, 2, rnd)
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_tanpi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_tanu(rop, x, 2, rnd)
+}
+
+/// Approximates `f(x) / π`, where `f` is correctly rounded.
+#[cfg(not(feature = "mpfr4_2"))]
+unsafe fn div_pi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t,
+                 f: unsafe extern "C" fn(mpfr_ptr, mpfr_srcptr, mpfr_rnd_t) -> c_int) -> c_int {
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        let mut pi = working(wp);
+        f(&mut r.value, x, MPFR_RNDN);
+        mpfr_const_pi(&mut pi.value, MPFR_RNDN);
+        mpfr_div(&mut r.value, &r.value, &pi.value, MPFR_RNDN);
+        // Three roundings with the relative error of at most 2^-wp each
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+// By Niven's theorem, the inverse functions of rational arguments are rational only at 0,
+// ±1/2 and ±1, where the dyadic values (the only ones which can be exact) come from the
+// arguments handled separately; ±1/2 give thirds and sixths
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_asinpi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_cmp_ui(x, 1) > 0 || mpfr_cmp_si(x, -1) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    if mpfr_cmp_ui(x, 1) == 0 || mpfr_cmp_si(x, -1) == 0 {
+        return set_unit(rop, mpfr_sgn(x) < 0, 1, rnd);
+    }
+    div_pi(rop, x, rnd, mpfr_asin)
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_acospi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_cmp_ui(x, 1) > 0 || mpfr_cmp_si(x, -1) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return set_unit(rop, false, 1, rnd);
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    if mpfr_cmp_si(x, -1) == 0 {
+        return set_unit(rop, false, 0, rnd);
+    }
+    div_pi(rop, x, rnd, mpfr_acos)
+}
+
+#[cfg(not(feature = "mpfr4_2"))]
+pub unsafe fn mpfr_atanpi(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_unit(rop, mpfr_sgn(x) < 0, 1, rnd);
+    }
+    if mpfr_cmp_ui(x, 1) == 0 || mpfr_cmp_si(x, -1) == 0 {
+        return set_unit(rop, mpfr_sgn(x) < 0, 2, rnd);
+    }
+    div_pi(rop, x, rnd, mpfr_atan)
+}
+
+// The bindings take `gmp_randstate_t` by value, which is an array in `gmp-sys`, so the random
+// functions are declared here with a pointer to the state instead
+
+extern "C" {
+    pub fn mpfr_urandomb(rop: mpfr_ptr, state: gmp_randstate_ptr) -> c_int;
+    pub fn mpfr_urandom(rop: mpfr_ptr, state: gmp_randstate_ptr, rnd: mpfr_rnd_t) -> c_int;
+}
+
+#[cfg(feature = "mpfr4")]
+extern "C" {
+    pub fn mpfr_nrandom(rop: mpfr_ptr, state: gmp_randstate_ptr, rnd: mpfr_rnd_t) -> c_int;
+    pub fn mpfr_erandom(rop: mpfr_ptr, state: gmp_randstate_ptr, rnd: mpfr_rnd_t) -> c_int;
+}
+
+#[cfg(not(feature = "mpfr4"))]
+extern "C" {
+    fn mpfr_grandom(rop1: mpfr_ptr, rop2: mpfr_ptr, state: gmp_randstate_ptr,
+                    rnd: mpfr_rnd_t) -> c_int;
+}
+
+// MPFR 3 generates normal numbers in pairs, skipping the second one when its pointer is null.
+// The ternary values are combined as in mpfr_sin_cos, where 2 stands for a result rounded down
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_nrandom(rop: mpfr_ptr, state: gmp_randstate_ptr, rnd: mpfr_rnd_t) -> c_int {
+    match mpfr_grandom(rop, ptr::null_mut(), state, rnd) & 3 {
+        0 => 0,
+        1 => 1,
+        _ => -1
+    }
+}
+
+// An exponential number is -ln U for a uniform U in (0, 1), whose bits are drawn only as far
+// as needed. When both ends of the interval known to contain U round to the same number, and
+// one of them shows on which side of -ln U it lies, this number is the rounding of -ln U
+
+#[cfg(not(feature = "mpfr4"))]
+pub unsafe fn mpfr_erandom(rop: mpfr_ptr, state: gmp_randstate_ptr, rnd: mpfr_rnd_t) -> c_int {
+    let prec = mpfr_get_prec(rop);
+    let step = cmp::max(prec, 64);
+    let neg_log = |x: &BigFloat| {
+        let mut r = BigFloat::fresh_with_prec(prec.bits());
+        let t = mpfr_log(&mut r.value, &x.value, flip(rnd));
+        mpfr_neg(&mut r.value, &r.value, MPFR_RNDN);
+        (r, -t)
+    };
+
+    let mut u = BigFloat::fresh_with_prec(step.bits());
+    let mut chunk = BigFloat::fresh_with_prec(step.bits());
+    mpfr_urandomb(&mut u.value, state);
+    let mut k = step;
+    loop {
+        // U lies in [u, u + 2^-k), and -ln 0 is left out as it raises the divide-by-zero flag
+        if mpfr_zero_p(&u.value) == 0 {
+            let mut v = BigFloat::fresh_with_prec(k.bits());
+            mpfr_set_ui_2exp(&mut v.value, 1, -k, MPFR_RNDN);
+            mpfr_add(&mut v.value, &v.value, &u.value, MPFR_RNDN);
+            let (a, ta) = neg_log(&v);
+            let (b, tb) = neg_log(&u);
+            if mpfr_equal_p(&a.value, &b.value) != 0 && (tb > 0 || ta < 0) {
+                mpfr_set(rop, &b.value, rnd);
+                return if tb > 0 { 1 } else { -1 };
+            }
+        }
+        mpfr_urandomb(&mut chunk.value, state);
+        mpfr_div_2si(&mut chunk.value, &chunk.value, k, MPFR_RNDN);
+        k += step;
+        mpfr_prec_round(&mut u.value, k, MPFR_RNDN);
+        mpfr_add(&mut u.value, &u.value, &chunk.value, MPFR_RNDN);
+    }
+}
+
+#[cfg(all(feature = "gmp", feature = "mpfr4"))]
+extern "C" {
+    pub fn mpfr_get_q(rop: mpq_ptr, op: mpfr_srcptr);
+}
+
+// A finite number is m 2^e for an integer m, and the power of two goes to the numerator
+// or to the denominator. Zero is handled separately since its exponent is the minimal one
+
+#[cfg(all(feature = "gmp", not(feature = "mpfr4")))]
+pub unsafe fn mpfr_get_q(rop: mpq_ptr, op: mpfr_srcptr) {
+    if mpfr_regular_p(op) == 0 {
+        if mpfr_zero_p(op) == 0 {
+            mpfr_set_erangeflag();
+        }
+        __gmpq_set_ui(rop, 0, 1);
+        return;
+    }
+    let num: mpz_ptr = &mut (*rop)._mp_num;
+    let den: mpz_ptr = &mut (*rop)._mp_den;
+    let e = mpfr_get_z_2exp(num, op);
+    __gmpz_set_ui(den, 1);
+    if e >= 0 {
+        __gmpz_mul_2exp(num, num, e as c_ulong);
+    } else {
+        __gmpz_mul_2exp(den, den, -e as c_ulong);
+    }
+    __gmpq_canonicalize(rop);
+}
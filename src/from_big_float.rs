@@ -1,5 +1,8 @@
 use mpfr_sys::*;
 
+#[cfg(feature = "gmp")]
+use gmp::{Mpz, Mpq};
+
 use {BigFloat, grnd};
 
 pub trait FromBigFloat {
@@ -36,3 +39,29 @@ impl FromBigFloat for String {
     }
 }
 
+
+/// Rounds the number to an integer with the global rounding mode. NaN and infinities give zero
+/// and set the erange flag.
+#[cfg(feature = "gmp")]
+impl FromBigFloat for Mpz {
+    type Target = Mpz;
+
+    fn from_big_float(x: &BigFloat) -> Mpz {
+        let mut z = Mpz::new();
+        unsafe {
+            mpfr_get_z(z.inner_mut(), &x.value, grnd());
+        }
+        z
+    }
+}
+
+/// Converts the number exactly, see `BigFloat::get_q()`.
+#[cfg(feature = "gmp")]
+impl FromBigFloat for Mpq {
+    type Target = Mpq;
+
+    #[inline]
+    fn from_big_float(x: &BigFloat) -> Mpq {
+        x.get_q()
+    }
+}
@@ -0,0 +1,89 @@
+use std::cmp::Ordering;
+
+use mpfr_sys::*;
+
+use {BigFloat, RoundingMode, global_rounding_mode, grnd, ternary};
+use ext::{mpfr_fmma, mpfr_fmms};
+
+/// Fused multiply-add operations.
+///
+/// The result of each operation is rounded only once, as if the intermediate products
+/// and the sum were computed with infinite precision.
+pub trait FusedMulAdd {
+    type Output;
+
+    /// Computes `self * b + c`.
+    fn fma(self, b: &BigFloat, c: &BigFloat) -> Self::Output;
+    /// Computes `self * b - c`.
+    fn fms(self, b: &BigFloat, c: &BigFloat) -> Self::Output;
+    /// Computes `self * b + c * d`.
+    fn fmma(self, b: &BigFloat, c: &BigFloat, d: &BigFloat) -> Self::Output;
+    /// Computes `self * b - c * d`.
+    fn fmms(self, b: &BigFloat, c: &BigFloat, d: &BigFloat) -> Self::Output;
+
+    // The same functions with an explicit rounding mode; they also return the ternary value
+    fn fma_round(self, b: &BigFloat, c: &BigFloat, rnd: RoundingMode)
+        -> (Self::Output, Ordering);
+    fn fms_round(self, b: &BigFloat, c: &BigFloat, rnd: RoundingMode)
+        -> (Self::Output, Ordering);
+    fn fmma_round(self, b: &BigFloat, c: &BigFloat, d: &BigFloat, rnd: RoundingMode)
+        -> (Self::Output, Ordering);
+    fn fmms_round(self, b: &BigFloat, c: &BigFloat, d: &BigFloat, rnd: RoundingMode)
+        -> (Self::Output, Ordering);
+}
+
+macro_rules! impl_fused_all {
+    ($($meth:ident, $round:ident, $assign:ident($($p:ident),+) -> $mpfr:ident);+) => {
+        impl FusedMulAdd for BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, $($p: &BigFloat),+) -> BigFloat {
+                self.$round($($p,)+ global_rounding_mode::get()).0
+            }
+
+            fn $round(mut self, $($p: &BigFloat,)+ rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let t = unsafe {
+                    $mpfr(&mut self.value, &self.value $(, &$p.value)+, rnd as mpfr_rnd_t)
+                };
+                (self, ternary(t))
+            }
+            )+
+        }
+
+        impl<'r> FusedMulAdd for &'r BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, $($p: &BigFloat),+) -> BigFloat {
+                self.clone().$meth($($p),+)
+            }
+
+            #[inline]
+            fn $round(self, $($p: &BigFloat,)+ rnd: RoundingMode) -> (BigFloat, Ordering) {
+                self.clone().$round($($p,)+ rnd)
+            }
+            )+
+        }
+
+        // Destination-writing variants, see math.rs
+        impl BigFloat {
+            $(
+            pub fn $assign(&mut self, a: &BigFloat $(, $p: &BigFloat)+) {
+                unsafe {
+                    $mpfr(&mut self.value, &a.value $(, &$p.value)+, grnd());
+                }
+            }
+            )+
+        }
+    }
+}
+
+impl_fused_all! {
+    fma, fma_round, assign_fma(b, c) -> mpfr_fma;
+    fms, fms_round, assign_fms(b, c) -> mpfr_fms;
+    fmma, fmma_round, assign_fmma(b, c, d) -> mpfr_fmma;
+    fmms, fmms_round, assign_fmms(b, c, d) -> mpfr_fmms
+}
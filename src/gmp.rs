@@ -0,0 +1,191 @@
+// Integers and rationals of GMP, as far as they are needed to convert big floats to and from
+// them and to compute with both.
+
+use std::mem;
+use std::fmt;
+use std::ffi::CStr;
+
+use libc::{c_char, c_ulong};
+
+use gmp_sys::{__mpz_struct, __mpq_struct, mpz_ptr, mpz_srcptr, mpq_ptr, mpq_srcptr};
+use gmp_sys::{__gmpz_init, __gmpz_init_set, __gmpz_init_set_ui, __gmpz_clear, __gmpz_cmp};
+use gmp_sys::{__gmpz_mul_2exp, __gmpz_add_ui, __gmpz_neg, __gmpz_sizeinbase, __gmpz_get_str};
+use gmp_sys::{__gmpq_init, __gmpq_clear, __gmpq_set, __gmpq_set_num, __gmpq_set_den};
+use gmp_sys::{__gmpq_canonicalize, __gmpq_equal};
+
+/// An arbitrary precision integer.
+pub struct Mpz {
+    mpz: __mpz_struct
+}
+
+impl Mpz {
+    /// Creates a zero integer.
+    pub fn new() -> Mpz {
+        unsafe {
+            let mut mpz = mem::uninitialized();
+            __gmpz_init(&mut mpz);
+            Mpz { mpz: mpz }
+        }
+    }
+
+    /// Returns a pointer to the underlying GMP integer.
+    #[inline]
+    pub fn inner(&self) -> mpz_srcptr {
+        &self.mpz
+    }
+
+    /// Returns a mutable pointer to the underlying GMP integer.
+    #[inline]
+    pub fn inner_mut(&mut self) -> mpz_ptr {
+        &mut self.mpz
+    }
+}
+
+impl From<i64> for Mpz {
+    fn from(n: i64) -> Mpz {
+        // `c_ulong` may have only 32 bits, so the magnitude is set in two halves
+        let m = if n < 0 { (-(n + 1)) as u64 + 1 } else { n as u64 };
+        unsafe {
+            let mut z = Mpz { mpz: mem::uninitialized() };
+            let p = z.inner_mut();
+            __gmpz_init_set_ui(p, (m >> 32) as c_ulong);
+            __gmpz_mul_2exp(p, p, 32);
+            __gmpz_add_ui(p, p, (m & 0xffffffff) as c_ulong);
+            if n < 0 {
+                __gmpz_neg(p, p);
+            }
+            z
+        }
+    }
+}
+
+impl Drop for Mpz {
+    fn drop(&mut self) {
+        unsafe { __gmpz_clear(&mut self.mpz) }
+    }
+}
+
+impl Clone for Mpz {
+    fn clone(&self) -> Mpz {
+        unsafe {
+            let mut mpz = mem::uninitialized();
+            __gmpz_init_set(&mut mpz, &self.mpz);
+            Mpz { mpz: mpz }
+        }
+    }
+}
+
+impl PartialEq for Mpz {
+    #[inline]
+    fn eq(&self, other: &Mpz) -> bool {
+        unsafe { __gmpz_cmp(&self.mpz, &other.mpz) == 0 }
+    }
+}
+
+impl Eq for Mpz {}
+
+/// Formats a GMP integer in base 10.
+fn to_string(z: mpz_srcptr) -> String {
+    // the size in base 10 may be one too large, plus the sign and the zero byte
+    let n = unsafe { __gmpz_sizeinbase(z, 10) } as usize + 2;
+    let mut data: Vec<u8> = Vec::with_capacity(n);
+    let s = unsafe {
+        data.set_len(n);
+        __gmpz_get_str(data.as_mut_ptr() as *mut c_char, 10, z);
+        CStr::from_ptr(data.as_ptr() as *const c_char).to_bytes().to_vec()
+    };
+    String::from_utf8(s).unwrap()
+}
+
+impl fmt::Display for Mpz {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", to_string(&self.mpz))
+    }
+}
+
+impl fmt::Debug for Mpz {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mpz({})", self)
+    }
+}
+
+/// An arbitrary precision rational, always in lowest terms with a positive denominator.
+pub struct Mpq {
+    mpq: __mpq_struct
+}
+
+impl Mpq {
+    /// Creates a zero rational.
+    pub fn new() -> Mpq {
+        unsafe {
+            let mut mpq = mem::uninitialized();
+            __gmpq_init(&mut mpq);
+            Mpq { mpq: mpq }
+        }
+    }
+
+    /// Creates the rational `num / den` in lowest terms.
+    ///
+    /// # Panics
+    ///
+    /// If `den` is zero.
+    pub fn ratio(num: &Mpz, den: &Mpz) -> Mpq {
+        if den.mpz._mp_size == 0 {
+            panic!("Zero denominator of a rational");
+        }
+        let mut q = Mpq::new();
+        unsafe {
+            __gmpq_set_num(&mut q.mpq, &num.mpz);
+            __gmpq_set_den(&mut q.mpq, &den.mpz);
+            __gmpq_canonicalize(&mut q.mpq);
+        }
+        q
+    }
+
+    /// Returns a pointer to the underlying GMP rational.
+    #[inline]
+    pub fn inner(&self) -> mpq_srcptr {
+        &self.mpq
+    }
+
+    /// Returns a mutable pointer to the underlying GMP rational.
+    #[inline]
+    pub fn inner_mut(&mut self) -> mpq_ptr {
+        &mut self.mpq
+    }
+}
+
+impl Drop for Mpq {
+    fn drop(&mut self) {
+        unsafe { __gmpq_clear(&mut self.mpq) }
+    }
+}
+
+impl Clone for Mpq {
+    fn clone(&self) -> Mpq {
+        let mut q = Mpq::new();
+        unsafe { __gmpq_set(&mut q.mpq, &self.mpq) }
+        q
+    }
+}
+
+impl PartialEq for Mpq {
+    #[inline]
+    fn eq(&self, other: &Mpq) -> bool {
+        unsafe { __gmpq_equal(&self.mpq, &other.mpq) != 0 }
+    }
+}
+
+impl Eq for Mpq {}
+
+impl fmt::Display for Mpq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", to_string(&self.mpq._mp_num), to_string(&self.mpq._mp_den))
+    }
+}
+
+impl fmt::Debug for Mpq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mpq({})", self)
+    }
+}
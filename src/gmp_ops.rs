@@ -0,0 +1,98 @@
+// Arithmetic and comparisons with the integers and rationals of `mpfr::gmp`, and exact
+// conversions to them.
+
+use std::cmp::Ordering;
+
+use mpfr_sys::*;
+
+use gmp::{Mpz, Mpq};
+use {BigFloat, RoundingMode, global_rounding_mode, ternary};
+use ext::mpfr_get_q;
+
+macro_rules! generate_gmp_ops {
+    ($t:ty, $($(#[$attr:meta])* fn $method:ident, $round:ident -> $mpfr:ident),+) => (
+        impl BigFloat {
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub fn $method(&self, rhs: &$t) -> BigFloat {
+                self.$round(rhs, global_rounding_mode::get()).0
+            }
+
+            $(#[$attr])*
+            ///
+            /// The result has the precision of `self` and is rounded with the given rounding
+            /// mode instead of the global one. The ternary value is returned with it:
+            /// `Greater` if the result is greater than the exact one, `Less` if it is less,
+            /// `Equal` if it is exact.
+            pub fn $round(&self, rhs: &$t, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(self.prec());
+                let t = unsafe {
+                    $mpfr(&mut r.value, &self.value, rhs.inner(), rnd as mpfr_rnd_t)
+                };
+                (r, ternary(t))
+            }
+        )+
+        }
+    )
+}
+
+generate_gmp_ops! { Mpz,
+    #[doc="Computes `self + rhs` for an integer `rhs`."]
+    fn add_z, add_z_round -> mpfr_add_z,
+    #[doc="Computes `self - rhs` for an integer `rhs`."]
+    fn sub_z, sub_z_round -> mpfr_sub_z,
+    #[doc="Computes `self * rhs` for an integer `rhs`."]
+    fn mul_z, mul_z_round -> mpfr_mul_z,
+    #[doc="Computes `self / rhs` for an integer `rhs`."]
+    fn div_z, div_z_round -> mpfr_div_z,
+    #[doc="Computes `self` raised to the power of an integer `rhs`."]
+    fn pow_z, pow_z_round -> mpfr_pow_z
+}
+
+generate_gmp_ops! { Mpq,
+    #[doc="Computes `self + rhs` for a rational `rhs`."]
+    fn add_q, add_q_round -> mpfr_add_q,
+    #[doc="Computes `self - rhs` for a rational `rhs`."]
+    fn sub_q, sub_q_round -> mpfr_sub_q,
+    #[doc="Computes `self * rhs` for a rational `rhs`."]
+    fn mul_q, mul_q_round -> mpfr_mul_q,
+    #[doc="Computes `self / rhs` for a rational `rhs`."]
+    fn div_q, div_q_round -> mpfr_div_q
+}
+
+impl BigFloat {
+    /// Compares this number with an integer exactly. Returns `None` if this number is NaN.
+    pub fn cmp_z(&self, z: &Mpz) -> Option<Ordering> {
+        // MPFR returns zero for NaN, so it is told apart from equality by the operand itself
+        if self.is_nan() {
+            return None;
+        }
+        Some(unsafe { mpfr_cmp_z(&self.value, z.inner()) }.cmp(&0))
+    }
+
+    /// Compares this number with a rational exactly. Returns `None` if this number is NaN.
+    pub fn cmp_q(&self, q: &Mpq) -> Option<Ordering> {
+        if self.is_nan() {
+            return None;
+        }
+        Some(unsafe { mpfr_cmp_q(&self.value, q.inner()) }.cmp(&0))
+    }
+
+    /// Returns an integer `m` and an exponent `e` such that this number is exactly `m * 2^e`,
+    /// where `m` has at most as many bits as the precision of this number. Zero gives zero
+    /// with the minimal exponent, and NaN and infinities also set the erange flag.
+    pub fn get_z_2exp(&self) -> (Mpz, i64) {
+        let mut z = Mpz::new();
+        let e = unsafe { mpfr_get_z_2exp(z.inner_mut(), &self.value) };
+        (z, e as i64)
+    }
+
+    /// Returns this number as an exact rational in lowest terms. NaN and infinities give zero
+    /// and set the erange flag.
+    pub fn get_q(&self) -> Mpq {
+        let mut q = Mpq::new();
+        unsafe { mpfr_get_q(q.inner_mut(), &self.value); }
+        q
+    }
+}
@@ -2,23 +2,32 @@
 
 extern crate libc;
 extern crate "mpfr-sys" as mpfr_sys;
+extern crate "gmp-sys" as gmp_sys;
 #[macro_use] #[no_link] extern crate bitflags;
 extern crate num;
+#[cfg(feature = "rand")] extern crate rand;
 
 use std::mem;
 use std::ptr;
 use std::ffi::CStr;
 use std::fmt;
 use std::ops::{Add, Mul, Sub, Div, Rem, Neg};
+use std::ops::{AddAssign, MulAssign, SubAssign, DivAssign, RemAssign};
+use std::ops::{Shl, Shr, ShlAssign, ShrAssign};
 use std::cmp::Ordering;
 use std::num::{Int, FromPrimitive};
 
-use libc::{c_double, c_long, c_ulong};
+
+use libc::{c_int, c_long};
 
 use num::{Zero, One, Num};
 
 use mpfr_sys::*;
 
+use primitive::{Operand, ToOperand};
+use primitive::{remainder_d, remainder_ui, remainder_si, d_remainder, ui_remainder, si_remainder};
+use constants::Constant;
+
 pub use flags::Flags;
 pub use update_big_float::UpdateBigFloat;
 pub use from_big_float::FromBigFloat;
@@ -27,6 +36,9 @@ pub use builder::{BigFloatBuilder, BigFloatBuilderWithPrec};
 pub use rounding_mode::{RoundingMode, global_rounding_mode};
 pub use math::Math;
 pub use pow::Pow;
+pub use fused::FusedMulAdd;
+pub use remainder::Remainder;
+pub use binary_math::BinaryMath;
 pub use precision::{Precision, ToPrecision};
 
 #[macro_use] mod macros;
@@ -38,10 +50,28 @@ mod builder;
 mod rounding_mode;
 mod math;
 mod pow;
+mod fused;
+mod sum;
+mod remainder;
+mod binary_math;
+mod ext;
+mod ziv;
+mod reciprocal;
+mod special;
+mod zeta;
+mod elliptic;
+mod constants;
 mod util;
 mod precision;
+mod primitive;
+#[cfg(feature = "gmp")]
+mod gmp_ops;
 
 pub mod format;
+pub mod distributions;
+pub mod random;
+#[cfg(feature = "gmp")]
+pub mod gmp;
 
 pub mod traits {
     pub use UpdateBigFloat;
@@ -49,6 +79,9 @@ pub mod traits {
     pub use ToBigFloat;
     pub use Math;
     pub use Pow;
+    pub use FusedMulAdd;
+    pub use Remainder;
+    pub use BinaryMath;
     pub use ToPrecision;
 }
 
@@ -57,6 +90,12 @@ fn grnd() -> mpfr_rnd_t {
     global_rounding_mode::get() as mpfr_rnd_t
 }
 
+/// Converts an MPFR ternary value to the relation between the rounded and the exact result.
+#[inline]
+fn ternary(t: c_int) -> Ordering {
+    t.cmp(&0)
+}
+
 /// Represents a numerical sign.
 #[derive(Copy)]
 pub enum Sign {
@@ -143,14 +182,45 @@ macro_rules! generate_predicates {
 }
 
 macro_rules! generate_constant_setters {
-    ($t:ty, $($(#[$attr:meta])* fn $method:ident -> $mpfr:ident),+) => (
+    ($t:ty, $($(#[$attr:meta])* fn $method:ident, $round:ident -> $c:ident),+) => (
         impl $t {
         $(
             $(#[$attr])*
+            #[inline]
             pub fn $method(&mut self) {
-                unsafe {
-                    $mpfr(&mut self.value, grnd());
-                }
+                self.$round(global_rounding_mode::get());
+            }
+
+            $(#[$attr])*
+            ///
+            /// The result is rounded with the given rounding mode instead of the global one.
+            /// Returns the ternary value: `Greater` if the stored value is greater than
+            /// the exact one, `Less` if it is less, `Equal` if it is exact.
+            #[inline]
+            pub fn $round(&mut self, rnd: RoundingMode) -> Ordering {
+                constants::set_cached(self, Constant::$c, rnd)
+            }
+        )+
+        }
+    )
+}
+
+macro_rules! generate_rounding_ops {
+    ($t:ty, $($(#[$attr:meta])* fn $method:ident -> $mpfr:ident),+) => (
+        impl $t {
+        $(
+            $(#[$attr])*
+            ///
+            /// The result has the precision of `self` and is rounded with the given rounding
+            /// mode instead of the global one. The ternary value is returned with it:
+            /// `Greater` if the result is greater than the exact one, `Less` if it is less,
+            /// `Equal` if it is exact.
+            pub fn $method(&self, rhs: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let mut r = BigFloat::fresh_with_prec(self.prec());
+                let t = unsafe {
+                    $mpfr(&mut r.value, &self.value, &rhs.value, rnd as mpfr_rnd_t)
+                };
+                (r, ternary(t))
             }
         )+
         }
@@ -282,8 +352,11 @@ impl BigFloat {
         }
     }
 
+    /// Frees the caches of MPFR and the cached values of the constants set with
+    /// `set_to_const_*()` methods in the current thread.
     #[inline]
     pub fn free_cache() {
+        constants::clear_cache();
         unsafe {
             mpfr_free_cache();
         }
@@ -305,11 +378,76 @@ generate_predicates! { BigFloat,
     fn is_integer -> mpfr_integer_p
 }
 
+macro_rules! generate_assign_ops {
+    ($t:ty, $($(#[$attr:meta])* fn $method:ident -> $mpfr:ident),+) => (
+        impl $t {
+        $(
+            $(#[$attr])*
+            ///
+            /// The result is rounded to the precision of `self`, which is overwritten.
+            pub fn $method(&mut self, a: &BigFloat, b: &BigFloat) {
+                unsafe {
+                    $mpfr(&mut self.value, &a.value, &b.value, grnd());
+                }
+            }
+        )+
+        }
+    )
+}
+
 generate_constant_setters! { BigFloat,
-    fn set_to_const_log2     -> mpfr_const_log2,
-    fn set_to_const_pi       -> mpfr_const_pi,
-    fn set_to_const_euler    -> mpfr_const_euler,
-    fn set_to_const_catalan  -> mpfr_const_catalan
+    #[doc="Sets this number to log(2)."]
+    fn set_to_const_log2, set_to_const_log2_round                 -> Log2,
+    #[doc="Sets this number to π."]
+    fn set_to_const_pi, set_to_const_pi_round                     -> Pi,
+    #[doc="Sets this number to the Euler-Mascheroni constant."]
+    fn set_to_const_euler, set_to_const_euler_round               -> Euler,
+    #[doc="Sets this number to the Catalan constant."]
+    fn set_to_const_catalan, set_to_const_catalan_round           -> Catalan,
+    #[doc="Sets this number to e."]
+    fn set_to_const_e, set_to_const_e_round                       -> E,
+    #[doc="Sets this number to log(10)."]
+    fn set_to_const_ln10, set_to_const_ln10_round                 -> Ln10,
+    #[doc="Sets this number to sqrt(2)."]
+    fn set_to_const_sqrt2, set_to_const_sqrt2_round               -> Sqrt2,
+    #[doc="Sets this number to the golden ratio (1 + sqrt(5)) / 2."]
+    fn set_to_const_golden_ratio, set_to_const_golden_ratio_round -> GoldenRatio,
+    #[doc="Sets this number to Apéry's constant ζ(3)."]
+    fn set_to_const_apery, set_to_const_apery_round               -> Apery,
+    #[doc="Sets this number to the Glaisher-Kinkelin constant."]
+    fn set_to_const_glaisher, set_to_const_glaisher_round         -> Glaisher,
+    #[doc="Sets this number to Khinchin's constant."]
+    fn set_to_const_khinchin, set_to_const_khinchin_round         -> Khinchin
+}
+
+generate_rounding_ops! { BigFloat,
+    #[doc="Computes `self + rhs`."]
+    fn add_round -> mpfr_add,
+    #[doc="Computes `self - rhs`."]
+    fn sub_round -> mpfr_sub,
+    #[doc="Computes `self * rhs`."]
+    fn mul_round -> mpfr_mul,
+    #[doc="Computes `self / rhs`."]
+    fn div_round -> mpfr_div,
+    #[doc="Computes `self % rhs`, see `Remainder::remainder()`."]
+    fn rem_round -> mpfr_remainder,
+    #[doc="Computes `self` raised to the power of `rhs`."]
+    fn pow_round -> mpfr_pow
+}
+
+generate_assign_ops! { BigFloat,
+    #[doc="Stores `a + b` into `self`."]
+    fn assign_add -> mpfr_add,
+    #[doc="Stores `a - b` into `self`."]
+    fn assign_sub -> mpfr_sub,
+    #[doc="Stores `a * b` into `self`."]
+    fn assign_mul -> mpfr_mul,
+    #[doc="Stores `a / b` into `self`."]
+    fn assign_div -> mpfr_div,
+    #[doc="Stores `a % b` into `self`, see `Remainder::remainder()`."]
+    fn assign_rem -> mpfr_remainder,
+    #[doc="Stores `a` raised to the power of `b` into `self`."]
+    fn assign_pow -> mpfr_pow
 }
 
 impl FromPrimitive for BigFloat {
@@ -332,18 +470,57 @@ impl FromPrimitive for BigFloat {
 
 // Addition
 impl_commutative_op! { Add, add, mpfr_add, mpfr_add_d, mpfr_add_ui, mpfr_add_si }
+impl_assign_op! { AddAssign, add_assign, mpfr_add, mpfr_add_d, mpfr_add_ui, mpfr_add_si }
 
 // Multiplication
 impl_commutative_op! { Mul, mul, mpfr_mul, mpfr_mul_d, mpfr_mul_ui, mpfr_mul_si }
+impl_assign_op! { MulAssign, mul_assign, mpfr_mul, mpfr_mul_d, mpfr_mul_ui, mpfr_mul_si }
 
 // Subtraction
-impl_noncommutative_op! { Sub, sub, mpfr_sub, mpfr_sub_d, mpfr_sub_ui, mpfr_sub_si }
+impl_noncommutative_op! {
+    Sub, sub, mpfr_sub, mpfr_sub_d, mpfr_sub_ui, mpfr_sub_si,
+    mpfr_d_sub, mpfr_ui_sub, mpfr_si_sub
+}
+impl_assign_op! { SubAssign, sub_assign, mpfr_sub, mpfr_sub_d, mpfr_sub_ui, mpfr_sub_si }
 
 // Division
-impl_noncommutative_op! { Div, div, mpfr_div, mpfr_div_d, mpfr_div_ui, mpfr_div_si }
+impl_noncommutative_op! {
+    Div, div, mpfr_div, mpfr_div_d, mpfr_div_ui, mpfr_div_si,
+    mpfr_d_div, mpfr_ui_div, mpfr_si_div
+}
+impl_assign_op! { DivAssign, div_assign, mpfr_div, mpfr_div_d, mpfr_div_ui, mpfr_div_si }
 
-// Remainder
-impl_noncommutative_op! { Rem, rem, mpfr_remainder }
+// Remainder; this is the IEEE 754 remainder (see `Remainder::remainder()`), which differs
+// from `%` on Rust primitives (see `Remainder::fmod()`)
+impl_noncommutative_op! {
+    Rem, rem, mpfr_remainder, remainder_d, remainder_ui, remainder_si,
+    d_remainder, ui_remainder, si_remainder
+}
+impl_assign_op! { RemAssign, rem_assign, mpfr_remainder, remainder_d, remainder_ui, remainder_si }
+
+// Multiplication and division by a power of two
+impl_shift_op! { Shl, shl, ShlAssign, shl_assign, mpfr_mul_2si }
+impl_shift_op! { Shr, shr, ShrAssign, shr_assign, mpfr_div_2si }
+
+impl BigFloat {
+    /// Computes `self * 2^k`, the same as `self << k`.
+    ///
+    /// The result is exact unless it overflows or underflows, in which case
+    /// `Flags::Overflow` or `Flags::Underflow` is set.
+    #[inline]
+    pub fn mul_2exp(&self, k: i32) -> BigFloat {
+        self << k
+    }
+
+    /// Computes `self / 2^k`, the same as `self >> k`.
+    ///
+    /// The result is exact unless it overflows or underflows, in which case
+    /// `Flags::Overflow` or `Flags::Underflow` is set.
+    #[inline]
+    pub fn div_2exp(&self, k: i32) -> BigFloat {
+        self >> k
+    }
+}
 
 impl Num for BigFloat {}
 
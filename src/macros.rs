@@ -6,10 +6,25 @@
 // operands are values, LHS gets a priority. If both of the operands are references,
 // the LHS is cloned and used to hold the result.
 //
-// The order is important if participating values have different precisions.
+// The order is important if participating values have different precisions. When the
+// result precision should be chosen explicitly, `BigFloat::assign_add()` and friends can be
+// used instead; they round the result directly to the precision of the destination.
 //
 // If one of the operands is of primitive type, then either the BigFloat operand will
-// be reused for the result or (if it is a reference) it will be cloned.
+// be reused for the result or (if it is a reference) it will be cloned. Primitive operands
+// are passed to MPFR as C longs, unsigned longs or doubles (see primitive.rs); integers
+// which do not fit into a C long are converted to a BigFloat exactly, so the result is
+// still rounded only once.
+
+// Invokes the given macro with the list of all supported primitive types appended
+macro_rules! with_primitives {
+    ($mac:ident! { $($args:tt)* }) => {
+        $mac! {
+            $($args)*;
+            i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+        }
+    }
+}
 
 // Commutative operations (+, *)
 
@@ -68,22 +83,31 @@ macro_rules! impl_commutative_op_ref_ref {
 }
 
 macro_rules! impl_commutative_op_val_prim {
-    ($tr:ident, $meth:ident, $prim:ty, $c_prim:ty, $mpfr:ident) => {
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident;
+     $($prim:ty),+) => {
+        $(
         impl $tr<$prim> for BigFloat {
             type Output = BigFloat;
 
             fn $meth(mut self, rhs: $prim) -> BigFloat {
                 unsafe {
-                    $mpfr(&mut self.value, &self.value, rhs as $c_prim, grnd());
+                    match rhs.to_operand() {
+                        Operand::Signed(v)   => $mpfr_si(&mut self.value, &self.value, v, grnd()),
+                        Operand::Unsigned(v) => $mpfr_ui(&mut self.value, &self.value, v, grnd()),
+                        Operand::Double(v)   => $mpfr_d(&mut self.value, &self.value, v, grnd()),
+                        Operand::Exact(v)    => $mpfr(&mut self.value, &self.value, &v.value, grnd())
+                    };
                 }
                 self
             }
         }
+        )+
     }
 }
 
 macro_rules! impl_commutative_op_ref_prim {
-    ($tr:ident, $meth:ident, $prim:ty) => {
+    ($tr:ident, $meth:ident; $($prim:ty),+) => {
+        $(
         impl<'r> $tr<$prim> for &'r BigFloat {
             type Output = BigFloat;
 
@@ -92,11 +116,13 @@ macro_rules! impl_commutative_op_ref_prim {
                 self.clone().$meth(rhs)
             }
         }
+        )+
     }
 }
 
 macro_rules! impl_commutative_op_prim_val {
-    ($tr:ident, $meth:ident, $prim:ty) => {
+    ($tr:ident, $meth:ident; $($prim:ty),+) => {
+        $(
         impl $tr<BigFloat> for $prim {
             type Output = BigFloat;
 
@@ -105,11 +131,13 @@ macro_rules! impl_commutative_op_prim_val {
                 rhs.$meth(self)
             }
         }
+        )+
     }
 }
 
 macro_rules! impl_commutative_op_prim_ref {
-    ($tr:ident, $meth:ident, $prim:ty) => {
+    ($tr:ident, $meth:ident; $($prim:ty),+) => {
+        $(
         impl<'r> $tr<&'r BigFloat> for $prim {
             type Output = BigFloat;
 
@@ -118,24 +146,20 @@ macro_rules! impl_commutative_op_prim_ref {
                 rhs.$meth(self)
             }
         }
+        )+
     }
 }
 
 macro_rules! impl_commutative_op {
-    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_f64:ident, $mpfr_u32:ident, $mpfr_i32:ident) => {
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident) => {
         impl_commutative_op_val_ref! { $tr, $meth, $mpfr }
         impl_commutative_op_val_val! { $tr, $meth }
         impl_commutative_op_ref_val! { $tr, $meth }
         impl_commutative_op_ref_ref! { $tr, $meth }
-        impl_commutative_op_val_prim! { $tr, $meth, f64, c_double, $mpfr_f64 }
-        impl_commutative_op_ref_prim! { $tr, $meth, f64 }
-        impl_commutative_op_val_prim! { $tr, $meth, u32, c_ulong, $mpfr_u32 }
-        impl_commutative_op_ref_prim! { $tr, $meth, u32 }
-        impl_commutative_op_val_prim! { $tr, $meth, i32, c_long, $mpfr_i32 }
-        impl_commutative_op_ref_prim! { $tr, $meth, i32 }
-        // Does not work now due to trait coherence rules :(
-        //impl_commutative_op_prim_val! { $tr, $meth, f64 }
-        //impl_commutative_op_prim_ref! { $tr, $meth, f64 }
+        with_primitives! { impl_commutative_op_val_prim! { $tr, $meth, $mpfr, $mpfr_d, $mpfr_ui, $mpfr_si } }
+        with_primitives! { impl_commutative_op_ref_prim! { $tr, $meth } }
+        with_primitives! { impl_commutative_op_prim_val! { $tr, $meth } }
+        with_primitives! { impl_commutative_op_prim_ref! { $tr, $meth } }
     }
 }
 
@@ -169,14 +193,57 @@ macro_rules! impl_noncommutative_op_ref_val {
 }
 
 macro_rules! impl_noncommutative_op_val_prim {
-    ($tr:ident, $meth:ident, $prim:ty, $c_prim:ty, $mpfr:ident) => {
-        impl_commutative_op_val_prim! { $tr, $meth, $prim, $c_prim, $mpfr }
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident;
+     $($prim:ty),+) => {
+        impl_commutative_op_val_prim! { $tr, $meth, $mpfr, $mpfr_d, $mpfr_ui, $mpfr_si; $($prim),+ }
     }
 }
 
 macro_rules! impl_noncommutative_op_ref_prim {
-    ($tr:ident, $meth:ident, $prim:ty) => {
-        impl_commutative_op_ref_prim! { $tr, $meth, $prim }
+    ($tr:ident, $meth:ident; $($prim:ty),+) => {
+        impl_commutative_op_ref_prim! { $tr, $meth; $($prim),+ }
+    }
+}
+
+// Primitive LHS can't simply be swapped with the BigFloat RHS, so MPFR's dedicated
+// functions (mpfr_ui_sub, mpfr_d_div, etc.) are used. The BigFloat operand is reused
+// for the result or cloned if it is a reference.
+
+macro_rules! impl_noncommutative_op_prim_val {
+    ($tr:ident, $meth:ident, $mpfr:ident, $d_mpfr:ident, $ui_mpfr:ident, $si_mpfr:ident;
+     $($prim:ty),+) => {
+        $(
+        impl $tr<BigFloat> for $prim {
+            type Output = BigFloat;
+
+            fn $meth(self, mut rhs: BigFloat) -> BigFloat {
+                unsafe {
+                    match self.to_operand() {
+                        Operand::Signed(v)   => $si_mpfr(&mut rhs.value, v, &rhs.value, grnd()),
+                        Operand::Unsigned(v) => $ui_mpfr(&mut rhs.value, v, &rhs.value, grnd()),
+                        Operand::Double(v)   => $d_mpfr(&mut rhs.value, v, &rhs.value, grnd()),
+                        Operand::Exact(v)    => $mpfr(&mut rhs.value, &v.value, &rhs.value, grnd())
+                    };
+                }
+                rhs
+            }
+        }
+        )+
+    }
+}
+
+macro_rules! impl_noncommutative_op_prim_ref {
+    ($tr:ident, $meth:ident; $($prim:ty),+) => {
+        $(
+        impl<'r> $tr<&'r BigFloat> for $prim {
+            type Output = BigFloat;
+
+            #[inline]
+            fn $meth(self, rhs: &'r BigFloat) -> BigFloat {
+                self.$meth(rhs.clone())
+            }
+        }
+        )+
     }
 }
 
@@ -187,13 +254,104 @@ macro_rules! impl_noncommutative_op {
         impl_noncommutative_op_ref_val! { $tr, $meth, $mpfr }
         impl_noncommutative_op_ref_ref! { $tr, $meth }
     };
-    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_f64:ident, $mpfr_u32:ident, $mpfr_i32:ident) => {
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident,
+     $d_mpfr:ident, $ui_mpfr:ident, $si_mpfr:ident) => {
         impl_noncommutative_op! { $tr, $meth, $mpfr }
-        impl_noncommutative_op_val_prim! { $tr, $meth, f64, c_double, $mpfr_f64 }
-        impl_noncommutative_op_ref_prim! { $tr, $meth, f64 }
-        impl_noncommutative_op_val_prim! { $tr, $meth, u32, c_ulong, $mpfr_u32 }
-        impl_noncommutative_op_ref_prim! { $tr, $meth, u32 }
-        impl_noncommutative_op_val_prim! { $tr, $meth, i32, c_long, $mpfr_i32 }
-        impl_noncommutative_op_ref_prim! { $tr, $meth, i32 }
+        with_primitives! { impl_noncommutative_op_val_prim! { $tr, $meth, $mpfr, $mpfr_d, $mpfr_ui, $mpfr_si } }
+        with_primitives! { impl_noncommutative_op_ref_prim! { $tr, $meth } }
+        with_primitives! { impl_noncommutative_op_prim_val! { $tr, $meth, $mpfr, $d_mpfr, $ui_mpfr, $si_mpfr } }
+        with_primitives! { impl_noncommutative_op_prim_ref! { $tr, $meth } }
+    }
+}
+
+// Compound assignment operations (+=, -=, *=, /=, %=)
+//
+// The LHS is always used to hold the result, so its precision is preserved regardless
+// of the precision of the RHS.
+
+macro_rules! impl_assign_op_ref {
+    ($tr:ident, $meth:ident, $mpfr:ident) => {
+        impl<'a> $tr<&'a BigFloat> for BigFloat {
+            fn $meth(&mut self, rhs: &'a BigFloat) {
+                unsafe {
+                    $mpfr(&mut self.value, &self.value, &rhs.value, grnd());
+                }
+            }
+        }
+    }
+}
+
+macro_rules! impl_assign_op_val {
+    ($tr:ident, $meth:ident) => {
+        impl $tr<BigFloat> for BigFloat {
+            #[inline]
+            fn $meth(&mut self, rhs: BigFloat) {
+                self.$meth(&rhs)
+            }
+        }
+    }
+}
+
+macro_rules! impl_assign_op_prim {
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident;
+     $($prim:ty),+) => {
+        $(
+        impl $tr<$prim> for BigFloat {
+            fn $meth(&mut self, rhs: $prim) {
+                unsafe {
+                    match rhs.to_operand() {
+                        Operand::Signed(v)   => $mpfr_si(&mut self.value, &self.value, v, grnd()),
+                        Operand::Unsigned(v) => $mpfr_ui(&mut self.value, &self.value, v, grnd()),
+                        Operand::Double(v)   => $mpfr_d(&mut self.value, &self.value, v, grnd()),
+                        Operand::Exact(v)    => $mpfr(&mut self.value, &self.value, &v.value, grnd())
+                    };
+                }
+            }
+        }
+        )+
+    }
+}
+
+macro_rules! impl_assign_op {
+    ($tr:ident, $meth:ident, $mpfr:ident, $mpfr_d:ident, $mpfr_ui:ident, $mpfr_si:ident) => {
+        impl_assign_op_ref! { $tr, $meth, $mpfr }
+        impl_assign_op_val! { $tr, $meth }
+        with_primitives! { impl_assign_op_prim! { $tr, $meth, $mpfr, $mpfr_d, $mpfr_ui, $mpfr_si } }
+    }
+}
+
+// Shift operations (<<, >>), i.e. multiplication and division by a power of two.
+//
+// These are exact unless the result overflows or underflows, which is reported through
+// the Overflow and Underflow flags.
+
+macro_rules! impl_shift_op {
+    ($tr:ident, $meth:ident, $tr_assign:ident, $meth_assign:ident, $mpfr:ident) => {
+        impl $tr_assign<i32> for BigFloat {
+            fn $meth_assign(&mut self, k: i32) {
+                unsafe {
+                    $mpfr(&mut self.value, &self.value, k as c_long, grnd());
+                }
+            }
+        }
+
+        impl $tr<i32> for BigFloat {
+            type Output = BigFloat;
+
+            #[inline]
+            fn $meth(mut self, k: i32) -> BigFloat {
+                self.$meth_assign(k);
+                self
+            }
+        }
+
+        impl<'r> $tr<i32> for &'r BigFloat {
+            type Output = BigFloat;
+
+            #[inline]
+            fn $meth(self, k: i32) -> BigFloat {
+                self.clone().$meth(k)
+            }
+        }
     }
 }
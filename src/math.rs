@@ -1,8 +1,18 @@
-use libc::c_ulong;
+use std::cmp::Ordering;
+
+use libc::{c_int, c_long, c_ulong};
 
 use mpfr_sys::*;
 
-use {BigFloat, grnd};
+use {BigFloat, Precision, RoundingMode, Sign, global_rounding_mode, grnd, ternary};
+use ext::{mpfr_rint_roundeven, mpfr_beta, mpfr_gamma_inc};
+use ext::{mpfr_sinpi, mpfr_cospi, mpfr_tanpi, mpfr_sinu, mpfr_cosu, mpfr_tanu};
+use ext::{mpfr_asinpi, mpfr_acospi, mpfr_atanpi};
+use reciprocal::{acot, asec, acsc, acoth, asech, acsch};
+use special::{lambert_w0, lambert_wm1, erf_inv, erfc_inv, expint_n};
+use zeta::{polylog, polylog_si, hurwitz_zeta, clausen, dirichlet_eta};
+use elliptic::{elliptic_k, elliptic_e, elliptic_f, elliptic_e_inc};
+use elliptic::{jacobi_sn, jacobi_cn, jacobi_dn};
 
 pub trait Math {
     type Output;
@@ -39,19 +49,326 @@ pub trait Math {
     fn atanh(self) -> Self::Output;
     fn log1p(self) -> Self::Output;
     fn expm1(self) -> Self::Output;
+
+    /// Rounds to the next lower or equal integer.
+    fn floor(self) -> Self::Output;
+    /// Rounds to the next higher or equal integer.
+    fn ceil(self) -> Self::Output;
+    /// Rounds to the nearest integer, ties away from zero.
+    fn round(self) -> Self::Output;
+    /// Rounds to the nearest integer, ties to even.
+    fn round_even(self) -> Self::Output;
+    /// Rounds to the next integer towards zero.
+    fn trunc(self) -> Self::Output;
+    /// Rounds to an integer in the direction of the global rounding mode.
+    fn rint(self) -> Self::Output;
+    /// Returns the fractional part, which has the same sign as the number.
+    fn frac(self) -> Self::Output;
+    /// Returns the integral and the fractional parts at once.
+    fn modf(self) -> (Self::Output, Self::Output);
+    /// Computes the sine and the cosine at once, which is faster than computing them
+    /// separately.
+    fn sin_cos(self) -> (Self::Output, Self::Output);
+    /// Computes the hyperbolic sine and the hyperbolic cosine at once.
+    fn sinh_cosh(self) -> (Self::Output, Self::Output);
+    /// Returns the absolute value with the sign set to negative if `neg` is true.
+    fn setsign(self, neg: bool) -> Self::Output;
+
+    /// Computes the Gamma function.
+    fn gamma(self) -> Self::Output;
+    /// Computes the logarithm of the Gamma function. The result is NaN where the Gamma function
+    /// is negative, see `lgamma()`.
+    fn lngamma(self) -> Self::Output;
+    /// Computes the logarithm of the absolute value of the Gamma function, also returning
+    /// the sign of the Gamma function.
+    fn lgamma(self) -> (Self::Output, Sign);
+    /// Computes the Digamma function, the logarithmic derivative of the Gamma function.
+    fn digamma(self) -> Self::Output;
+    /// Computes the Beta function `B(self, y)`.
+    ///
+    /// Zeros, infinities and the poles of the Gamma function give the same values as in
+    /// MPFR 4, e.g. `B(-m, n) = (-1)^n B(n, m - n + 1)` for positive integers `n < m`.
+    fn beta(self, y: &BigFloat) -> Self::Output;
+    /// Computes the upper incomplete Gamma function `Γ(self, x)`.
+    ///
+    /// Without the `mpfr4` feature only positive `self` and non-negative `x` are supported,
+    /// NaN is returned otherwise. With the feature the computation is slow for large `x`.
+    fn gamma_inc(self, x: &BigFloat) -> Self::Output;
+    /// Computes the Riemann Zeta function.
+    fn zeta(self) -> Self::Output;
+
+    /// Computes the error function.
+    fn erf(self) -> Self::Output;
+    /// Computes the complementary error function `1 - erf(x)`.
+    fn erfc(self) -> Self::Output;
+    /// Computes the exponential integral `Ei(x)`.
+    ///
+    /// For negative `x` MPFR 4 (the `mpfr4` feature) gives `Ei(x) = -E1(-x)`, while older
+    /// versions of MPFR give NaN.
+    fn eint(self) -> Self::Output;
+    /// Computes the real part of the dilogarithm `-∫ log(1 - t) / t dt` from 0 to `x`.
+    fn li2(self) -> Self::Output;
+    /// Computes the Airy function `Ai(x)`.
+    fn ai(self) -> Self::Output;
+    /// Computes the Bessel function of the first kind of order 0.
+    fn j0(self) -> Self::Output;
+    /// Computes the Bessel function of the first kind of order 1.
+    fn j1(self) -> Self::Output;
+    /// Computes the Bessel function of the first kind of order `n`. Orders which do not fit
+    /// a C `long` give NaN and raise the erange flag.
+    fn jn(self, n: i64) -> Self::Output;
+    /// Computes the Bessel function of the second kind of order 0.
+    fn y0(self) -> Self::Output;
+    /// Computes the Bessel function of the second kind of order 1.
+    fn y1(self) -> Self::Output;
+    /// Computes the Bessel function of the second kind of order `n`. Orders which do not fit
+    /// a C `long` give NaN and raise the erange flag.
+    fn yn(self, n: i64) -> Self::Output;
+
+    /// Computes `sin(πx)`, exactly for all arguments where the result is rational.
+    fn sinpi(self) -> Self::Output;
+    /// Computes `cos(πx)`, exactly for all arguments where the result is rational.
+    fn cospi(self) -> Self::Output;
+    /// Computes `tan(πx)`, exactly for all arguments where the result is rational.
+    fn tanpi(self) -> Self::Output;
+    /// Computes the sine of an angle measured in units of `1/u` of the full turn, i.e.
+    /// `sin(2πx/u)`; `sinu(360)` gives the sine of an angle in degrees.
+    fn sinu(self, u: u32) -> Self::Output;
+    /// Computes `cos(2πx/u)`, see `sinu()`.
+    fn cosu(self, u: u32) -> Self::Output;
+    /// Computes `tan(2πx/u)`, see `sinu()`.
+    fn tanu(self, u: u32) -> Self::Output;
+    /// Computes `asin(x)/π`.
+    fn asinpi(self) -> Self::Output;
+    /// Computes `acos(x)/π`.
+    fn acospi(self) -> Self::Output;
+    /// Computes `atan(x)/π`.
+    fn atanpi(self) -> Self::Output;
+
+    /// Computes the inverse cotangent `atan(1/x)`, with the values in `[-π/2, π/2]`:
+    /// `acot(+0) = π/2` and `acot(-0) = -π/2`, and infinities give zeros of their sign.
+    fn acot(self) -> Self::Output;
+    /// Computes the inverse secant `acos(1/x)`. The result is NaN for `|x| < 1`.
+    fn asec(self) -> Self::Output;
+    /// Computes the inverse cosecant `asin(1/x)`. The result is NaN for `|x| < 1`.
+    fn acsc(self) -> Self::Output;
+    /// Computes the inverse hyperbolic cotangent `atanh(1/x)`.
+    /// The result is NaN for `|x| < 1`.
+    fn acoth(self) -> Self::Output;
+    /// Computes the inverse hyperbolic secant `acosh(1/x)`.
+    /// The result is NaN outside of `[0, 1]`.
+    fn asech(self) -> Self::Output;
+    /// Computes the inverse hyperbolic cosecant `asinh(1/x)`.
+    fn acsch(self) -> Self::Output;
+
+    /// Computes the principal branch of the Lambert W function, the solution `w >= -1` of
+    /// `w e^w = x`. The result is NaN for `x < -1/e`.
+    fn lambert_w0(self) -> Self::Output;
+    /// Computes the lower branch of the Lambert W function, the solution `w <= -1` of
+    /// `w e^w = x`. The result is NaN outside of `[-1/e, 0]`.
+    fn lambert_wm1(self) -> Self::Output;
+    /// Computes the inverse error function. The result is NaN outside of `[-1, 1]`.
+    fn erf_inv(self) -> Self::Output;
+    /// Computes the inverse complementary error function. The result is NaN outside
+    /// of `[0, 2]`.
+    fn erfc_inv(self) -> Self::Output;
+    /// Computes the generalized exponential integral `E_n(x) = ∫ e^(-xt) / t^n dt` from 1
+    /// to ∞. The result is NaN for negative `x`.
+    fn expint_n(self, n: u32) -> Self::Output;
+
+    /// Computes the polylogarithm `Li_s(x) = Σ x^k / k^s` of this number `x` and a real
+    /// order `s`. The result is NaN for `x > 1`.
+    fn polylog(self, s: &BigFloat) -> Self::Output;
+    /// Computes the polylogarithm `Li_n(x)` of an integer order `n`. For `n <= 0` it is
+    /// a rational function of `x`, otherwise the result is NaN for `x > 1`.
+    fn polylog_si(self, n: i32) -> Self::Output;
+    /// Computes the Hurwitz zeta function `ζ(s, a) = Σ 1 / (a + k)^s` of this number `s`.
+    /// The result is NaN for `a <= 0`.
+    fn hurwitz_zeta(self, a: &BigFloat) -> Self::Output;
+    /// Computes the Clausen function `Cl_2(x) = Σ sin(kx) / k^2`.
+    fn clausen(self) -> Self::Output;
+    /// Computes the Dirichlet eta function `η(s) = Σ (-1)^(k-1) / k^s`.
+    fn dirichlet_eta(self) -> Self::Output;
+
+    /// Computes the complete elliptic integral of the first kind `K(m)` of this parameter
+    /// `m = k²`. The result is NaN for `m > 1`.
+    fn elliptic_k(self) -> Self::Output;
+    /// Computes the complete elliptic integral of the second kind `E(m)` of this parameter
+    /// `m = k²`. The result is NaN for `m > 1`.
+    fn elliptic_e(self) -> Self::Output;
+    /// Computes the incomplete elliptic integral of the first kind `F(φ, m)` of this amplitude
+    /// `φ`. For `m >= 1` the result is NaN unless `m sin² θ < 1` for all `θ` up to `φ`.
+    fn elliptic_f(self, m: &BigFloat) -> Self::Output;
+    /// Computes the incomplete elliptic integral of the second kind `E(φ, m)` of this
+    /// amplitude `φ`, with the same domain as `elliptic_f()` for `m > 1`.
+    fn elliptic_e_inc(self, m: &BigFloat) -> Self::Output;
+    /// Computes the Jacobi elliptic function `sn(u, m)` of this number `u`.
+    fn jacobi_sn(self, m: &BigFloat) -> Self::Output;
+    /// Computes the Jacobi elliptic function `cn(u, m)` of this number `u`.
+    fn jacobi_cn(self, m: &BigFloat) -> Self::Output;
+    /// Computes the Jacobi elliptic function `dn(u, m)` of this number `u`.
+    fn jacobi_dn(self, m: &BigFloat) -> Self::Output;
+
+    // The same functions with an explicit rounding mode; they also return the ternary value
+    // (`Greater` if the result was rounded up, `Less` if down, `Equal` if it is exact)
+    fn sqr_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sqrt_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sqrt_rec_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cbrt_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn root_round(self, k: u32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn abs_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn log_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn log2_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn log10_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn exp_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn exp2_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn exp10_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sin_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cos_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn tan_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sec_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn csc_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cot_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acos_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn asin_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn atan_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cosh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sinh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn tanh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sech_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn csch_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn coth_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acosh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn asinh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn atanh_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn log1p_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn expm1_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn floor_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn ceil_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn round_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn round_even_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn trunc_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn rint_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn frac_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn setsign_round(self, neg: bool, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn gamma_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn lngamma_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn lgamma_round(self, rnd: RoundingMode) -> (Self::Output, Sign, Ordering);
+    fn digamma_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn beta_round(self, y: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn gamma_inc_round(self, x: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn zeta_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn erf_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn erfc_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn eint_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn li2_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn ai_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn j0_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn j1_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn jn_round(self, n: i64, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn y0_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn y1_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn yn_round(self, n: i64, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sinpi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cospi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn tanpi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn sinu_round(self, u: u32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn cosu_round(self, u: u32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn tanu_round(self, u: u32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn asinpi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acospi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn atanpi_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acot_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn asec_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acsc_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acoth_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn asech_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn acsch_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn lambert_w0_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn lambert_wm1_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn erf_inv_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn erfc_inv_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn expint_n_round(self, n: u32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn polylog_round(self, s: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn polylog_si_round(self, n: i32, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn hurwitz_zeta_round(self, a: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn clausen_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn dirichlet_eta_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn elliptic_k_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn elliptic_e_round(self, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn elliptic_f_round(self, m: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn elliptic_e_inc_round(self, m: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn jacobi_sn_round(self, m: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn jacobi_cn_round(self, m: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
+    fn jacobi_dn_round(self, m: &BigFloat, rnd: RoundingMode) -> (Self::Output, Ordering);
 }
 
+// Functions are grouped by their signatures:
+// - unary: `fn(x, args...) -> y`, backed by functions like `mpfr_sin(rop, op, args..., rnd)`;
+// - pair: `fn(x) -> (y, z)`, backed by functions like `mpfr_modf(rop1, rop2, op, rnd)`;
+// - signed: `fn(x) -> (y, sign)`, backed by functions like `mpfr_lgamma(rop, signp, op, rnd)`;
+// - binary: `fn(x, y: &BigFloat) -> z`, backed by functions like `mpfr_beta(rop, x, y, rnd)`.
+
 macro_rules! impl_math_val {
-    ($($meth:ident($($p:ident: $t:ty as $ct:ty),*) -> $mpfr:ident);+) => {
+    (unary { $($meth:ident, $round:ident, $assign:ident($($p:ident: $t:ty as $ct:ty),*)
+               -> $mpfr:ident);+ }
+     pair { $($pmeth:ident, $pwith:ident, $passign:ident -> $pmpfr:ident);+ }
+     signed { $($smeth:ident, $sround:ident, $sassign:ident -> $smpfr:ident);+ }
+     binary { $($bmeth:ident, $bround:ident, $bassign:ident -> $bmpfr:ident);+ }) => {
         impl Math for BigFloat {
             type Output = BigFloat;
 
             $(
-            fn $meth(mut self, $($p: $t),*) -> BigFloat {
+            #[inline]
+            fn $meth(self, $($p: $t),*) -> BigFloat {
+                self.$round($($p,)* global_rounding_mode::get()).0
+            }
+
+            fn $round(mut self, $($p: $t,)* rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let t = unsafe {
+                    $mpfr(&mut self.value, &self.value $(, $p as $ct)*, rnd as mpfr_rnd_t)
+                };
+                (self, ternary(t))
+            }
+            )+
+
+            $(
+            fn $pmeth(mut self) -> (BigFloat, BigFloat) {
+                let mut other = BigFloat::fresh_with_prec(self.prec());
                 unsafe {
-                    $mpfr(&mut self.value, &self.value $(, $p as $ct)*, grnd());
+                    $pmpfr(&mut self.value, &mut other.value, &self.value, grnd());
                 }
-                self
+                (self, other)
+            }
+            )+
+
+            $(
+            #[inline]
+            fn $smeth(self) -> (BigFloat, Sign) {
+                let (r, sign, _) = self.$sround(global_rounding_mode::get());
+                (r, sign)
+            }
+
+            fn $sround(mut self, rnd: RoundingMode) -> (BigFloat, Sign, Ordering) {
+                let mut sign: c_int = 0;
+                let t = unsafe {
+                    $smpfr(&mut self.value, &mut sign, &self.value, rnd as mpfr_rnd_t)
+                };
+                (self, Sign::from_int(sign), ternary(t))
+            }
+            )+
+
+            $(
+            #[inline]
+            fn $bmeth(self, y: &BigFloat) -> BigFloat {
+                self.$bround(y, global_rounding_mode::get()).0
+            }
+
+            fn $bround(mut self, y: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                let t = unsafe {
+                    $bmpfr(&mut self.value, &self.value, &y.value, rnd as mpfr_rnd_t)
+                };
+                (self, ternary(t))
             }
             )+
         }
@@ -59,7 +376,11 @@ macro_rules! impl_math_val {
 }
 
 macro_rules! impl_math_ref {
-    ($($meth:ident($($p:ident: $t:ty as $ct:ty),*) -> $mpfr:ident);+) => {
+    (unary { $($meth:ident, $round:ident, $assign:ident($($p:ident: $t:ty as $ct:ty),*)
+               -> $mpfr:ident);+ }
+     pair { $($pmeth:ident, $pwith:ident, $passign:ident -> $pmpfr:ident);+ }
+     signed { $($smeth:ident, $sround:ident, $sassign:ident -> $smpfr:ident);+ }
+     binary { $($bmeth:ident, $bround:ident, $bassign:ident -> $bmpfr:ident);+ }) => {
         impl<'r> Math for &'r BigFloat {
             type Output = BigFloat;
 
@@ -68,6 +389,97 @@ macro_rules! impl_math_ref {
             fn $meth(self, $($p: $t),*) -> BigFloat {
                 self.clone().$meth($($p),*)
             }
+
+            #[inline]
+            fn $round(self, $($p: $t,)* rnd: RoundingMode) -> (BigFloat, Ordering) {
+                self.clone().$round($($p,)* rnd)
+            }
+            )+
+
+            $(
+            #[inline]
+            fn $pmeth(self) -> (BigFloat, BigFloat) {
+                self.clone().$pmeth()
+            }
+            )+
+
+            $(
+            #[inline]
+            fn $smeth(self) -> (BigFloat, Sign) {
+                self.clone().$smeth()
+            }
+
+            #[inline]
+            fn $sround(self, rnd: RoundingMode) -> (BigFloat, Sign, Ordering) {
+                self.clone().$sround(rnd)
+            }
+            )+
+
+            $(
+            #[inline]
+            fn $bmeth(self, y: &BigFloat) -> BigFloat {
+                self.clone().$bmeth(y)
+            }
+
+            #[inline]
+            fn $bround(self, y: &BigFloat, rnd: RoundingMode) -> (BigFloat, Ordering) {
+                self.clone().$bround(y, rnd)
+            }
+            )+
+        }
+    }
+}
+
+// Destination-writing variants: the result is computed directly into `self` and rounded
+// to its precision, the previous value is discarded. Functions with two results store
+// the second one into `other`, and also have variants which create both results with
+// the given precisions.
+macro_rules! impl_math_assign {
+    (unary { $($meth:ident, $round:ident, $assign:ident($($p:ident: $t:ty as $ct:ty),*)
+               -> $mpfr:ident);+ }
+     pair { $($pmeth:ident, $pwith:ident, $passign:ident -> $pmpfr:ident);+ }
+     signed { $($smeth:ident, $sround:ident, $sassign:ident -> $smpfr:ident);+ }
+     binary { $($bmeth:ident, $bround:ident, $bassign:ident -> $bmpfr:ident);+ }) => {
+        impl BigFloat {
+            $(
+            pub fn $assign(&mut self, x: &BigFloat $(, $p: $t)*) {
+                unsafe {
+                    $mpfr(&mut self.value, &x.value $(, $p as $ct)*, grnd());
+                }
+            }
+            )+
+
+            $(
+            pub fn $pwith(&self, first: Precision, second: Precision) -> (BigFloat, BigFloat) {
+                let mut r1 = BigFloat::fresh_with_prec(first);
+                let mut r2 = BigFloat::fresh_with_prec(second);
+                r1.$passign(&mut r2, self);
+                (r1, r2)
+            }
+
+            pub fn $passign(&mut self, other: &mut BigFloat, x: &BigFloat) {
+                unsafe {
+                    $pmpfr(&mut self.value, &mut other.value, &x.value, grnd());
+                }
+            }
+            )+
+
+            $(
+            pub fn $sassign(&mut self, x: &BigFloat) -> Sign {
+                let mut sign: c_int = 0;
+                unsafe {
+                    $smpfr(&mut self.value, &mut sign, &x.value, grnd());
+                }
+                Sign::from_int(sign)
+            }
+            )+
+
+            $(
+            pub fn $bassign(&mut self, x: &BigFloat, y: &BigFloat) {
+                unsafe {
+                    $bmpfr(&mut self.value, &x.value, &y.value, grnd());
+                }
+            }
             )+
         }
     }
@@ -77,40 +489,236 @@ macro_rules! impl_math_all {
     ($($args:tt)*) => {
         impl_math_val! { $($args)* }
         impl_math_ref! { $($args)* }
+        impl_math_assign! { $($args)* }
+    }
+}
+
+// In-place variants of the integer rounding functions
+macro_rules! impl_math_in_place {
+    ($($(#[$attr:meta])* fn $meth:ident -> $mpfr:ident),+) => {
+        impl BigFloat {
+            $(
+            $(#[$attr])*
+            pub fn $meth(&mut self) {
+                unsafe {
+                    $mpfr(&mut self.value, &self.value, grnd());
+                }
+            }
+            )+
+        }
+    }
+}
+
+// mpfr_jn and mpfr_yn take the order before the argument, as a long which may have only
+// 32 bits; orders beyond it give NaN and raise the erange flag
+
+fn order(n: i64) -> Option<c_long> {
+    if n >= c_long::min_value() as i64 && n <= c_long::max_value() as i64 {
+        Some(n as c_long)
+    } else {
+        None
+    }
+}
+
+unsafe fn jn(rop: mpfr_ptr, op: mpfr_srcptr, n: i64, rnd: mpfr_rnd_t) -> c_int {
+    match order(n) {
+        Some(n) => mpfr_jn(rop, n, op, rnd),
+        None => {
+            mpfr_set_nan(rop);
+            mpfr_set_erangeflag();
+            0
+        }
+    }
+}
+
+unsafe fn yn(rop: mpfr_ptr, op: mpfr_srcptr, n: i64, rnd: mpfr_rnd_t) -> c_int {
+    match order(n) {
+        Some(n) => mpfr_yn(rop, n, op, rnd),
+        None => {
+            mpfr_set_nan(rop);
+            mpfr_set_erangeflag();
+            0
+        }
     }
 }
 
 impl_math_all! {
-    sqr() -> mpfr_sqr;
-    sqrt() -> mpfr_sqrt;
-    sqrt_rec() -> mpfr_rec_sqrt;
-    cbrt() -> mpfr_cbrt;
-    root(k: u32 as c_ulong) -> mpfr_root;
-    abs() -> mpfr_abs;
-    log() -> mpfr_log;
-    log2() -> mpfr_log2;
-    log10() -> mpfr_log10;
-    exp() -> mpfr_exp;
-    exp2() -> mpfr_exp2;
-    exp10() -> mpfr_exp10;
-    sin() -> mpfr_sin;
-    cos() -> mpfr_cos;
-    tan() -> mpfr_tan;
-    sec() -> mpfr_sec;
-    csc() -> mpfr_csc;
-    cot() -> mpfr_cot;
-    acos() -> mpfr_acos;
-    asin() -> mpfr_asin;
-    atan() -> mpfr_atan;
-    cosh() -> mpfr_cosh;
-    sinh() -> mpfr_sinh;
-    tanh() -> mpfr_tanh;
-    sech() -> mpfr_sech;
-    csch() -> mpfr_csch;
-    coth() -> mpfr_coth;
-    acosh() -> mpfr_acosh;
-    asinh() -> mpfr_asinh;
-    atanh() -> mpfr_atanh;
-    log1p() -> mpfr_log1p;
-    expm1() -> mpfr_expm1
+    unary {
+        sqr, sqr_round, assign_sqr() -> mpfr_sqr;
+        sqrt, sqrt_round, assign_sqrt() -> mpfr_sqrt;
+        sqrt_rec, sqrt_rec_round, assign_sqrt_rec() -> mpfr_rec_sqrt;
+        cbrt, cbrt_round, assign_cbrt() -> mpfr_cbrt;
+        root, root_round, assign_root(k: u32 as c_ulong) -> mpfr_root;
+        abs, abs_round, assign_abs() -> mpfr_abs;
+        log, log_round, assign_log() -> mpfr_log;
+        log2, log2_round, assign_log2() -> mpfr_log2;
+        log10, log10_round, assign_log10() -> mpfr_log10;
+        exp, exp_round, assign_exp() -> mpfr_exp;
+        exp2, exp2_round, assign_exp2() -> mpfr_exp2;
+        exp10, exp10_round, assign_exp10() -> mpfr_exp10;
+        sin, sin_round, assign_sin() -> mpfr_sin;
+        cos, cos_round, assign_cos() -> mpfr_cos;
+        tan, tan_round, assign_tan() -> mpfr_tan;
+        sec, sec_round, assign_sec() -> mpfr_sec;
+        csc, csc_round, assign_csc() -> mpfr_csc;
+        cot, cot_round, assign_cot() -> mpfr_cot;
+        acos, acos_round, assign_acos() -> mpfr_acos;
+        asin, asin_round, assign_asin() -> mpfr_asin;
+        atan, atan_round, assign_atan() -> mpfr_atan;
+        cosh, cosh_round, assign_cosh() -> mpfr_cosh;
+        sinh, sinh_round, assign_sinh() -> mpfr_sinh;
+        tanh, tanh_round, assign_tanh() -> mpfr_tanh;
+        sech, sech_round, assign_sech() -> mpfr_sech;
+        csch, csch_round, assign_csch() -> mpfr_csch;
+        coth, coth_round, assign_coth() -> mpfr_coth;
+        acosh, acosh_round, assign_acosh() -> mpfr_acosh;
+        asinh, asinh_round, assign_asinh() -> mpfr_asinh;
+        atanh, atanh_round, assign_atanh() -> mpfr_atanh;
+        log1p, log1p_round, assign_log1p() -> mpfr_log1p;
+        expm1, expm1_round, assign_expm1() -> mpfr_expm1;
+        floor, floor_round, assign_floor() -> mpfr_rint_floor;
+        ceil, ceil_round, assign_ceil() -> mpfr_rint_ceil;
+        round, round_round, assign_round() -> mpfr_rint_round;
+        round_even, round_even_round, assign_round_even() -> mpfr_rint_roundeven;
+        trunc, trunc_round, assign_trunc() -> mpfr_rint_trunc;
+        rint, rint_round, assign_rint() -> mpfr_rint;
+        frac, frac_round, assign_frac() -> mpfr_frac;
+        setsign, setsign_round, assign_setsign(neg: bool as c_int) -> mpfr_setsign;
+        gamma, gamma_round, assign_gamma() -> mpfr_gamma;
+        lngamma, lngamma_round, assign_lngamma() -> mpfr_lngamma;
+        digamma, digamma_round, assign_digamma() -> mpfr_digamma;
+        zeta, zeta_round, assign_zeta() -> mpfr_zeta;
+        erf, erf_round, assign_erf() -> mpfr_erf;
+        erfc, erfc_round, assign_erfc() -> mpfr_erfc;
+        eint, eint_round, assign_eint() -> mpfr_eint;
+        li2, li2_round, assign_li2() -> mpfr_li2;
+        ai, ai_round, assign_ai() -> mpfr_ai;
+        j0, j0_round, assign_j0() -> mpfr_j0;
+        j1, j1_round, assign_j1() -> mpfr_j1;
+        jn, jn_round, assign_jn(n: i64 as i64) -> jn;
+        y0, y0_round, assign_y0() -> mpfr_y0;
+        y1, y1_round, assign_y1() -> mpfr_y1;
+        yn, yn_round, assign_yn(n: i64 as i64) -> yn;
+        sinpi, sinpi_round, assign_sinpi() -> mpfr_sinpi;
+        cospi, cospi_round, assign_cospi() -> mpfr_cospi;
+        tanpi, tanpi_round, assign_tanpi() -> mpfr_tanpi;
+        sinu, sinu_round, assign_sinu(u: u32 as c_ulong) -> mpfr_sinu;
+        cosu, cosu_round, assign_cosu(u: u32 as c_ulong) -> mpfr_cosu;
+        tanu, tanu_round, assign_tanu(u: u32 as c_ulong) -> mpfr_tanu;
+        asinpi, asinpi_round, assign_asinpi() -> mpfr_asinpi;
+        acospi, acospi_round, assign_acospi() -> mpfr_acospi;
+        atanpi, atanpi_round, assign_atanpi() -> mpfr_atanpi;
+        acot, acot_round, assign_acot() -> acot;
+        asec, asec_round, assign_asec() -> asec;
+        acsc, acsc_round, assign_acsc() -> acsc;
+        acoth, acoth_round, assign_acoth() -> acoth;
+        asech, asech_round, assign_asech() -> asech;
+        acsch, acsch_round, assign_acsch() -> acsch;
+        lambert_w0, lambert_w0_round, assign_lambert_w0() -> lambert_w0;
+        lambert_wm1, lambert_wm1_round, assign_lambert_wm1() -> lambert_wm1;
+        erf_inv, erf_inv_round, assign_erf_inv() -> erf_inv;
+        erfc_inv, erfc_inv_round, assign_erfc_inv() -> erfc_inv;
+        expint_n, expint_n_round, assign_expint_n(n: u32 as c_ulong) -> expint_n;
+        polylog_si, polylog_si_round, assign_polylog_si(n: i32 as c_long) -> polylog_si;
+        clausen, clausen_round, assign_clausen() -> clausen;
+        dirichlet_eta, dirichlet_eta_round, assign_dirichlet_eta() -> dirichlet_eta;
+        elliptic_k, elliptic_k_round, assign_elliptic_k() -> elliptic_k;
+        elliptic_e, elliptic_e_round, assign_elliptic_e() -> elliptic_e
+    }
+    pair {
+        modf, modf_with_prec, assign_modf -> mpfr_modf;
+        sin_cos, sin_cos_with_prec, assign_sin_cos -> mpfr_sin_cos;
+        sinh_cosh, sinh_cosh_with_prec, assign_sinh_cosh -> mpfr_sinh_cosh
+    }
+    signed {
+        lgamma, lgamma_round, assign_lgamma -> mpfr_lgamma
+    }
+    binary {
+        beta, beta_round, assign_beta -> mpfr_beta;
+        gamma_inc, gamma_inc_round, assign_gamma_inc -> mpfr_gamma_inc;
+        polylog, polylog_round, assign_polylog -> polylog;
+        hurwitz_zeta, hurwitz_zeta_round, assign_hurwitz_zeta -> hurwitz_zeta;
+        elliptic_f, elliptic_f_round, assign_elliptic_f -> elliptic_f;
+        elliptic_e_inc, elliptic_e_inc_round, assign_elliptic_e_inc -> elliptic_e_inc;
+        jacobi_sn, jacobi_sn_round, assign_jacobi_sn -> jacobi_sn;
+        jacobi_cn, jacobi_cn_round, assign_jacobi_cn -> jacobi_cn;
+        jacobi_dn, jacobi_dn_round, assign_jacobi_dn -> jacobi_dn
+    }
+}
+
+impl_math_in_place! {
+    #[doc="Rounds this number to the next lower or equal integer in place."]
+    fn floor_mut -> mpfr_rint_floor,
+    #[doc="Rounds this number to the next higher or equal integer in place."]
+    fn ceil_mut -> mpfr_rint_ceil,
+    #[doc="Rounds this number to the nearest integer, ties away from zero, in place."]
+    fn round_mut -> mpfr_rint_round,
+    #[doc="Rounds this number to the nearest integer, ties to even, in place."]
+    fn round_even_mut -> mpfr_rint_roundeven,
+    #[doc="Rounds this number to the next integer towards zero in place."]
+    fn trunc_mut -> mpfr_rint_trunc,
+    #[doc="Rounds this number to an integer in the direction of the global rounding mode \
+           in place."]
+    fn rint_mut -> mpfr_rint,
+    #[doc="Replaces this number with its fractional part."]
+    fn frac_mut -> mpfr_frac
+}
+
+// mpfr_fac_ui and mpfr_zeta_ui take an unsigned long, which may have only 32 bits
+
+unsafe fn fac(rop: mpfr_ptr, n: u64, rnd: mpfr_rnd_t) -> c_int {
+    let emax = mpfr_get_emax() as i64;
+    if n > c_ulong::max_value() as u64 || emax >= 4 && n > emax as u64 {
+        // n! > 2^n for n >= 4, and exponents are no wider than an unsigned long
+        return mpfr_set_ui_2exp(rop, 1, mpfr_get_emax(), rnd);
+    }
+    mpfr_fac_ui(rop, n as c_ulong, rnd)
+}
+
+unsafe fn zeta_ui(rop: mpfr_ptr, n: u64, rnd: mpfr_rnd_t) -> c_int {
+    if n <= c_ulong::max_value() as u64 {
+        return mpfr_zeta_ui(rop, n as c_ulong, rnd);
+    }
+    // 0 < ζ(n) - 1 < 2^(2 - n) is far below any ulp of 1, as precisions fit a long
+    mpfr_set_ui(rop, 1, MPFR_RNDN);
+    mpfr_set_inexflag();
+    if rnd == MPFR_RNDU || rnd == MPFR_RNDA {
+        mpfr_nextabove(rop);
+        1
+    } else {
+        -1
+    }
+}
+
+// Setters for the functions of integer arguments, see also the corresponding
+// constructors in the builder
+macro_rules! generate_integer_setters {
+    ($($(#[$attr:meta])* fn $method:ident, $round:ident -> $mpfr:ident),+) => (
+        impl BigFloat {
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub fn $method(&mut self, n: u64) {
+                self.$round(n, global_rounding_mode::get());
+            }
+
+            $(#[$attr])*
+            ///
+            /// The result is rounded with the given rounding mode instead of the global one.
+            /// Returns the ternary value.
+            pub fn $round(&mut self, n: u64, rnd: RoundingMode) -> Ordering {
+                unsafe {
+                    ternary($mpfr(&mut self.value, n, rnd as mpfr_rnd_t))
+                }
+            }
+        )+
+        }
+    )
+}
+
+generate_integer_setters! {
+    #[doc="Sets this number to `n!`."]
+    fn set_to_factorial, set_to_factorial_round -> fac,
+    #[doc="Sets this number to the value of the Riemann Zeta function at `n`."]
+    fn set_to_zeta_ui, set_to_zeta_ui_round -> zeta_ui
 }
@@ -0,0 +1,159 @@
+use libc::{c_int, c_double, c_long, c_ulong, intmax_t, uintmax_t};
+
+use mpfr_sys::*;
+
+use BigFloat;
+use precision::ToPrecision;
+
+/// A primitive operand in the form expected by MPFR functions.
+pub enum Operand {
+    Signed(c_long),
+    Unsigned(c_ulong),
+    Double(c_double),
+    /// Holds integers which don't fit into a C long; the conversion is always exact.
+    Exact(BigFloat)
+}
+
+pub trait ToOperand {
+    fn to_operand(self) -> Operand;
+}
+
+macro_rules! impl_to_operand {
+    ($($t:ty => $variant:ident as $ct:ty),+) => {
+        $(
+        impl ToOperand for $t {
+            #[inline]
+            fn to_operand(self) -> Operand {
+                Operand::$variant(self as $ct)
+            }
+        }
+        )+
+    }
+}
+
+impl_to_operand! {
+    i8  => Signed as c_long,
+    i16 => Signed as c_long,
+    i32 => Signed as c_long,
+    u8  => Unsigned as c_ulong,
+    u16 => Unsigned as c_ulong,
+    u32 => Unsigned as c_ulong,
+    f32 => Double as c_double,
+    f64 => Double as c_double
+}
+
+macro_rules! impl_to_operand_wide {
+    ($($t:ty => $variant:ident, $ct:ty, $wide:ty, $exact:ident),+) => {
+        $(
+        impl ToOperand for $t {
+            #[inline]
+            fn to_operand(self) -> Operand {
+                let v = self as $wide;
+                if v >= <$ct>::min_value() as $wide && v <= <$ct>::max_value() as $wide {
+                    Operand::$variant(v as $ct)
+                } else {
+                    Operand::Exact($exact(v))
+                }
+            }
+        }
+        )+
+    }
+}
+
+impl_to_operand_wide! {
+    i64   => Signed, c_long, i128, exact_i128,
+    isize => Signed, c_long, i128, exact_i128,
+    i128  => Signed, c_long, i128, exact_i128,
+    u64   => Unsigned, c_ulong, u128, exact_u128,
+    usize => Unsigned, c_ulong, u128, exact_u128,
+    u128  => Unsigned, c_ulong, u128, exact_u128
+}
+
+/// Converts a 128-bit unsigned integer to a BigFloat with enough precision to hold it exactly.
+pub fn exact_u128(v: u128) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(128.bits());
+    let mut lo = BigFloat::fresh_with_prec(64.bits());
+    unsafe {
+        __gmpfr_set_uj_2exp(&mut r.value, (v >> 64) as uintmax_t, 64 as intmax_t, MPFR_RNDN);
+        __gmpfr_set_uj(&mut lo.value, v as u64 as uintmax_t, MPFR_RNDN);
+        mpfr_add(&mut r.value, &r.value, &lo.value, MPFR_RNDN);
+    }
+    r
+}
+
+/// Converts a 128-bit signed integer to a BigFloat with enough precision to hold it exactly.
+pub fn exact_i128(v: i128) -> BigFloat {
+    // wrapping_neg() gives the correct magnitude for i128::MIN as well
+    let mut r = exact_u128(if v < 0 { (v as u128).wrapping_neg() } else { v as u128 });
+    if v < 0 {
+        unsafe {
+            mpfr_neg(&mut r.value, &r.value, MPFR_RNDN);
+        }
+    }
+    r
+}
+
+/// Converts a double to a BigFloat exactly.
+fn exact_d(d: c_double) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(53.bits());
+    unsafe {
+        mpfr_set_d(&mut r.value, d, MPFR_RNDN);
+    }
+    r
+}
+
+fn exact_si(i: c_long) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(64.bits());
+    unsafe {
+        mpfr_set_si(&mut r.value, i, MPFR_RNDN);
+    }
+    r
+}
+
+fn exact_ui(i: c_ulong) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(64.bits());
+    unsafe {
+        mpfr_set_ui(&mut r.value, i, MPFR_RNDN);
+    }
+    r
+}
+
+// MPFR does not provide functions for some of the operand combinations. The following ones
+// have the signatures MPFR functions would have and convert the primitive operand to
+// a BigFloat exactly before calling the generic function.
+
+pub unsafe fn pow_d(rop: mpfr_ptr, op: mpfr_srcptr, x: c_double, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_pow(rop, op, &exact_d(x).value, rnd)
+}
+
+pub unsafe fn d_pow(rop: mpfr_ptr, x: c_double, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_pow(rop, &exact_d(x).value, op, rnd)
+}
+
+pub unsafe fn si_pow(rop: mpfr_ptr, x: c_long, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_pow(rop, &exact_si(x).value, op, rnd)
+}
+
+pub unsafe fn remainder_d(rop: mpfr_ptr, op: mpfr_srcptr, x: c_double, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, op, &exact_d(x).value, rnd)
+}
+
+pub unsafe fn remainder_ui(rop: mpfr_ptr, op: mpfr_srcptr, x: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, op, &exact_ui(x).value, rnd)
+}
+
+pub unsafe fn remainder_si(rop: mpfr_ptr, op: mpfr_srcptr, x: c_long, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, op, &exact_si(x).value, rnd)
+}
+
+pub unsafe fn d_remainder(rop: mpfr_ptr, x: c_double, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, &exact_d(x).value, op, rnd)
+}
+
+pub unsafe fn ui_remainder(rop: mpfr_ptr, x: c_ulong, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, &exact_ui(x).value, op, rnd)
+}
+
+pub unsafe fn si_remainder(rop: mpfr_ptr, x: c_long, op: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    mpfr_remainder(rop, &exact_si(x).value, op, rnd)
+}
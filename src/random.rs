@@ -0,0 +1,254 @@
+// Random numbers: a GMP random state and the MPFR functions drawing numbers from it.
+//
+// Uniform numbers are made either of random bits directly or by rounding a uniform real
+// number, and normal and exponential numbers are correctly rounded as well, so the result
+// depends on the rounding mode but not on how the real number was approximated. Without the
+// `mpfr4` feature the normal and exponential numbers are produced by other algorithms, so
+// their sequences for a given seed differ from those of MPFR 4.
+
+use std::mem;
+use std::cmp::Ordering;
+#[cfg(feature = "rand")]
+use std::cmp;
+
+use libc::c_ulong;
+
+use mpfr_sys::*;
+use gmp_sys::__gmp_randstate_struct;
+use gmp_sys::{__gmp_randinit_mt, __gmp_randinit_lc_2exp_size, __gmp_randinit_set};
+use gmp_sys::{__gmp_randseed, __gmp_randclear};
+use gmp_sys::{__gmpz_init_set_ui, __gmpz_clear, __gmpz_mul_2exp, __gmpz_add_ui};
+#[cfg(feature = "rand")]
+use gmp_sys::__gmpz_init;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "rand")]
+use rand::prelude::Distribution;
+
+use {BigFloat, Precision, RoundingMode, global_rounding_mode, ternary};
+use ext::{mpfr_urandomb, mpfr_urandom, mpfr_nrandom, mpfr_erandom};
+
+/// The state of a random number generator.
+///
+/// A state produces the same sequence of numbers for the same algorithm and seed. A clone
+/// continues the sequence from the same point independently of the original state.
+///
+/// ```rust
+/// # use mpfr::random::RandState;
+/// # use mpfr::traits::*;
+/// let mut a = RandState::new();
+/// a.seed(42);
+/// let mut b = a.clone();
+/// assert_eq!(a.urandomb(64.bits()), b.urandomb(64.bits()));
+/// ```
+pub struct RandState {
+    state: __gmp_randstate_struct
+}
+
+impl Drop for RandState {
+    fn drop(&mut self) {
+        unsafe { __gmp_randclear(&mut self.state) }
+    }
+}
+
+impl Clone for RandState {
+    fn clone(&self) -> RandState {
+        RandState {
+            state: unsafe {
+                let mut state = mem::uninitialized();
+                __gmp_randinit_set(&mut state, &self.state);
+                state
+            }
+        }
+    }
+}
+
+impl RandState {
+    /// Creates a state of the Mersenne Twister generator, the default one of GMP.
+    #[inline]
+    pub fn new() -> RandState {
+        RandState::mersenne_twister()
+    }
+
+    /// Creates a state of the Mersenne Twister generator, which is fast and has a period
+    /// of `2^19937 - 1`.
+    pub fn mersenne_twister() -> RandState {
+        RandState {
+            state: unsafe {
+                let mut state = mem::uninitialized();
+                __gmp_randinit_mt(&mut state);
+                state
+            }
+        }
+    }
+
+    /// Creates a state of a linear congruential generator `X = (aX + c) mod 2^m` whose
+    /// parameters are chosen by GMP so that every number has at least `size` good bits.
+    ///
+    /// Returns `None` if `size` is greater than 128, the largest size GMP supports.
+    pub fn linear_congruential(size: u64) -> Option<RandState> {
+        // Also keeps the size from being truncated where `c_ulong` has 32 bits
+        if size > 128 {
+            return None;
+        }
+        unsafe {
+            let mut state = mem::uninitialized();
+            if __gmp_randinit_lc_2exp_size(&mut state, size as c_ulong) != 0 {
+                Some(RandState { state: state })
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Creates a Mersenne Twister state seeded with `⌈prec / 32⌉` words (but at least two)
+    /// taken from a `rand` generator, so that its numbers are reproducible whenever that
+    /// generator is, and the seed has as many random bits as the numbers of the given
+    /// precision.
+    #[cfg(feature = "rand")]
+    pub fn from_rng<R: Rng + ?Sized>(rng: &mut R, prec: Precision) -> RandState {
+        let mut state = RandState::new();
+        let words = cmp::max((prec.bits() + 31) / 32, 2);
+        unsafe {
+            let mut seed = mem::uninitialized();
+            __gmpz_init(&mut seed);
+            for _ in 0..words {
+                __gmpz_mul_2exp(&mut seed, &seed, 32);
+                __gmpz_add_ui(&mut seed, &seed, rng.next_u32() as c_ulong);
+            }
+            __gmp_randseed(&mut state.state, &seed);
+            __gmpz_clear(&mut seed);
+        }
+        state
+    }
+
+    /// Restarts the generator with the given seed.
+    pub fn seed(&mut self, seed: u64) {
+        // `c_ulong` may have only 32 bits, so the seed is set in two halves
+        unsafe {
+            let mut z = mem::uninitialized();
+            __gmpz_init_set_ui(&mut z, (seed >> 32) as c_ulong);
+            __gmpz_mul_2exp(&mut z, &z, 32);
+            __gmpz_add_ui(&mut z, &z, (seed & 0xffffffff) as c_ulong);
+            __gmp_randseed(&mut self.state, &z);
+            __gmpz_clear(&mut z);
+        }
+    }
+
+    /// Returns a uniformly distributed number in `[0, 1)` whose bits up to the given
+    /// precision are random. No rounding happens, so the result is a multiple of `2^-prec`.
+    pub fn urandomb(&mut self, prec: Precision) -> BigFloat {
+        let mut r = BigFloat::fresh_with_prec(prec);
+        unsafe { mpfr_urandomb(&mut r.value, &mut self.state); }
+        r
+    }
+
+    /// Returns a uniformly distributed random real number in `[0, 1]` rounded to the given
+    /// precision. Unlike `urandomb()`, small numbers keep all their precision.
+    #[inline]
+    pub fn urandom(&mut self, prec: Precision) -> BigFloat {
+        self.urandom_round(prec, global_rounding_mode::get()).0
+    }
+
+    /// Returns a random real number from the standard normal distribution rounded to
+    /// the given precision.
+    #[inline]
+    pub fn nrandom(&mut self, prec: Precision) -> BigFloat {
+        self.nrandom_round(prec, global_rounding_mode::get()).0
+    }
+
+    /// Returns a random real number from the exponential distribution with the mean 1 rounded
+    /// to the given precision.
+    #[inline]
+    pub fn erandom(&mut self, prec: Precision) -> BigFloat {
+        self.erandom_round(prec, global_rounding_mode::get()).0
+    }
+
+    // The same functions with an explicit rounding mode; they also return the ternary value
+
+    pub fn urandom_round(&mut self, prec: Precision, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(prec);
+        let t = unsafe { mpfr_urandom(&mut r.value, &mut self.state, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+
+    pub fn nrandom_round(&mut self, prec: Precision, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(prec);
+        let t = unsafe { mpfr_nrandom(&mut r.value, &mut self.state, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+
+    pub fn erandom_round(&mut self, prec: Precision, rnd: RoundingMode) -> (BigFloat, Ordering) {
+        let mut r = BigFloat::fresh_with_prec(prec);
+        let t = unsafe { mpfr_erandom(&mut r.value, &mut self.state, rnd as mpfr_rnd_t) };
+        (r, ternary(t))
+    }
+}
+
+// Distributions for `rand`. Every sample is drawn from a state seeded by the generator passed
+// in (see `RandState::from_rng`), so the numbers depend on that generator only, and they are
+// rounded with the global rounding mode
+
+/// The uniform distribution on `[0, 1]` with numbers of the given precision.
+#[cfg(feature = "rand")]
+#[derive(Copy, Debug)]
+pub struct Uniform {
+    pub prec: Precision
+}
+
+/// The standard normal distribution with numbers of the given precision.
+#[cfg(feature = "rand")]
+#[derive(Copy, Debug)]
+pub struct StandardNormal {
+    pub prec: Precision
+}
+
+/// The exponential distribution with the mean 1 and numbers of the given precision.
+#[cfg(feature = "rand")]
+#[derive(Copy, Debug)]
+pub struct Exp1 {
+    pub prec: Precision
+}
+
+#[cfg(feature = "rand")]
+impl Uniform {
+    pub fn new(prec: Precision) -> Uniform {
+        Uniform { prec: prec }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl StandardNormal {
+    pub fn new(prec: Precision) -> StandardNormal {
+        StandardNormal { prec: prec }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Exp1 {
+    pub fn new(prec: Precision) -> Exp1 {
+        Exp1 { prec: prec }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Distribution<BigFloat> for Uniform {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigFloat {
+        RandState::from_rng(rng, self.prec).urandom(self.prec)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Distribution<BigFloat> for StandardNormal {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigFloat {
+        RandState::from_rng(rng, self.prec).nrandom(self.prec)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Distribution<BigFloat> for Exp1 {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigFloat {
+        RandState::from_rng(rng, self.prec).erandom(self.prec)
+    }
+}
@@ -0,0 +1,192 @@
+// Inverses of the reciprocal trigonometric and hyperbolic functions, with the same signatures
+// as MPFR functions.
+//
+// Composing MPFR functions like `atan(1/x)` rounds twice, so these are computed with Ziv's
+// strategy instead (see `ziv.rs`). The formulas are chosen so that the relative error of
+// the intermediate results is not amplified, e.g. `asec(x)` is not computed as `acos(1/x)`
+// which loses all accuracy near `x = 1`. None of these functions has a dyadic value at
+// a finite nonzero dyadic argument, except for the ones handled separately.
+
+use libc::c_int;
+
+use mpfr_sys::*;
+
+use BigFloat;
+use ziv::{Approx, round_with, working, exp_of, exact_sub};
+
+unsafe fn one() -> BigFloat {
+    let mut r = working(2);
+    mpfr_set_ui(&mut r.value, 1, MPFR_RNDN);
+    r
+}
+
+unsafe fn set_zero_like(rop: mpfr_ptr, x: mpfr_srcptr) -> c_int {
+    mpfr_set_zero(rop, if mpfr_signbit(x) != 0 { -1 } else { 1 });
+    0
+}
+
+unsafe fn set_inf_like(rop: mpfr_ptr, x: mpfr_srcptr) -> c_int {
+    mpfr_set_inf(rop, if mpfr_signbit(x) != 0 { -1 } else { 1 });
+    0
+}
+
+/// Returns `|x| - 1` computed exactly.
+unsafe fn abs_minus_one(x: mpfr_srcptr) -> BigFloat {
+    let mut a = working(mpfr_get_prec(x) as i64);
+    mpfr_abs(&mut a.value, x, MPFR_RNDN);
+    exact_sub(&a.value, &one().value)
+}
+
+/// Approximates `sqrt(x^2 - 1)` as `sqrt((|x| - 1) (|x| + 1))` given `m = |x| - 1`,
+/// with the relative error of at most two roundings.
+unsafe fn sqrt_sqr_minus_one(m: &BigFloat, wp: i64) -> BigFloat {
+    let mut r = working(wp);
+    mpfr_add_ui(&mut r.value, &m.value, 2, MPFR_RNDN);
+    mpfr_mul(&mut r.value, &r.value, &m.value, MPFR_RNDN);
+    mpfr_sqrt(&mut r.value, &r.value, MPFR_RNDN);
+    r
+}
+
+/// `acot(x) = atan(1/x)`, with the values in `[-π/2, π/2]`; the sign of a zero argument
+/// selects the end of the range as `1/±0 = ±∞` does.
+pub unsafe fn acot(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_zero_like(rop, x);
+    }
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        mpfr_ui_div(&mut r.value, 1, x, MPFR_RNDN);
+        // atan does not amplify the relative error of its argument
+        mpfr_atan(&mut r.value, &r.value, MPFR_RNDN);
+        let err = exp_of(&r) + 2 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// `asec(x) = acos(1/x)` for `|x| >= 1`, computed as `atan2(sqrt(x^2 - 1), ±1)`.
+pub unsafe fn asec(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_cmpabs(x, &one().value) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    let m = abs_minus_one(x);
+    let mut sign = working(2);
+    mpfr_set_si(&mut sign.value, if mpfr_sgn(x) < 0 { -1 } else { 1 }, MPFR_RNDN);
+    round_with(rop, rnd, |wp| {
+        let mut r = sqrt_sqr_minus_one(&m, wp);
+        mpfr_atan2(&mut r.value, &r.value, &sign.value, MPFR_RNDN);
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// `acsc(x) = asin(1/x)` for `|x| >= 1`, computed as `atan2(±1, sqrt(x^2 - 1))`.
+pub unsafe fn acsc(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_cmpabs(x, &one().value) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_zero_like(rop, x);
+    }
+    let m = abs_minus_one(x);
+    let mut sign = working(2);
+    mpfr_set_si(&mut sign.value, if mpfr_sgn(x) < 0 { -1 } else { 1 }, MPFR_RNDN);
+    round_with(rop, rnd, |wp| {
+        let mut r = sqrt_sqr_minus_one(&m, wp);
+        mpfr_atan2(&mut r.value, &sign.value, &r.value, MPFR_RNDN);
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// `acoth(x) = atanh(1/x)` for `|x| > 1`, computed as `±log1p(2 / (|x| - 1)) / 2`.
+pub unsafe fn acoth(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_cmpabs(x, &one().value) < 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_cmpabs(x, &one().value) == 0 {
+        return set_inf_like(rop, x);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_zero_like(rop, x);
+    }
+    let m = abs_minus_one(x);
+    let negative = mpfr_sgn(x) < 0;
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        mpfr_ui_div(&mut r.value, 2, &m.value, MPFR_RNDN);
+        // log1p does not amplify the relative error of its argument
+        mpfr_log1p(&mut r.value, &r.value, MPFR_RNDN);
+        mpfr_div_2ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+        if negative {
+            mpfr_neg(&mut r.value, &r.value, MPFR_RNDN);
+        }
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// `asech(x) = acosh(1/x)` for `0 < x <= 1`, computed as
+/// `log1p((1 - x)/x + sqrt((1 - x)(1 + x))/x)`.
+pub unsafe fn asech(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_sgn(x) < 0 || mpfr_cmp_ui(x, 1) > 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        mpfr_set_inf(rop, 1);
+        return 0;
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    let w = exact_sub(&one().value, x);
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        let mut d = working(wp);
+        mpfr_add_ui(&mut r.value, x, 1, MPFR_RNDN);
+        mpfr_mul(&mut r.value, &r.value, &w.value, MPFR_RNDN);
+        mpfr_sqrt(&mut r.value, &r.value, MPFR_RNDN);
+        mpfr_div(&mut r.value, &r.value, x, MPFR_RNDN);
+        mpfr_div(&mut d.value, &w.value, x, MPFR_RNDN);
+        // Both terms are positive, so the sum has the relative error of at most
+        // four roundings, which is not amplified by log1p
+        mpfr_add(&mut r.value, &r.value, &d.value, MPFR_RNDN);
+        mpfr_log1p(&mut r.value, &r.value, MPFR_RNDN);
+        let err = exp_of(&r) + 3 - wp;
+        Approx::Within(r, err)
+    })
+}
+
+/// `acsch(x) = asinh(1/x)`.
+pub unsafe fn acsch(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return set_inf_like(rop, x);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_zero_like(rop, x);
+    }
+    round_with(rop, rnd, |wp| {
+        let mut r = working(wp);
+        mpfr_ui_div(&mut r.value, 1, x, MPFR_RNDN);
+        // asinh does not amplify the relative error of its argument
+        mpfr_asinh(&mut r.value, &r.value, MPFR_RNDN);
+        let err = exp_of(&r) + 2 - wp;
+        Approx::Within(r, err)
+    })
+}
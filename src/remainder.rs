@@ -0,0 +1,198 @@
+use std::cmp;
+
+use libc::{c_int, c_long};
+
+use mpfr_sys::*;
+
+use {BigFloat, grnd};
+use precision::ToPrecision;
+use ziv::exact_mul;
+
+/// Remainder-related operations.
+///
+/// Note that the `%` operator is backed by `remainder()`, i.e. it computes the IEEE 754
+/// remainder with the quotient rounded to the nearest integer. Use `fmod()` for the
+/// semantics of `%` on Rust primitive types and `rem_euclid()` for a non-negative result.
+///
+/// All of these are implemented for values and references on both sides, following
+/// the same rules of operand reuse as the arithmetic operators.
+pub trait Remainder<RHS=Self> {
+    type Output;
+
+    /// Computes `self - n * rhs` where `n` is the quotient `self / rhs` truncated towards zero.
+    ///
+    /// The result has the sign of `self`.
+    fn fmod(self, rhs: RHS) -> Self::Output;
+
+    /// Computes `self - n * rhs` where `n` is the quotient `self / rhs` rounded to the
+    /// nearest integer, ties to even. This is the same as `self % rhs`.
+    fn remainder(self, rhs: RHS) -> Self::Output;
+
+    /// The same as `remainder()`, but also returns the low bits of the quotient `n`
+    /// together with its sign (at least three bits are returned).
+    fn remquo(self, rhs: RHS) -> (Self::Output, i64);
+
+    /// Computes the quotient `n` of the Euclidean division, such that
+    /// `self = n * rhs + r` with `0 <= r < |rhs|`.
+    fn div_euclid(self, rhs: RHS) -> Self::Output;
+
+    /// Computes the remainder `r` of the Euclidean division, such that
+    /// `self = n * rhs + r` with `0 <= r < |rhs|`.
+    fn rem_euclid(self, rhs: RHS) -> Self::Output;
+}
+
+/// Returns the precision which is enough to hold `x - n * y` exactly for any integer `n`
+/// such that the result is less than `|y|` in magnitude.
+///
+/// The result lies on the grid of the operand with the smaller ulp and below `2^ey`, so its
+/// precision does not depend on the exponent of `x` (mpfr_fmod itself reduces huge arguments
+/// by modular exponentiation).
+unsafe fn exact_rem_prec(x: mpfr_srcptr, y: mpfr_srcptr) -> mpfr_prec_t {
+    let (px, py) = (mpfr_get_prec(x), mpfr_get_prec(y));
+    if mpfr_regular_p(x) != 0 && mpfr_regular_p(y) != 0 {
+        let ey = mpfr_get_exp(y);
+        let ulp = cmp::min(mpfr_get_exp(x) - px, ey - py);
+        cmp::max(ey - ulp, 2)
+    } else {
+        cmp::max(px, py)
+    }
+}
+
+/// Computes the Euclidean remainder of `x` and `y` exactly.
+unsafe fn exact_rem_euclid(x: mpfr_srcptr, y: mpfr_srcptr) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(exact_rem_prec(x, y).bits());
+    mpfr_fmod(&mut r.value, x, y, MPFR_RNDN);
+    if mpfr_sgn(&r.value) < 0 {
+        if mpfr_sgn(y) < 0 {
+            mpfr_sub(&mut r.value, &r.value, y, MPFR_RNDN);
+        } else {
+            mpfr_add(&mut r.value, &r.value, y, MPFR_RNDN);
+        }
+    }
+    r
+}
+
+unsafe fn rem_euclid(rop: mpfr_ptr, x: mpfr_srcptr, y: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    let r = exact_rem_euclid(x, y);
+    mpfr_set(rop, &r.value, rnd)
+}
+
+unsafe fn div_euclid(rop: mpfr_ptr, x: mpfr_srcptr, y: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    // The quotient is rounded towards -inf (+inf for negative divisor) with two more bits
+    // than the target precision. If it is less than 2^wp in magnitude, then the integer
+    // quotient is representable in that precision and rounding does not change it
+    let wp = mpfr_get_prec(rop) + 2;
+    let mut q = BigFloat::fresh_with_prec(wp.bits());
+    let negative = mpfr_sgn(y) < 0;
+    mpfr_div(&mut q.value, x, y, if negative { MPFR_RNDU } else { MPFR_RNDD });
+    if mpfr_regular_p(&q.value) == 0 || mpfr_get_exp(&q.value) <= wp {
+        return if negative {
+            mpfr_rint_ceil(rop, &q.value, rnd)
+        } else {
+            mpfr_rint_floor(rop, &q.value, rnd)
+        };
+    }
+
+    // Otherwise q is an integer, and the integer quotient is either q itself, when
+    // x - q * y (which is never negative) is less than |y|, or lies strictly between q and
+    // the next number of wp bits towards x / y. No result of the target precision nor
+    // a midpoint between two of them lies there, so any number in between rounds the same
+    let d = exact_mul(&q.value, y);
+    let mut r = BigFloat::fresh_with_prec(mpfr_get_prec(y).bits());
+    mpfr_sub(&mut r.value, x, &d.value, MPFR_RNDD);
+    if mpfr_cmpabs(&r.value, y) >= 0 {
+        mpfr_prec_round(&mut q.value, wp + 1, MPFR_RNDN);
+        if negative {
+            mpfr_nextbelow(&mut q.value);
+        } else {
+            mpfr_nextabove(&mut q.value);
+        }
+    }
+    mpfr_set(rop, &q.value, rnd)
+}
+
+#[inline]
+unsafe fn remquo(rop: mpfr_ptr, x: mpfr_srcptr, y: mpfr_srcptr, rnd: mpfr_rnd_t) -> i64 {
+    let mut q: c_long = 0;
+    mpfr_remquo(rop, &mut q, x, y, rnd);
+    q as i64
+}
+
+macro_rules! impl_remainder_all {
+    ($($meth:ident -> $f:ident),+) => {
+        impl<'a> Remainder<&'a BigFloat> for BigFloat {
+            type Output = BigFloat;
+
+            $(
+            fn $meth(mut self, rhs: &'a BigFloat) -> BigFloat {
+                unsafe {
+                    $f(&mut self.value, &self.value, &rhs.value, grnd());
+                }
+                self
+            }
+            )+
+
+            fn remquo(mut self, rhs: &'a BigFloat) -> (BigFloat, i64) {
+                let q = unsafe { remquo(&mut self.value, &self.value, &rhs.value, grnd()) };
+                (self, q)
+            }
+        }
+
+        impl Remainder<BigFloat> for BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, rhs: BigFloat) -> BigFloat {
+                self.$meth(&rhs)
+            }
+            )+
+
+            #[inline]
+            fn remquo(self, rhs: BigFloat) -> (BigFloat, i64) {
+                self.remquo(&rhs)
+            }
+        }
+
+        impl<'r> Remainder<BigFloat> for &'r BigFloat {
+            type Output = BigFloat;
+
+            $(
+            fn $meth(self, mut rhs: BigFloat) -> BigFloat {
+                unsafe {
+                    $f(&mut rhs.value, &self.value, &rhs.value, grnd());
+                }
+                rhs
+            }
+            )+
+
+            fn remquo(self, mut rhs: BigFloat) -> (BigFloat, i64) {
+                let q = unsafe { remquo(&mut rhs.value, &self.value, &rhs.value, grnd()) };
+                (rhs, q)
+            }
+        }
+
+        impl<'a, 'r> Remainder<&'a BigFloat> for &'r BigFloat {
+            type Output = BigFloat;
+
+            $(
+            #[inline]
+            fn $meth(self, rhs: &'a BigFloat) -> BigFloat {
+                self.clone().$meth(rhs)
+            }
+            )+
+
+            #[inline]
+            fn remquo(self, rhs: &'a BigFloat) -> (BigFloat, i64) {
+                self.clone().remquo(rhs)
+            }
+        }
+    }
+}
+
+impl_remainder_all! {
+    fmod -> mpfr_fmod,
+    remainder -> mpfr_remainder,
+    div_euclid -> div_euclid,
+    rem_euclid -> rem_euclid
+}
@@ -51,9 +51,11 @@ pub mod global_rounding_mode {
 /// for example, when the result of an arithmetic action is stored into a variable with
 /// smaller precision than that of the operands.
 ///
-/// Rounding mode parameter is not exposed on all of these operations directly. Instead it
+/// Rounding mode parameter is not exposed on most of these operations directly. Instead it
 /// can be set globally with `global_rounding_mode::set()` or adjusted temporarily with 
-/// `global_rounding_mode::with()`.
+/// `global_rounding_mode::with()`. Operations which accept the rounding mode explicitly
+/// are named with the `_round` suffix, e.g. `BigFloat::add_round()` or `Math::sin_round()`;
+/// they also return whether the result was rounded up, down or is exact.
 #[derive(Copy, PartialEq, Eq, Debug)]
 pub enum RoundingMode {
     ToNearest    = MPFR_RNDN as isize,
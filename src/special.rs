@@ -0,0 +1,609 @@
+// Special functions which are not provided by MPFR, with the same signatures as MPFR functions.
+//
+// The inverse functions are found with Newton's iteration at the working precision, and then
+// the root is enclosed in an interval whose ends are checked with directed rounding, which
+// gives a rigorous error bound for Ziv's strategy without analyzing the convergence of the
+// iteration (see `ziv.rs`). None of these functions has a dyadic value at a finite
+// nonzero dyadic argument, except for the ones handled separately.
+
+use std::mem;
+use std::cmp::{self, Ordering};
+
+use libc::{c_int, c_long, c_ulong};
+
+use mpfr_sys::*;
+
+use BigFloat;
+use ziv::{Approx, Sum, round_with, extended, retry, working, exp_of, log2_ceil, exact_sub};
+use ziv::{integer, newton, enclose, exp_approx, split, mul_approx};
+
+/// A bound of the domain of an inverse function together with the sign of `f(w) - x`
+/// in the limit.
+type Bound = Option<(c_long, Ordering)>;
+
+/// Returns the limit of the sign of `f(a) - x` if `a` is outside of the domain.
+unsafe fn sign_outside(a: &BigFloat, lo: Bound, hi: Bound) -> Option<Ordering> {
+    match (lo, hi) {
+        (Some((b, s)), _) if mpfr_cmp_si(&a.value, b) <= 0 => Some(s),
+        (_, Some((b, s))) if mpfr_cmp_si(&a.value, b) >= 0 => Some(s),
+        _ => None
+    }
+}
+
+/// Returns the sign of `f(a) - x` if it is certain, given the lower and the upper bounds
+/// of `f(a)` and `x`.
+fn bounds_sign(down: &BigFloat, up: &BigFloat, x_down: &BigFloat, x_up: &BigFloat)
+                -> Option<Ordering> {
+    if *down > *x_up {
+        Some(Ordering::Greater)
+    } else if *up < *x_down {
+        Some(Ordering::Less)
+    } else {
+        None
+    }
+}
+
+/// Returns the sign of `x` relative to `-1/e`.
+unsafe fn cmp_minus_inv_e(x: mpfr_srcptr) -> Ordering {
+    let mut p = mpfr_get_prec(x) as i64 + 16;
+    loop {
+        // -1/e lies in [-up, -down]
+        let (mut down, mut up) = (working(p), working(p));
+        mpfr_set_si(&mut down.value, -1, MPFR_RNDN);
+        mpfr_exp(&mut up.value, &down.value, MPFR_RNDU);
+        mpfr_exp(&mut down.value, &down.value, MPFR_RNDD);
+        mpfr_neg(&mut up.value, &up.value, MPFR_RNDN);
+        mpfr_neg(&mut down.value, &down.value, MPFR_RNDN);
+        if mpfr_cmp(x, &up.value) < 0 {
+            return Ordering::Less;
+        }
+        if mpfr_cmp(x, &down.value) > 0 {
+            return Ordering::Greater;
+        }
+        p *= 2;
+    }
+}
+
+/// The real branches of the Lambert W function, the solutions of `w e^w = x`. The principal
+/// branch `W0` is defined for `x >= -1/e` and takes values `w >= -1`, the lower branch `W-1`
+/// is defined for `-1/e <= x < 0` and takes values `w <= -1`.
+///
+/// The root is found from the equivalent equation `w + ln|w| = ln|x|`, which does not
+/// overflow for large arguments.
+unsafe fn lambert(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t, lower: bool) -> c_int {
+    if mpfr_nan_p(x) != 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return if lower {
+            mpfr_set_inf(rop, -1);
+            0
+        } else {
+            mpfr_set(rop, x, rnd)
+        };
+    }
+    if mpfr_inf_p(x) != 0 {
+        if !lower && mpfr_sgn(x) > 0 {
+            mpfr_set_inf(rop, 1);
+        } else {
+            mpfr_set_nan(rop);
+        }
+        return 0;
+    }
+    if (lower && mpfr_sgn(x) > 0) || (mpfr_sgn(x) < 0 && cmp_minus_inv_e(x) == Ordering::Less) {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+
+    // g(w) = w + ln|w| - ln|x| tends to -∞ at 0 and is -1 - ln|x| > 0 at -1
+    let (lo, hi): (Bound, Bound) = if lower {
+        (None, Some((-1, Ordering::Greater)))
+    } else if mpfr_sgn(x) < 0 {
+        (Some((-1, Ordering::Greater)), Some((0, Ordering::Less)))
+    } else {
+        (Some((0, Ordering::Less)), None)
+    };
+
+    round_with(rop, rnd, |wp| {
+        let mut ax = working(mpfr_get_prec(x) as i64);
+        mpfr_abs(&mut ax.value, x, MPFR_RNDN);
+        let (mut lx, mut lx_down, mut lx_up) = (working(wp), working(wp), working(wp));
+        mpfr_log(&mut lx.value, &ax.value, MPFR_RNDN);
+        mpfr_log(&mut lx_down.value, &ax.value, MPFR_RNDD);
+        mpfr_log(&mut lx_up.value, &ax.value, MPFR_RNDU);
+
+        // The initial approximation
+        let mut w = working(wp);
+        let mut t = working(wp);
+        if mpfr_cmp_d(x, -0.25) < 0 {
+            // Near the branch point, w = -1 ± p - p²/3 ± 11p³/72 where p = sqrt(2 (e x + 1))
+            mpfr_set_ui(&mut t.value, 1, MPFR_RNDN);
+            mpfr_exp(&mut t.value, &t.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, x, MPFR_RNDN);
+            mpfr_add_ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            if mpfr_sgn(&t.value) <= 0 {
+                mpfr_set_ui_2exp(&mut t.value, 1, -(wp / 2) as mpfr_exp_t, MPFR_RNDN);
+            } else {
+                mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+                mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+            }
+            if lower {
+                mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+            }
+            mpfr_mul_ui(&mut w.value, &t.value, 11, MPFR_RNDN);
+            mpfr_div_ui(&mut w.value, &w.value, 72, MPFR_RNDN);
+            mpfr_sub_d(&mut w.value, &w.value, 1.0 / 3.0, MPFR_RNDN);
+            mpfr_mul(&mut w.value, &w.value, &t.value, MPFR_RNDN);
+            mpfr_add_ui(&mut w.value, &w.value, 1, MPFR_RNDN);
+            mpfr_mul(&mut w.value, &w.value, &t.value, MPFR_RNDN);
+            mpfr_sub_ui(&mut w.value, &w.value, 1, MPFR_RNDN);
+        } else if !lower && mpfr_cmp_ui(x, 3) <= 0 {
+            mpfr_log1p(&mut w.value, x, MPFR_RNDN);
+        } else if !lower {
+            // w = L - ln L where L = ln x
+            mpfr_log(&mut t.value, &lx.value, MPFR_RNDN);
+            mpfr_sub(&mut w.value, &lx.value, &t.value, MPFR_RNDN);
+        } else {
+            // w = L1 - L2 + L2/L1 where L1 = ln(-x), L2 = ln(-L1)
+            mpfr_neg(&mut t.value, &lx.value, MPFR_RNDN);
+            mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+            mpfr_div(&mut w.value, &t.value, &lx.value, MPFR_RNDN);
+            mpfr_sub(&mut w.value, &w.value, &t.value, MPFR_RNDN);
+            mpfr_add(&mut w.value, &w.value, &lx.value, MPFR_RNDN);
+        }
+
+        let (lo_w, hi_w) = (lo.map(|(b, _)| integer(b)), hi.map(|(b, _)| integer(b)));
+        let d = newton(&mut w, wp, lo_w.as_ref(), hi_w.as_ref(), |w| {
+            // g(w) w / (w + 1)
+            let (mut g, mut t) = (working(wp), working(wp));
+            mpfr_abs(&mut g.value, &w.value, MPFR_RNDN);
+            mpfr_log(&mut g.value, &g.value, MPFR_RNDN);
+            mpfr_add(&mut g.value, &g.value, &w.value, MPFR_RNDN);
+            mpfr_sub(&mut g.value, &g.value, &lx.value, MPFR_RNDN);
+            mpfr_add_ui(&mut t.value, &w.value, 1, MPFR_RNDN);
+            mpfr_div(&mut t.value, &w.value, &t.value, MPFR_RNDN);
+            mpfr_mul(&mut g.value, &g.value, &t.value, MPFR_RNDN);
+            Some(g)
+        });
+        let d = match d {
+            Some(d) => d,
+            None => return retry(wp)
+        };
+
+        let err = enclose(&w, &d, wp, |a| {
+            sign_outside(a, lo, hi).or_else(|| {
+                // Bounds of a + ln|a|
+                let (mut down, mut up) = (working(wp), working(wp));
+                mpfr_abs(&mut t.value, &a.value, MPFR_RNDN);
+                mpfr_log(&mut down.value, &t.value, MPFR_RNDD);
+                mpfr_log(&mut up.value, &t.value, MPFR_RNDU);
+                mpfr_add(&mut down.value, &down.value, &a.value, MPFR_RNDD);
+                mpfr_add(&mut up.value, &up.value, &a.value, MPFR_RNDU);
+                bounds_sign(&down, &up, &lx_down, &lx_up)
+            })
+        });
+        match err {
+            Some(err) => Approx::Within(w, err),
+            None => retry(wp)
+        }
+    })
+}
+
+/// The principal branch of the Lambert W function.
+pub unsafe fn lambert_w0(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    lambert(rop, x, rnd, false)
+}
+
+/// The lower branch of the Lambert W function.
+pub unsafe fn lambert_wm1(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    lambert(rop, x, rnd, true)
+}
+
+/// Stores `(√π / 2) e^(y²)`, the reciprocal of the derivative of `erf(y)`, into `r`.
+unsafe fn erf_deriv_rec(r: &mut BigFloat, y: &BigFloat, wp: i64) {
+    let mut t = working(wp);
+    mpfr_sqr(&mut r.value, &y.value, MPFR_RNDN);
+    mpfr_exp(&mut r.value, &r.value, MPFR_RNDN);
+    mpfr_const_pi(&mut t.value, MPFR_RNDN);
+    mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut r.value, &r.value, &t.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+}
+
+/// Solves `erf(y) = x` for `0 < x <= 1/2`, or `erfc(y) = x` for `0 < x < 1/2` if `erfc` is
+/// set, and stores `y` (or `-y` if `negative` is set) into `rop`.
+unsafe fn solve_erf(rop: mpfr_ptr, x: &BigFloat, erfc: bool, negative: bool,
+                    rnd: mpfr_rnd_t) -> c_int {
+    let f: unsafe extern "C" fn(mpfr_ptr, mpfr_srcptr, mpfr_rnd_t) -> c_int =
+        if erfc { mpfr_erfc } else { mpfr_erf };
+    // erf(0) - x < 0 and erfc(0) - x > 0
+    let lo = Some((0, if erfc { Ordering::Greater } else { Ordering::Less }));
+
+    round_with(rop, rnd, |wp| {
+        let mut y = working(wp);
+        let mut t = working(wp);
+        if erfc {
+            // erfc(y) ≈ e^(-y²) / (y √π), so y² ≈ -ln x - ln(√π sqrt(-ln x))
+            mpfr_log(&mut y.value, &x.value, MPFR_RNDN);
+            mpfr_neg(&mut y.value, &y.value, MPFR_RNDN);
+            mpfr_const_pi(&mut t.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &y.value, MPFR_RNDN);
+            mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+            mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            mpfr_sub(&mut y.value, &y.value, &t.value, MPFR_RNDN);
+            if mpfr_sgn(&y.value) > 0 {
+                mpfr_sqrt(&mut y.value, &y.value, MPFR_RNDN);
+            } else {
+                mpfr_set_ui_2exp(&mut y.value, 1, -1, MPFR_RNDN);
+            }
+        } else {
+            // y ≈ (√π / 2) (x + π x³ / 12), which is below the root
+            mpfr_const_pi(&mut t.value, MPFR_RNDN);
+            mpfr_sqr(&mut y.value, &x.value, MPFR_RNDN);
+            mpfr_mul(&mut y.value, &y.value, &t.value, MPFR_RNDN);
+            mpfr_div_ui(&mut y.value, &y.value, 12, MPFR_RNDN);
+            mpfr_add_ui(&mut y.value, &y.value, 1, MPFR_RNDN);
+            mpfr_mul(&mut y.value, &y.value, &x.value, MPFR_RNDN);
+            mpfr_sqrt(&mut t.value, &t.value, MPFR_RNDN);
+            mpfr_mul(&mut y.value, &y.value, &t.value, MPFR_RNDN);
+            mpfr_div_2ui(&mut y.value, &y.value, 1, MPFR_RNDN);
+        }
+
+        let lo_y = lo.map(|(b, _)| integer(b));
+        let d = newton(&mut y, wp, lo_y.as_ref(), None, |y| {
+            let (mut v, mut r) = (working(wp), working(wp));
+            f(&mut v.value, &y.value, MPFR_RNDN);
+            mpfr_sub(&mut v.value, &v.value, &x.value, MPFR_RNDN);
+            erf_deriv_rec(&mut r, y, wp);
+            mpfr_mul(&mut v.value, &v.value, &r.value, MPFR_RNDN);
+            if erfc {
+                mpfr_neg(&mut v.value, &v.value, MPFR_RNDN);
+            }
+            Some(v)
+        });
+        let d = match d {
+            Some(d) => d,
+            None => return retry(wp)
+        };
+
+        let err = enclose(&y, &d, wp, |a| {
+            sign_outside(a, lo, None).or_else(|| {
+                let (mut down, mut up) = (working(wp), working(wp));
+                f(&mut down.value, &a.value, MPFR_RNDD);
+                f(&mut up.value, &a.value, MPFR_RNDU);
+                bounds_sign(&down, &up, x, x)
+            })
+        });
+        match err {
+            Some(err) => {
+                if negative {
+                    mpfr_neg(&mut y.value, &y.value, MPFR_RNDN);
+                }
+                Approx::Within(y, err)
+            }
+            None => retry(wp)
+        }
+    })
+}
+
+/// The inverse error function, defined for `-1 <= x <= 1`.
+pub unsafe fn erf_inv(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    let one = integer(1);
+    if mpfr_nan_p(x) != 0 || mpfr_cmpabs(x, &one.value) > 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    if mpfr_cmpabs(x, &one.value) == 0 {
+        mpfr_set_inf(rop, mpfr_sgn(x));
+        return 0;
+    }
+    let mut a = working(mpfr_get_prec(x) as i64);
+    mpfr_abs(&mut a.value, x, MPFR_RNDN);
+    let negative = mpfr_sgn(x) < 0;
+    if mpfr_cmp_ui_2exp(&a.value, 1, -1) <= 0 {
+        solve_erf(rop, &a, false, negative, rnd)
+    } else {
+        // erf(y) = x is ill-conditioned near 1, so erfc(y) = 1 - x is solved instead
+        solve_erf(rop, &exact_sub(&one.value, &a.value), true, negative, rnd)
+    }
+}
+
+/// The inverse complementary error function, defined for `0 <= x <= 2`.
+pub unsafe fn erfc_inv(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    let (one, two) = (integer(1), integer(2));
+    if mpfr_nan_p(x) != 0 || mpfr_sgn(x) < 0 || mpfr_cmp_ui(x, 2) > 0 {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 || mpfr_cmp_ui(x, 2) == 0 {
+        mpfr_set_inf(rop, if mpfr_zero_p(x) != 0 { 1 } else { -1 });
+        return 0;
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    // erfc(-y) = 2 - erfc(y)
+    let negative = mpfr_cmp_ui(x, 1) > 0;
+    let q = if negative {
+        exact_sub(&two.value, x)
+    } else {
+        let mut q = working(mpfr_get_prec(x) as i64);
+        mpfr_set(&mut q.value, x, MPFR_RNDN);
+        q
+    };
+    if mpfr_cmp_ui_2exp(&q.value, 1, -1) >= 0 {
+        solve_erf(rop, &exact_sub(&one.value, &q.value), false, negative, rnd)
+    } else {
+        solve_erf(rop, &q, true, negative, rnd)
+    }
+}
+
+/// The generalized exponential integral `E_n(x) = ∫_1^∞ e^(-xt) / t^n dt` for `x >= 0`.
+pub unsafe fn expint_n(rop: mpfr_ptr, x: mpfr_srcptr, n: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || (mpfr_sgn(x) < 0) {
+        mpfr_set_nan(rop);
+        return 0;
+    }
+    if mpfr_zero_p(x) != 0 {
+        if n <= 1 {
+            mpfr_set_inf(rop, 1);
+            return 0;
+        }
+        return mpfr_ui_div(rop, 1, &integer(n as c_long - 1).value, rnd);
+    }
+    if mpfr_inf_p(x) != 0 {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    // If e^-x underflows, so does E_n(x) < e^-x / x, which is then far below the smallest
+    // positive number
+    let mut t = working(2);
+    mpfr_neg(&mut t.value, x, MPFR_RNDN);
+    mpfr_exp(&mut t.value, &t.value, MPFR_RNDZ);
+    if mpfr_zero_p(&t.value) != 0 {
+        return mpfr_set_ui_2exp(rop, 1, mpfr_get_emin() - 3, rnd);
+    }
+
+    extended(rop, rnd, || round_with(rop, rnd, |wp| {
+        if n == 0 {
+            // E_0(x) = e^-x / x
+            let mut r = working(wp);
+            mpfr_neg(&mut r.value, x, MPFR_RNDN);
+            mpfr_exp(&mut r.value, &r.value, MPFR_RNDN);
+            mpfr_div(&mut r.value, &r.value, x, MPFR_RNDN);
+            let err = exp_of(&r) + 2 - wp;
+            Approx::Within(r, err)
+        } else if mpfr_cmp_ui(x, (wp as c_ulong).saturating_add(n).saturating_mul(2)) >= 0 {
+            expint_asymptotic(x, n, wp)
+        } else if mpfr_cmp_ui(x, wp as c_ulong) >= 0 {
+            expint_fraction(x, n, wp)
+        } else {
+            expint_series(x, n, wp)
+        }
+    }))
+}
+
+/// `E_n(x) = e^-x / x Σ_k (-1)^k n (n + 1) ... (n + k - 1) / x^k` for `x >= 2 (n + wp)`. For real
+/// `x > 0` the rest of the series is bounded by the first omitted term, and the terms
+/// decrease at least twice each until that term is below `2^-wp`.
+unsafe fn expint_asymptotic(x: mpfr_srcptr, n: c_ulong, wp: i64) -> Approx {
+    let (mut s, mut t) = (working(wp), working(wp));
+    mpfr_set_ui(&mut s.value, 1, MPFR_RNDN);
+    mpfr_set_ui(&mut t.value, 1, MPFR_RNDN);
+    let mut k: c_ulong = 0;
+    loop {
+        mpfr_mul_ui(&mut t.value, &t.value, n + k, MPFR_RNDN);
+        mpfr_div(&mut t.value, &t.value, x, MPFR_RNDN);
+        mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+        k += 1;
+        if exp_of(&t) < -wp - 2 {
+            break;
+        }
+        mpfr_add(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    }
+    mpfr_neg(&mut t.value, x, MPFR_RNDN);
+    mpfr_exp(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut s.value, &s.value, &t.value, MPFR_RNDN);
+    mpfr_div(&mut s.value, &s.value, x, MPFR_RNDN);
+
+    // The k-th term has the relative error of 2k roundings and is below 2^-k, so the sum
+    // (which is at least 1/2) is within (2K + 18) 2^-wp relative to the exact one,
+    // including the rest; the last three operations add three roundings
+    let err = exp_of(&s) + log2_ceil(2 * k as u64 + 21) - wp;
+    Approx::Within(s, err)
+}
+
+/// `E_n(x) = e^-x (1/(x+) n/(1+) 1/(x+) (n+1)/(1+) 2/(x+) (n+2)/(1+) ...)` for `x >= wp`,
+/// which takes `O(wp² / (x + n))` terms.
+///
+/// All the elements of the continued fraction are positive, so its convergents `A_k / B_k`
+/// lie alternately above and below its value, and the difference of the last two bounds
+/// the rest. The recurrences `A_k = b_k A_(k-1) + a_k A_(k-2)` (and the same for `B_k`) add
+/// positive terms, so `A_k` and `B_k` have the relative errors of at most `3k` roundings.
+unsafe fn expint_fraction(x: mpfr_srcptr, n: c_ulong, wp: i64) -> Approx {
+    // (A_(k-2), A_(k-1)) and (B_(k-2), B_(k-1)), starting from A_(-1) = 1, A_0 = 0, B_(-1) = 0
+    // and B_0 = 1; they are scaled by the same powers of two to keep B_(k-1) about 1
+    let (mut a0, mut a1, mut b0, mut b1) = (working(wp), working(wp), working(wp), working(wp));
+    let (mut f, mut g, mut d) = (working(wp), working(wp), working(wp));
+    let (mut ta, mut tb, mut t) = (working(wp), working(wp), working(wp));
+    mpfr_set_ui(&mut a0.value, 1, MPFR_RNDN);
+    mpfr_set_ui(&mut a1.value, 0, MPFR_RNDN);
+    mpfr_set_ui(&mut b0.value, 0, MPFR_RNDN);
+    mpfr_set_ui(&mut b1.value, 1, MPFR_RNDN);
+    mpfr_set_ui(&mut f.value, 0, MPFR_RNDN);
+    let mut k: c_ulong = 0;
+    loop {
+        k += 1;
+        let (a, b_is_x) = if k % 2 == 1 {
+            (cmp::max((k - 1) / 2, 1), true)
+        } else {
+            (n + k / 2 - 1, false)
+        };
+        fraction_step(&mut ta, &a0, &a1, &mut t, a, if b_is_x { Some(x) } else { None });
+        fraction_step(&mut tb, &b0, &b1, &mut t, a, if b_is_x { Some(x) } else { None });
+        mem::swap(&mut a0, &mut a1);
+        mem::swap(&mut a1, &mut ta);
+        mem::swap(&mut b0, &mut b1);
+        mem::swap(&mut b1, &mut tb);
+        let e = -mpfr_get_exp(&b1.value);
+        mpfr_mul_2si(&mut a0.value, &a0.value, e, MPFR_RNDN);
+        mpfr_mul_2si(&mut a1.value, &a1.value, e, MPFR_RNDN);
+        mpfr_mul_2si(&mut b0.value, &b0.value, e, MPFR_RNDN);
+        mpfr_mul_2si(&mut b1.value, &b1.value, e, MPFR_RNDN);
+
+        mem::swap(&mut f, &mut g);
+        mpfr_div(&mut f.value, &a1.value, &b1.value, MPFR_RNDN);
+        mpfr_sub(&mut d.value, &f.value, &g.value, MPFR_RNDN);
+        if k >= 2 && exp_of(&d) < exp_of(&f) - wp - 2 {
+            break;
+        }
+        if k as i64 > 4 * wp + 64 {
+            return retry(wp);
+        }
+    }
+
+    let mut r = working(wp);
+    mpfr_neg(&mut r.value, x, MPFR_RNDN);
+    mpfr_exp(&mut r.value, &r.value, MPFR_RNDN);
+    mpfr_mul(&mut r.value, &r.value, &f.value, MPFR_RNDN);
+    // The convergents have the relative errors of at most 6k + 1 roundings, which also bounds
+    // the error of their difference together with its rounding; the exponential and
+    // the product add two more
+    let c = log2_ceil(2 * (12 * k as u64 + 6));
+    let err = exp_of(&r) + cmp::max(exp_of(&d) - exp_of(&f) + 1, c - wp) + 2;
+    Approx::Within(r, err)
+}
+
+/// Sets `q = b p1 + a p0` for the recurrences of the convergents, where `b` is `x` or 1.
+unsafe fn fraction_step(q: &mut BigFloat, p0: &BigFloat, p1: &BigFloat, t: &mut BigFloat,
+                        a: c_ulong, b: Option<mpfr_srcptr>) {
+    match b {
+        Some(x) => { mpfr_mul(&mut q.value, &p1.value, x, MPFR_RNDN); }
+        None => { mpfr_set(&mut q.value, &p1.value, MPFR_RNDN); }
+    }
+    mpfr_mul_ui(&mut t.value, &p0.value, a, MPFR_RNDN);
+    mpfr_add(&mut q.value, &q.value, &t.value, MPFR_RNDN);
+}
+
+/// `E_n(x) = (-x)^(n-1) / (n-1)! (ψ(n) - ln x) - Σ_{k != n-1} (-x)^k / ((k - n + 1) k!)`.
+///
+/// The terms grow up to about `e^x` while the result is about `e^-x`, so the sum is computed
+/// with about `3x` more bits. After `k >= 2x` the terms `t_k = (-x)^k / k!` decrease at least
+/// twice each, so the rest of the series is below `|t_K| (2 + |ψ(n) - ln x|)` where
+/// `|ψ(n)| < 45`.
+unsafe fn expint_series(x: mpfr_srcptr, n: c_ulong, wp: i64) -> Approx {
+    let xf = mpfr_get_d(x, MPFR_RNDU);
+    let wq = wp + (3.0 * xf).ceil() as i64 + 2 * log2_ceil((xf as u64).saturating_add(n).saturating_add(wp as u64)) + 16;
+    let mut lx = working(wq);
+    mpfr_log(&mut lx.value, x, MPFR_RNDN);
+    let lx_exp = cmp::max(exp_of(&lx), 6) + 1;
+
+    let (mut s, mut t, mut u) = (working(wq), working(wq), working(wq));
+    mpfr_set_ui(&mut s.value, 0, MPFR_RNDN);
+    mpfr_set_ui(&mut t.value, 1, MPFR_RNDN);
+    let mut max_exp = ::std::i64::MIN / 2;
+    let mut k: c_ulong = 0;
+    loop {
+        if k == n - 1 {
+            mpfr_set_ui(&mut u.value, n, MPFR_RNDN);
+            mpfr_digamma(&mut u.value, &u.value, MPFR_RNDN);
+            mpfr_sub(&mut u.value, &u.value, &lx.value, MPFR_RNDN);
+            mpfr_mul(&mut u.value, &u.value, &t.value, MPFR_RNDN);
+            mpfr_add(&mut s.value, &s.value, &u.value, MPFR_RNDN);
+            max_exp = cmp::max(max_exp, exp_of(&t) + lx_exp);
+        } else if k > n - 1 {
+            mpfr_div_ui(&mut u.value, &t.value, k - (n - 1), MPFR_RNDN);
+            mpfr_sub(&mut s.value, &s.value, &u.value, MPFR_RNDN);
+        } else {
+            mpfr_div_ui(&mut u.value, &t.value, (n - 1) - k, MPFR_RNDN);
+            mpfr_add(&mut s.value, &s.value, &u.value, MPFR_RNDN);
+        }
+        max_exp = cmp::max(max_exp, cmp::max(exp_of(&u), exp_of(&s)));
+
+        k += 1;
+        mpfr_mul(&mut t.value, &t.value, x, MPFR_RNDN);
+        mpfr_div_ui(&mut t.value, &t.value, k, MPFR_RNDN);
+        mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+        if k as f64 >= 2.0 * xf + 1.0 && exp_of(&t) + lx_exp < exp_of(&s) - wp - 8 {
+            break;
+        }
+    }
+
+    // The k-th term has the relative error of at most 2k + 6 roundings
+    let k = k as u64;
+    let rounding = max_exp + log2_ceil((k + 1) * (2 * k + 6)) - wq;
+    let rest = exp_of(&t) + lx_exp + 1;
+    let err = cmp::max(rounding, rest) + 1;
+    Approx::Within(s, err)
+}
+
+/// The upper incomplete gamma function `Γ(a, y)`, or the regularized one `Q(a, y) = Γ(a, y) /
+/// Γ(a)` if `regularized` is set, for `y >= a + 1` by the expansion `y^(a - 1) e^-y Σ u_n`
+/// with `u_0 = 1` and `u_n = u_(n - 1) (a - n) / y`, which follows from
+/// `Γ(b, y) = y^(b - 1) e^-y + (b - 1) Γ(b - 1, y)`.
+///
+/// The rest after the `n`-th term is `u_(n + 1) g(b)` for `b = a - n - 1` and
+/// `g(b) = Γ(b, y) / (y^(b - 1) e^-y)`, where `0 < g(b) <= 1` for `b <= 1` and, by the
+/// concavity of `(b - 1) ln t - t`, `g(b) <= y / (y - b + 1)` for `1 < b < y + 1`. The terms
+/// decrease while `|a - n - 1| < y`, so the expansion converges quickly for large `y` (it is
+/// finite for an integer `a`), and `None` is returned if it stops decreasing first.
+pub unsafe fn gamma_upper_expansion(a: &BigFloat, y: &BigFloat, regularized: bool,
+                                    wp: i64) -> Option<Approx> {
+    let mut s = Sum::new(wp);
+    let (mut term, mut r, mut t) = (working(wp), working(wp), working(wp));
+    mpfr_set_ui(&mut term.value, 1, MPFR_RNDN);
+    s.add(&term, 0);
+    let mut n: u64 = 0;
+    loop {
+        // The factor of the next term, exactly
+        let b = exact_sub(&a.value, &integer(n as c_long + 1).value);
+        if mpfr_zero_p(&b.value) != 0 {
+            break;
+        }
+        // An upper bound of |b| / (y - b + 1) for b > 1, or of |b| / y
+        if mpfr_cmp_ui(&b.value, 1) > 0 {
+            mpfr_sub(&mut r.value, &y.value, &b.value, MPFR_RNDD);
+            mpfr_add_ui(&mut r.value, &r.value, 1, MPFR_RNDD);
+        } else {
+            mpfr_set(&mut r.value, &y.value, MPFR_RNDD);
+        }
+        mpfr_abs(&mut t.value, &b.value, MPFR_RNDU);
+        mpfr_div(&mut r.value, &t.value, &r.value, MPFR_RNDU);
+        let rest = exp_of(&term) + exp_of(&r) + 1;
+        if rest < exp_of(&s.value) - wp {
+            s.add_error(rest);
+            break;
+        }
+        if mpfr_cmpabs(&b.value, &y.value) >= 0 {
+            return None;
+        }
+        // Each term has the relative error of at most 2n roundings
+        n += 1;
+        mpfr_mul(&mut term.value, &term.value, &b.value, MPFR_RNDN);
+        mpfr_div(&mut term.value, &term.value, &y.value, MPFR_RNDN);
+        s.add(&term, 2 * n);
+    }
+
+    let am1 = exact_sub(&a.value, &integer(1).value);
+    let mut l = Sum::new(wp);
+    mpfr_log(&mut t.value, &y.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &am1.value, MPFR_RNDN);
+    l.add(&t, 2);
+    l.sub(y, 0);
+    if regularized {
+        mpfr_lngamma(&mut t.value, &a.value, MPFR_RNDN);
+        l.sub(&t, 1);
+    }
+    let (pref, ep) = match split(exp_approx(l, wp)) {
+        Ok(v) => v,
+        Err(r) => return Some(r)
+    };
+    let es = s.err();
+    let (r, e) = mul_approx(&pref, ep, &s.value, es, wp);
+    Some(Approx::Within(r, e))
+}
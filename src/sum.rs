@@ -0,0 +1,123 @@
+use std::cmp::Ordering;
+use std::iter::{Sum, Product};
+
+use libc::{c_int, c_ulong};
+
+use mpfr_sys::*;
+
+use {BigFloat, Precision, RoundingMode, global_rounding_mode, ternary, grnd};
+use ext::mpfr_dot;
+use ziv::{integer, exact_mul, extended};
+use precision::ToPrecision;
+
+/// Computes the sum of `terms` into `rop` with a single rounding.
+pub unsafe fn sum_into(rop: mpfr_ptr, terms: &[&BigFloat], rnd: mpfr_rnd_t) -> c_int {
+    // mpfr_sum does not modify the terms even though it accepts non-const pointers
+    let mut ptrs: Vec<mpfr_ptr> = terms.iter()
+        .map(|t| &t.value as *const __mpfr_struct as mpfr_ptr)
+        .collect();
+    mpfr_sum(rop, ptrs.as_mut_ptr(), ptrs.len() as c_ulong, rnd)
+}
+
+/// Returns the largest precision of the given numbers or the default precision
+/// if there are none.
+pub fn max_prec(xs: &[&BigFloat]) -> Precision {
+    match xs.iter().map(|x| x.prec().bits()).max() {
+        Some(bits) => bits.bits(),
+        None => BigFloat::get_default_prec()
+    }
+}
+
+impl BigFloat {
+    /// Computes the sum of `terms` with the given precision and rounding mode.
+    ///
+    /// Unlike adding the terms one by one, the result is rounded only once, so it is
+    /// correctly rounded regardless of the number of terms and their magnitudes.
+    /// The sum of an empty slice is +0.
+    pub fn sum(terms: &[&BigFloat], precision: Precision, rnd: RoundingMode) -> BigFloat {
+        let mut r = BigFloat::fresh_with_prec(precision);
+        unsafe {
+            sum_into(&mut r.value, terms, rnd as mpfr_rnd_t);
+        }
+        r
+    }
+
+    /// Computes the dot product of `a` and `b` with the given precision.
+    ///
+    /// The result is rounded only once using the global rounding mode, so it is correctly
+    /// rounded regardless of the length of the vectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` have different lengths.
+    #[inline]
+    pub fn dot(a: &[BigFloat], b: &[BigFloat], precision: Precision) -> BigFloat {
+        BigFloat::dot_round(a, b, precision, global_rounding_mode::get()).0
+    }
+
+    /// The same as `dot()`, but with an explicit rounding mode. Also returns the ternary
+    /// value: `Greater` if the result was rounded up, `Less` if down, `Equal` if it is exact.
+    pub fn dot_round(a: &[BigFloat], b: &[BigFloat], precision: Precision,
+                     rnd: RoundingMode) -> (BigFloat, Ordering) {
+        assert!(a.len() == b.len(), "dot product of vectors of different lengths");
+
+        let ptrs = |xs: &[BigFloat]| -> Vec<mpfr_ptr> {
+            xs.iter().map(|x| &x.value as *const __mpfr_struct as mpfr_ptr).collect()
+        };
+        let (pa, pb) = (ptrs(a), ptrs(b));
+
+        let mut r = BigFloat::fresh_with_prec(precision);
+        let t = unsafe {
+            mpfr_dot(&mut r.value, pa.as_ptr(), pb.as_ptr(), a.len() as c_ulong, rnd as mpfr_rnd_t)
+        };
+        (r, ternary(t))
+    }
+}
+
+// Iterator sums are correctly rounded as well; the result has the largest precision among
+// the terms and is rounded with the global rounding mode.
+
+impl Sum for BigFloat {
+    fn sum<I: Iterator<Item=BigFloat>>(iter: I) -> BigFloat {
+        let terms: Vec<BigFloat> = iter.collect();
+        let refs: Vec<&BigFloat> = terms.iter().collect();
+        BigFloat::sum(&refs, max_prec(&refs), global_rounding_mode::get())
+    }
+}
+
+impl<'a> Sum<&'a BigFloat> for BigFloat {
+    fn sum<I: Iterator<Item=&'a BigFloat>>(iter: I) -> BigFloat {
+        let refs: Vec<&BigFloat> = iter.collect();
+        BigFloat::sum(&refs, max_prec(&refs), global_rounding_mode::get())
+    }
+}
+
+// Products are correctly rounded as well: the factors are multiplied exactly in the widest
+// exponent range, the precisions of the partial products adding up, and the result is rounded
+// once to the largest precision among them with the global rounding mode; the product of
+// no factors is 1.
+
+impl Product for BigFloat {
+    fn product<I: Iterator<Item=BigFloat>>(iter: I) -> BigFloat {
+        let factors: Vec<BigFloat> = iter.collect();
+        factors.iter().product()
+    }
+}
+
+impl<'a> Product<&'a BigFloat> for BigFloat {
+    fn product<I: Iterator<Item=&'a BigFloat>>(iter: I) -> BigFloat {
+        let factors: Vec<&BigFloat> = iter.collect();
+        let mut r = BigFloat::fresh_with_prec(max_prec(&factors));
+        unsafe {
+            let rop: mpfr_ptr = &mut r.value;
+            extended(rop, grnd(), || {
+                let mut p = integer(1);
+                for f in factors.iter() {
+                    p = exact_mul(&p.value, &f.value);
+                }
+                mpfr_set(rop, &p.value, grnd())
+            });
+        }
+        r
+    }
+}
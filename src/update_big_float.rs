@@ -4,7 +4,11 @@ use libc::{uintmax_t, intmax_t, c_double, c_float, c_int, c_long, c_ulong};
 
 use mpfr_sys::*;
 
+#[cfg(feature = "gmp")]
+use gmp::{Mpz, Mpq};
+
 use {BigFloat, grnd};
+use primitive::{Operand, ToOperand};
 
 pub trait UpdateBigFloat {
     fn update_big_float(self, target: &mut BigFloat);
@@ -43,9 +47,32 @@ impl_big_float_set! {
     i32 as c_long,    mpfr_set_si;
     u32 as c_ulong,   mpfr_set_ui;
     i64 as intmax_t,  __gmpfr_set_sj;
-    u64 as uintmax_t, __gmpfr_set_uj
+    u64 as uintmax_t, __gmpfr_set_uj;
+    isize as intmax_t,  __gmpfr_set_sj;
+    usize as uintmax_t, __gmpfr_set_uj
+}
+
+macro_rules! impl_big_float_set_wide {
+    ($($t:ty),+) => {
+        $(
+        impl UpdateBigFloat for $t {
+            fn update_big_float(self, target: &mut BigFloat) {
+                unsafe {
+                    match self.to_operand() {
+                        Operand::Signed(v)   => mpfr_set_si(&mut target.value, v, grnd()),
+                        Operand::Unsigned(v) => mpfr_set_ui(&mut target.value, v, grnd()),
+                        Operand::Double(v)   => mpfr_set_d(&mut target.value, v, grnd()),
+                        Operand::Exact(v)    => mpfr_set(&mut target.value, &v.value, grnd())
+                    };
+                }
+            }
+        }
+        )+
+    }
 }
 
+impl_big_float_set_wide! { i128, u128 }
+
 impl<'a> UpdateBigFloat for &'a str {
     #[inline]
     fn update_big_float(self, target: &mut BigFloat) {
@@ -67,4 +94,31 @@ impl<'a> UpdateBigFloat for (&'a str, usize) {
     }
 }
 
-// TODO: when bindings for libgmp are available, use them here too, probably as a Cargo feature
+#[cfg(feature = "gmp")]
+impl<'a> UpdateBigFloat for &'a Mpz {
+    fn update_big_float(self, target: &mut BigFloat) {
+        unsafe {
+            mpfr_set_z(&mut target.value, self.inner(), grnd());
+        }
+    }
+}
+
+/// Sets the number to `z * 2^e`.
+#[cfg(feature = "gmp")]
+impl<'a> UpdateBigFloat for (&'a Mpz, i64) {
+    fn update_big_float(self, target: &mut BigFloat) {
+        unsafe {
+            mpfr_set_z_2exp(&mut target.value, self.0.inner(),
+                            self.1 as mpfr_exp_t, grnd());
+        }
+    }
+}
+
+#[cfg(feature = "gmp")]
+impl<'a> UpdateBigFloat for &'a Mpq {
+    fn update_big_float(self, target: &mut BigFloat) {
+        unsafe {
+            mpfr_set_q(&mut target.value, self.inner(), grnd());
+        }
+    }
+}
@@ -0,0 +1,1117 @@
+// The Hurwitz zeta function, the polylogarithm, the Clausen function and the Dirichlet eta
+// function, with the same signatures as MPFR functions.
+//
+// They are computed with Ziv's strategy (see `ziv.rs`) from series whose rounding errors are
+// tracked with `Sum`. The values at nonpositive integers (and the polylogarithms of
+// nonpositive integer order) are rational and often dyadic, so up to `EXACT_ORDER_MAX` they
+// are computed exactly and rounded once instead; beyond it the exact values get too long,
+// and the series are summed until the approximation determines the rational value (see
+// `round_rational`). No other value at a finite dyadic argument is dyadic, except for the
+// ones handled separately.
+
+use std::cmp;
+
+use libc::{c_int, c_long, c_ulong};
+
+use mpfr_sys::*;
+
+use BigFloat;
+use ziv::{Approx, Sum, round_with, working, exp_of, log2_ceil, exact_add, exact_sub, exact_mul};
+use ziv::{integer, set_nan, flip};
+
+/// Returns `x` as `c_long` if it is an integer which fits into it.
+unsafe fn small_integer(x: mpfr_srcptr) -> Option<c_long> {
+    if mpfr_integer_p(x) != 0 && mpfr_fits_slong_p(x, MPFR_RNDN) != 0 {
+        Some(mpfr_get_si(x, MPFR_RNDN))
+    } else {
+        None
+    }
+}
+
+/// Returns an upper bound of `|x|` as `f64`.
+unsafe fn abs_f64(x: mpfr_srcptr) -> f64 {
+    mpfr_get_d(x, MPFR_RNDA).abs()
+}
+
+unsafe fn set_inf(rop: mpfr_ptr, sign: c_int) -> c_int {
+    mpfr_set_inf(rop, sign);
+    0
+}
+
+// The functions at nonpositive integers are computed exactly only up to this order
+const EXACT_ORDER_MAX: c_ulong = 256;
+
+/// Returns `-n` for `n <= 0`, which fits into `c_ulong` even for the smallest `c_long`.
+#[inline]
+fn negated(n: c_long) -> c_ulong {
+    (-(n + 1)) as c_ulong + 1
+}
+
+/// Returns `n` as `i64` for the bounds of bit lengths, saturating.
+#[inline]
+fn bits(n: c_ulong) -> i64 {
+    cmp::min(n as u64, ::std::i64::MAX as u64) as i64
+}
+
+/// Returns the number of bits after the binary point of a regular `x`.
+unsafe fn fraction_bits(x: mpfr_srcptr) -> i64 {
+    cmp::max(mpfr_min_prec(x) as i64 - mpfr_get_exp(x) as i64, 0)
+}
+
+/// Returns the negative integer `-n`.
+unsafe fn minus(n: c_ulong) -> BigFloat {
+    let mut r = working(64);
+    mpfr_set_ui(&mut r.value, n, MPFR_RNDN);
+    mpfr_neg(&mut r.value, &r.value, MPFR_RNDN);
+    r
+}
+
+/// Stores the rational value approximated by `f` (see `round_with`) into `rop`, where
+/// `den()` is a multiple of its denominator below `2^den_exp`.
+///
+/// Once the error of the approximation times the denominator is below 1/4, the numerator is
+/// the nearest integer, and the value is then computed from it at any precision, exactly if it
+/// is dyadic (a zero numerator gives +0). So `den()` is only called when the working precision
+/// exceeds `den_exp`.
+unsafe fn round_rational<D, F>(rop: mpfr_ptr, rnd: mpfr_rnd_t, den_exp: i64, den: D, mut f: F)
+    -> c_int where D: FnOnce() -> BigFloat, F: FnMut(i64) -> Approx
+{
+    let mut den = Some(den);
+    let mut value: Option<(BigFloat, BigFloat)> = None;
+    round_with(rop, rnd, |wp| {
+        if value.is_none() {
+            let a = f(wp);
+            let known = match a {
+                Approx::Within(ref v, err) => {
+                    mpfr_number_p(&v.value) != 0 && err.saturating_add(den_exp) <= -2
+                }
+                _ => false
+            };
+            if !known {
+                return a;
+            }
+            if let Approx::Within(v, _) = a {
+                let d = den.take().unwrap()();
+                let mut num = exact_mul(&v.value, &d.value);
+                mpfr_rint(&mut num.value, &num.value, MPFR_RNDN);
+                if mpfr_zero_p(&num.value) != 0 {
+                    mpfr_set_zero(&mut num.value, 1);
+                }
+                value = Some((num, d));
+            }
+        }
+        let (ref num, ref d) = *value.as_ref().unwrap();
+        // A dyadic quotient has no more bits than the numerator
+        let mut q = working(cmp::max(mpfr_get_prec(&num.value) as i64, wp));
+        if mpfr_div(&mut q.value, &num.value, &d.value, MPFR_RNDN) == 0 {
+            Approx::Exact(q)
+        } else {
+            let err = exp_of(&q) - wp;
+            Approx::Within(q, err)
+        }
+    })
+}
+
+/// The Hurwitz zeta function `ζ(s, a) = Σ_{k>=0} 1 / (a + k)^s` (analytically continued
+/// to `s < 1`) for `a > 0`.
+pub unsafe fn hurwitz_zeta(rop: mpfr_ptr, s: mpfr_srcptr, a: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(s) != 0 || mpfr_nan_p(a) != 0 || mpfr_sgn(a) <= 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(s) != 0 {
+        if mpfr_sgn(s) < 0 {
+            return set_nan(rop);
+        }
+        return match mpfr_cmp_ui(a, 1) {
+            c if c > 0 => { mpfr_set_zero(rop, 1); 0 }
+            0 => mpfr_set_ui(rop, 1, rnd),
+            _ => set_inf(rop, 1)
+        };
+    }
+    if mpfr_inf_p(a) != 0 {
+        if mpfr_cmp_ui(s, 1) > 0 {
+            mpfr_set_zero(rop, 1);
+            return 0;
+        }
+        return set_nan(rop);
+    }
+    if mpfr_cmp_ui(s, 1) == 0 {
+        return set_inf(rop, 1);
+    }
+    match small_integer(s) {
+        Some(n) if n <= 0 && negated(n) <= EXACT_ORDER_MAX => {
+            hurwitz_negative(rop, negated(n), a, rnd)
+        }
+        Some(n) if n < 0 => hurwitz_rational(rop, s, negated(n), a, rnd),
+        _ => round_with(rop, rnd, |wp| hurwitz_series(s, a, wp))
+    }
+}
+
+/// `ζ(-n, a) = -B_{n+1}(a) / (n + 1)` for large `n`, from Hurwitz's formula or the series.
+/// With `m = n + 1` and `a = A / 2^e`, the denominator divides `2^(em+1) m` times the product
+/// of the odd primes up to `m + 1`, by the theorem of von Staudt and Clausen on the
+/// denominators of `B_k`; the primes have less than `1.47 (m + 1)` bits (Rosser and
+/// Schoenfeld).
+unsafe fn hurwitz_rational(rop: mpfr_ptr, s: mpfr_srcptr, n: c_ulong, a: mpfr_srcptr,
+                           rnd: mpfr_rnd_t) -> c_int {
+    if trivial_zero(n, a) {
+        mpfr_set_zero(rop, 1);
+        return 0;
+    }
+    let m = bits(n).saturating_add(1);
+    let e = fraction_bits(a);
+    let den_exp = e.saturating_mul(m).saturating_add(log2_ceil(m as u64) + 2)
+                   .saturating_add(m.saturating_add(1).saturating_mul(3) / 2 + 1);
+    let den = || {
+        let mut d = working(den_exp + 1);
+        mpfr_set_ui(&mut d.value, m as c_ulong, MPFR_RNDN);
+        let end = m as usize + 2;
+        let mut composite: Vec<bool> = (0..end).map(|_| false).collect();
+        for q in 3..end {
+            if q % 2 == 1 && !composite[q] {
+                mpfr_mul_ui(&mut d.value, &d.value, q as c_ulong, MPFR_RNDN);
+                let mut j = q * q;
+                while j < end {
+                    composite[j] = true;
+                    j += q;
+                }
+            }
+        }
+        mpfr_mul_2si(&mut d.value, &d.value, (e * m + 1) as c_long, MPFR_RNDN);
+        d
+    };
+    round_rational(rop, rnd, den_exp, den, |wp| {
+        hurwitz_fourier(n, a, wp).unwrap_or_else(|| hurwitz_series(s, a, wp))
+    })
+}
+
+/// Whether `ζ(-n, a) = -B_{n+1}(a) / (n + 1)` vanishes as `B_m(1/2) = B_m(1) = 0` for odd
+/// `m > 1`.
+unsafe fn trivial_zero(n: c_ulong, a: mpfr_srcptr) -> bool {
+    n > 0 && n % 2 == 0 && (mpfr_cmp_ui(a, 1) == 0 || mpfr_cmp_ui_2exp(a, 1, -1) == 0)
+}
+
+/// Hurwitz's formula `ζ(-n, b) = 2 n! / (2π)^(n+1) Σ_{k>=1} cos(2πkb - π(n + 1)/2) / k^(n+1)`
+/// for `0 < b <= 1`, with `ζ(-n, a) = ζ(-n, b) - Σ_{j<J} (b + j)^n` for `a = b + J`. Returns
+/// `None` where the Euler-Maclaurin formula is cheaper, i.e. for `J > 2n + 2` or when more
+/// than `4 wp` terms of the series are needed.
+///
+/// `K` terms with `(K + 1)^n >= 2^(wp+2)` leave the rest below
+/// `(K + 1)^-(n+1) + (K + 1)^-n / n <= 2^(-wp-1)`. The terms are computed with the absolute
+/// error of 23 roundings at the precision `wp2` relative to `k^-(n+1)`, the sum of which is
+/// below 2, and the prefactor with the relative error of `2n + 5` ones, so with
+/// `2^wp2 >= 8 (n + K) 2^wp` the error of the first part is below `2^(-wp+3)` times the
+/// prefactor.
+unsafe fn hurwitz_fourier(n: c_ulong, a: mpfr_srcptr, wp: i64) -> Option<Approx> {
+    let k_max = ((wp + 2) as f64 / n as f64).exp2().ceil().max(2.0);
+    let mut j = working(cmp::max(mpfr_get_prec(a) as i64, 64));
+    mpfr_ceil(&mut j.value, a);
+    mpfr_sub_ui(&mut j.value, &j.value, 1, MPFR_RNDN);
+    let j_max = n.saturating_mul(2).saturating_add(2);
+    if k_max > (4 * wp) as f64 || mpfr_cmp_ui(&j.value, j_max) > 0 {
+        return None;
+    }
+    let k_max = k_max as c_ulong;
+    let b = exact_sub(a, &j.value);
+    let j = mpfr_get_ui(&j.value, MPFR_RNDN);
+
+    let mut sum = Sum::new(wp);
+    let mut t = working(wp);
+    if !trivial_zero(n, &b.value) {
+        let (f, series) = hurwitz_fourier_parts(n, &b, k_max, wp);
+        mpfr_mul(&mut t.value, &f.value, &series.value, MPFR_RNDN);
+        sum.add(&t, 0);
+        sum.add_error(exp_of(&f) + 3 - wp);
+    }
+    for i in 0..j {
+        let v = exact_add(&b.value, &integer(i as c_long).value);
+        mpfr_pow_ui(&mut t.value, &v.value, n, MPFR_RNDN);
+        sum.sub(&t, 1);
+    }
+    Some(sum.approx())
+}
+
+/// Returns the prefactor and the sum of the first `k_max - 1` terms of Hurwitz's formula (see
+/// `hurwitz_fourier`).
+unsafe fn hurwitz_fourier_parts(n: c_ulong, b: &BigFloat, k_max: c_ulong, wp: i64)
+    -> (BigFloat, BigFloat)
+{
+    let m = n + 1;
+    let wp2 = wp + log2_ceil(n.saturating_add(k_max) as u64) + 3;
+    let (mut t, mut u) = (working(wp2), working(wp2));
+    let mut f = working(wp2);
+    mpfr_set_ui(&mut t.value, m, MPFR_RNDN);
+    mpfr_gamma(&mut f.value, &t.value, MPFR_RNDN);
+    let mut pi2 = working(wp2);
+    mpfr_const_pi(&mut pi2.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut pi2.value, &pi2.value, 1, MPFR_RNDN);
+    mpfr_pow_ui(&mut t.value, &pi2.value, m, MPFR_RNDN);
+    if mpfr_inf_p(&t.value) != 0 {
+        // n! / (2π)^(n+1) > (2π)^(n+1) for n > 107, so the quotient overflows as well
+        mpfr_set_inf(&mut f.value, 1);
+    } else {
+        mpfr_div(&mut f.value, &f.value, &t.value, MPFR_RNDN);
+    }
+    mpfr_mul_2ui(&mut f.value, &f.value, 1, MPFR_RNDN);
+
+    let mut series = working(wp2);
+    mpfr_set_ui(&mut series.value, 0, MPFR_RNDN);
+    for k in 1..k_max {
+        // θ = 2π frac(kb), and cos(θ - π(n + 1)/2) by the residue of n + 1 modulo 4
+        let kb = exact_mul(&integer(k as c_long).value, &b.value);
+        let mut th = working(mpfr_get_prec(&kb.value) as i64);
+        mpfr_frac(&mut th.value, &kb.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &th.value, &pi2.value, MPFR_RNDN);
+        if m % 2 == 0 {
+            mpfr_cos(&mut t.value, &t.value, MPFR_RNDN);
+        } else {
+            mpfr_sin(&mut t.value, &t.value, MPFR_RNDN);
+        }
+        if m % 4 >= 2 {
+            mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+        }
+        mpfr_set_ui(&mut u.value, k, MPFR_RNDN);
+        mpfr_pow_ui(&mut u.value, &u.value, m, MPFR_RNDN);
+        mpfr_div(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+        mpfr_add(&mut series.value, &series.value, &t.value, MPFR_RNDN);
+    }
+    (f, series)
+}
+
+/// `ζ(-n, a) = -B_{n+1}(a) / (n + 1)`, computed exactly from the Bernoulli polynomial
+/// `B_m(a) = Σ_{k=0..m} 1/(k + 1) Σ_{j=0..k} (-1)^j C(k, j) (a + j)^m`.
+unsafe fn hurwitz_negative(rop: mpfr_ptr, n: c_ulong, a: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    let m = n + 1;
+    // (m + 1)! is divisible by every k + 1
+    let pf = (m as i64 + 1) * (log2_ceil(m as u64 + 1) + 1) + 64;
+    let mut f = working(pf);
+    mpfr_fac_ui(&mut f.value, m + 1, MPFR_RNDN);
+
+    let powers: Vec<BigFloat> = (0..m + 1).map(|j| {
+        let v = exact_add(a, &integer(j as c_long).value);
+        let mut p = working(mpfr_get_prec(&v.value) as i64 * m as i64);
+        mpfr_pow_ui(&mut p.value, &v.value, m, MPFR_RNDN);
+        p
+    }).collect();
+
+    let mut total = integer(0);
+    let mut c = working(2 * m as i64 + 64);
+    let mut w = working(pf);
+    for k in 0..m + 1 {
+        let mut inner = integer(0);
+        mpfr_set_ui(&mut c.value, 1, MPFR_RNDN);
+        for j in 0..k + 1 {
+            let t = exact_mul(&c.value, &powers[j as usize].value);
+            inner = if j % 2 == 0 {
+                exact_add(&inner.value, &t.value)
+            } else {
+                exact_sub(&inner.value, &t.value)
+            };
+            // C(k, j + 1) = C(k, j) (k - j) / (j + 1)
+            mpfr_mul_ui(&mut c.value, &c.value, k - j, MPFR_RNDN);
+            mpfr_div_ui(&mut c.value, &c.value, j + 1, MPFR_RNDN);
+        }
+        mpfr_div_ui(&mut w.value, &f.value, k + 1, MPFR_RNDN);
+        total = exact_add(&total.value, &exact_mul(&w.value, &inner.value).value);
+    }
+
+    // The zeros are +0, as those of mpfr_zeta
+    if mpfr_zero_p(&total.value) == 0 {
+        mpfr_neg(&mut total.value, &total.value, MPFR_RNDN);
+    }
+    let mut d = working(pf + 64);
+    mpfr_mul_ui(&mut d.value, &f.value, m, MPFR_RNDN);
+    mpfr_div(rop, &total.value, &d.value, rnd)
+}
+
+/// The Euler-Maclaurin formula
+///
+/// `ζ(s, a) = Σ_{k<N} (a + k)^-s + (a + N)^(1-s) / (s - 1) + (a + N)^-s / 2
+///            + Σ_{j=1..M} B_2j / (2j)! (s)_{2j-1} (a + N)^(-s-2j+1) + R`
+///
+/// where `(s)_k` is the rising factorial and `|R| <= 4 |(s)_2M| / (2π)^2M (a + N)^(-s-2M+1) /
+/// (s + 2M - 1)` for `s + 2M - 1 > 0` (F. Johansson, Rigorous high-precision computation of
+/// the Hurwitz zeta function and its derivatives, 2015). With `B_2j / (2j)! =
+/// (-1)^(j+1) 2 ζ(2j) / (2π)^2j` the correction terms are `(-1)^(j+1) 2 ζ(2j) c_j P` where
+/// `P = (a + N)^(1-s)` and `c_j = (s)_{2j-1} / (2π (a + N))^2j`, and `|R| <= 4 |c_M| P`.
+unsafe fn hurwitz_series(s: mpfr_srcptr, a: mpfr_srcptr, wp: i64) -> Approx {
+    let abs_s = abs_f64(s);
+    let m = cmp::max(wp / 5 + 5, ((1.0 - mpfr_get_d(s, MPFR_RNDD)) / 2.0).ceil() as i64 + 1);
+    let n = (abs_s + 2.0 * m as f64 + 1.0 - mpfr_get_d(a, MPFR_RNDD)).ceil().max(0.0) as c_ulong;
+
+    let mut sum = Sum::new(wp);
+    let mut t = working(wp);
+    let mut minus_s = working(mpfr_get_prec(s) as i64);
+    mpfr_neg(&mut minus_s.value, s, MPFR_RNDN);
+    for k in 0..n {
+        let v = exact_add(a, &integer(k as c_long).value);
+        mpfr_pow(&mut t.value, &v.value, &minus_s.value, MPFR_RNDN);
+        sum.add(&t, 1);
+    }
+
+    let v = exact_add(a, &integer(n as c_long).value);
+    let one = integer(1);
+    let mut p = working(wp);
+    mpfr_pow(&mut p.value, &v.value, &exact_sub(&one.value, s).value, MPFR_RNDN);
+    mpfr_div(&mut t.value, &p.value, &exact_sub(s, &one.value).value, MPFR_RNDN);
+    sum.add(&t, 2);
+    mpfr_div(&mut t.value, &p.value, &v.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+    sum.add(&t, 2);
+
+    // q = (2π (a + N))², with the relative error of 5 roundings
+    let mut q = working(wp);
+    mpfr_const_pi(&mut q.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut q.value, &q.value, 1, MPFR_RNDN);
+    mpfr_mul(&mut q.value, &q.value, &v.value, MPFR_RNDN);
+    mpfr_sqr(&mut q.value, &q.value, MPFR_RNDN);
+    let mut c = working(wp);
+    mpfr_div(&mut c.value, s, &q.value, MPFR_RNDN);
+    let (mut z, mut u) = (working(wp), working(wp));
+    for j in 1..(m as c_ulong + 1) {
+        if j > 1 {
+            // c_j = c_{j-1} (s + 2j - 3) (s + 2j - 2) / q
+            mpfr_add_ui(&mut u.value, s, 2 * j - 3, MPFR_RNDN);
+            mpfr_mul(&mut c.value, &c.value, &u.value, MPFR_RNDN);
+            mpfr_add_ui(&mut u.value, s, 2 * j - 2, MPFR_RNDN);
+            mpfr_mul(&mut c.value, &c.value, &u.value, MPFR_RNDN);
+            mpfr_div(&mut c.value, &c.value, &q.value, MPFR_RNDN);
+        }
+        mpfr_zeta_ui(&mut z.value, 2 * j, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &c.value, &z.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &p.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        // c_j has the relative error of 10j roundings
+        let roundings = 10 * j as u64 + 4;
+        if j % 2 == 1 {
+            sum.add(&t, roundings);
+        } else {
+            sum.sub(&t, roundings);
+        }
+    }
+    sum.add_error(exp_of(&c) + exp_of(&p) + 3);
+    sum.approx()
+}
+
+/// The polylogarithm `Li_s(x) = Σ_{k>=1} x^k / k^s` of a real order `s` for `x <= 1`
+/// (where it is real). For integer `s` see `polylog_si`.
+pub unsafe fn polylog(rop: mpfr_ptr, x: mpfr_srcptr, s: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_nan_p(s) != 0 {
+        return set_nan(rop);
+    }
+    if let Some(n) = small_integer(s) {
+        return polylog_si(rop, x, n, rnd);
+    }
+    if mpfr_inf_p(s) != 0 {
+        // Only the first term remains
+        if mpfr_sgn(s) > 0 && mpfr_cmpabs(x, &integer(1).value) <= 0 {
+            return mpfr_set(rop, x, rnd);
+        }
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    if mpfr_cmp_ui(x, 1) > 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(x) != 0 {
+        // Li_s(-e^μ) ~ -μ^s / Γ(s + 1) for μ → ∞
+        if mpfr_sgn(s) > 0 {
+            return set_inf(rop, -1);
+        }
+        // -1 / Γ(s + 1) is positive when the floor of s is even
+        let mut t = working(mpfr_get_prec(s) as i64);
+        mpfr_floor(&mut t.value, s);
+        mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        mpfr_set_zero(rop, if mpfr_integer_p(&t.value) != 0 { 1 } else { -1 });
+        return 0;
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        return if mpfr_cmp_ui(s, 1) > 0 { mpfr_zeta(rop, s, rnd) } else { set_inf(rop, 1) };
+    }
+    if mpfr_cmp_si(x, -1) == 0 {
+        let t = dirichlet_eta(rop, s, flip(rnd));
+        mpfr_neg(rop, rop, MPFR_RNDN);
+        return -t;
+    }
+    round_with(rop, rnd, |wp| {
+        if mpfr_cmp_si(x, -2) < 0 {
+            polylog_jonquiere(x, s, wp)
+        } else {
+            polylog_series(x, s, None, wp).approx()
+        }
+    })
+}
+
+/// The polylogarithm `Li_n(x)` of an integer order `n`. For `n <= 0` it is a rational function
+/// defined for all `x != 1`, otherwise it is real for `x <= 1`.
+pub unsafe fn polylog_si(rop: mpfr_ptr, x: mpfr_srcptr, n: c_long, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if n <= 0 {
+        return polylog_negative(rop, x, negated(n), rnd);
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    if mpfr_cmp_ui(x, 1) > 0 {
+        return set_nan(rop);
+    }
+    if mpfr_cmp_ui(x, 1) == 0 {
+        return if n > 1 { mpfr_zeta_ui(rop, n as c_ulong, rnd) } else { set_inf(rop, 1) };
+    }
+    if n == 1 {
+        // Li_1(x) = -log(1 - x)
+        let mut t = working(mpfr_get_prec(x) as i64);
+        mpfr_neg(&mut t.value, x, MPFR_RNDN);
+        let r = mpfr_log1p(rop, &t.value, flip(rnd));
+        mpfr_neg(rop, rop, MPFR_RNDN);
+        return -r;
+    }
+    if n == 2 {
+        return mpfr_li2(rop, x, rnd);
+    }
+    if mpfr_inf_p(x) != 0 {
+        return set_inf(rop, -1);
+    }
+    let s = integer(n);
+    if mpfr_cmp_si(x, -1) == 0 {
+        let t = dirichlet_eta(rop, &s.value, flip(rnd));
+        mpfr_neg(rop, rop, MPFR_RNDN);
+        return -t;
+    }
+    round_with(rop, rnd, |wp| {
+        if mpfr_cmp_si(x, -2) < 0 {
+            polylog_inversion(x, n as c_ulong, wp)
+        } else {
+            polylog_series(x, &s.value, Some(n), wp).approx()
+        }
+    })
+}
+
+/// `Li_-n(x) = x A_n(x) / (1 - x)^(n+1)` where `A_n` is the Eulerian polynomial, computed
+/// exactly and rounded once.
+unsafe fn polylog_negative(rop: mpfr_ptr, x: mpfr_srcptr, n: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_cmp_ui(x, 1) == 0 {
+        return set_inf(rop, 1);
+    }
+    if mpfr_inf_p(x) != 0 {
+        // The limit is -1 for n = 0, otherwise zero with the sign of
+        // x^n / (-x)^(n+1) = (-1)^(n+1) / x
+        if n == 0 {
+            return mpfr_set_si(rop, -1, rnd);
+        }
+        let negative = (n % 2 == 0) == (mpfr_sgn(x) > 0);
+        mpfr_set_zero(rop, if negative { -1 } else { 1 });
+        return 0;
+    }
+    if n > EXACT_ORDER_MAX {
+        return polylog_rational(rop, x, n, rnd);
+    }
+
+    // The Eulerian numbers A(n, k) = (k + 1) A(n - 1, k) + (n - k) A(n - 1, k - 1) are not
+    // greater than n!
+    let pa = (n as i64 + 1) * (log2_ceil(n as u64 + 1) + 1) + 64;
+    let mut coeffs = vec![working(pa)];
+    mpfr_set_ui(&mut coeffs[0].value, 1, MPFR_RNDN);
+    let mut t = working(pa);
+    for i in 2..n + 1 {
+        let mut next: Vec<BigFloat> = (0..i).map(|_| working(pa)).collect();
+        for k in 0..i {
+            mpfr_set_ui(&mut next[k as usize].value, 0, MPFR_RNDN);
+            if k < i - 1 {
+                mpfr_mul_ui(&mut t.value, &coeffs[k as usize].value, k + 1, MPFR_RNDN);
+                mpfr_add(&mut next[k as usize].value, &next[k as usize].value, &t.value, MPFR_RNDN);
+            }
+            if k > 0 {
+                mpfr_mul_ui(&mut t.value, &coeffs[k as usize - 1].value, i - k, MPFR_RNDN);
+                mpfr_add(&mut next[k as usize].value, &next[k as usize].value, &t.value, MPFR_RNDN);
+            }
+        }
+        coeffs = next;
+    }
+
+    // x A_n(x) with Horner's rule
+    let mut num = integer(0);
+    for c in coeffs.iter().rev() {
+        num = exact_add(&exact_mul(&num.value, x).value, &c.value);
+    }
+    num = exact_mul(&num.value, x);
+
+    let d = exact_sub(&integer(1).value, x);
+    let mut den = working(mpfr_get_prec(&d.value) as i64 * (n as i64 + 1));
+    mpfr_pow_ui(&mut den.value, &d.value, n + 1, MPFR_RNDN);
+    mpfr_div(rop, &num.value, &den.value, rnd)
+}
+
+/// `Li_-n(x)` for large `n` and finite `x != 1`, from the series. With `x = X / 2^e`, the
+/// value times `(2^e - X)^(n+1)` is an integer, see `polylog_negative`.
+unsafe fn polylog_rational(rop: mpfr_ptr, x: mpfr_srcptr, n: c_ulong, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    let s = minus(n);
+    if mpfr_cmp_si(x, -1) == 0 {
+        let t = dirichlet_eta(rop, &s.value, flip(rnd));
+        mpfr_neg(rop, rop, MPFR_RNDN);
+        return -t;
+    }
+
+    let mut d = exact_sub(&integer(1).value, x);
+    mpfr_mul_2si(&mut d.value, &d.value, fraction_bits(x) as c_long, MPFR_RNDN);
+    mpfr_abs(&mut d.value, &d.value, MPFR_RNDN);
+    let den_exp = if mpfr_cmp_ui(&d.value, 1) == 0 {
+        1
+    } else {
+        bits(n).saturating_add(1).saturating_mul(exp_of(&d))
+    };
+    let den = || {
+        let mut r = working(den_exp + 1);
+        mpfr_pow_ui(&mut r.value, &d.value, n + 1, MPFR_RNDN);
+        r
+    };
+    round_rational(rop, rnd, den_exp, den, |wp| {
+        if mpfr_cmp_ui(x, 1) > 0 {
+            // Li_-n(x) = (-1)^(n+1) Li_-n(1/x); the relative error of 1/x is amplified at most
+            // x Li_-n'(x) / Li_-n(x) = 1 + x A_n'(x) / A_n(x) + (n + 1) x / (1 - x) <=
+            // n + (n + 1) / (x - 1) times, as A_n has nonnegative coefficients and degree n - 1
+            let mut y = working(wp);
+            mpfr_ui_div(&mut y.value, 1, x, MPFR_RNDN);
+            let mut sum = polylog_series(&y.value, &s.value, None, wp);
+            let c = exact_sub(x, &integer(1).value);
+            let amplified = log2_ceil(n as u64 + 1) + cmp::max(1 - exp_of(&c), 0) + 1;
+            let err = exp_of(&sum.value) + amplified - wp + 1;
+            sum.add_error(err);
+            if n % 2 == 0 {
+                mpfr_neg(&mut sum.value.value, &sum.value.value, MPFR_RNDN);
+            }
+            sum.approx()
+        } else if mpfr_cmp_si(x, -2) < 0 {
+            polylog_jonquiere(x, &s.value, wp)
+        } else {
+            polylog_series(x, &s.value, None, wp).approx()
+        }
+    })
+}
+
+/// Returns `e` such that `|t_k| < 2^e` for all `k >= K`, where `t_k = ζ(s - k) μ^k / k!`
+/// (or the same with `η(s - k)` if `eta` is set) and `K >= max(s + 1, 2|s|) + 1`.
+///
+/// By the functional equation, `|ζ(1 - z)| <= 2 Γ(z) ζ(z) / (2π)^z < 4 Γ(z) / (2π)^z` for
+/// `z >= 2`, and `|η(1 - z)| <= 2^z |ζ(1 - z)| < 4 Γ(z) / π^z`. These bounds of `|t_k|`
+/// decrease at least three times each for `|μ| <= ln 2`, so `e` is the bound of the first one
+/// with two more bits.
+unsafe fn log_series_rest(s: mpfr_srcptr, k: c_ulong, mu: &BigFloat, eta: bool) -> i64 {
+    let (mut r, mut t) = (working(64), working(64));
+    // ln Γ(k + 1 - s) - ln k!
+    mpfr_ui_sub(&mut r.value, k + 1, s, MPFR_RNDN);
+    mpfr_lngamma(&mut r.value, &r.value, MPFR_RNDN);
+    mpfr_set_ui(&mut t.value, k + 1, MPFR_RNDN);
+    mpfr_lngamma(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_sub(&mut r.value, &r.value, &t.value, MPFR_RNDN);
+    // k ln|μ|
+    mpfr_abs(&mut t.value, &mu.value, MPFR_RNDN);
+    mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &t.value, k, MPFR_RNDN);
+    mpfr_add(&mut r.value, &r.value, &t.value, MPFR_RNDN);
+    // -(k + 1 - s) ln q
+    mpfr_const_pi(&mut t.value, MPFR_RNDN);
+    if !eta {
+        mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+    }
+    mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul_ui(&mut t.value, &t.value, k + 1, MPFR_RNDN);
+    mpfr_sub(&mut r.value, &r.value, &t.value, MPFR_RNDN);
+    mpfr_const_pi(&mut t.value, MPFR_RNDN);
+    if !eta {
+        mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+    }
+    mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, s, MPFR_RNDN);
+    mpfr_add(&mut r.value, &r.value, &t.value, MPFR_RNDN);
+
+    // The factor 4 and the rest of the series, with a margin for the rounding errors
+    (mpfr_get_d(&r.value, MPFR_RNDU) / ::std::f64::consts::LN_2).ceil() as i64 + 6
+}
+
+/// Sums the series for `Li_s(x)` which suits `x`, with `-2 <= x < 1`, `x != 0, -1`:
+///
+/// - `Σ_{k>=1} x^k / k^s` for `|x| <= 1/2`;
+/// - `Γ(1 - s) (-μ)^(s-1) + Σ_{k>=0} ζ(s - k) μ^k / k!` for `1/2 < x < 1` and `μ = ln x`,
+///   where for integer `s = n` the term with `k = n - 1` and the first one are replaced with
+///   `μ^(n-1) / (n-1)! (H_{n-1} - ln(-μ))`;
+/// - `-Σ_{k>=0} η(s - k) μ^k / k!` for `x < -1/2` and `μ = ln(-x)`.
+unsafe fn polylog_series(x: mpfr_srcptr, s: mpfr_srcptr, n: Option<c_long>, wp: i64) -> Sum {
+    if mpfr_cmp_ui_2exp(x, 1, -1) <= 0 && mpfr_cmp_si_2exp(x, -1, -1) >= 0 {
+        return polylog_direct(x, s, wp);
+    }
+
+    let eta = mpfr_sgn(x) < 0;
+    let abs_s = abs_f64(s);
+    let k0 = (mpfr_get_d(s, MPFR_RNDU) + 1.0).max(2.0 * abs_s).ceil() as c_ulong + 1;
+    let mut sum = Sum::new(wp);
+    let mut mu = working(wp);
+    if eta {
+        mpfr_neg(&mut mu.value, x, MPFR_RNDN);
+        mpfr_log(&mut mu.value, &mu.value, MPFR_RNDN);
+    } else {
+        mpfr_log(&mut mu.value, x, MPFR_RNDN);
+    }
+    let one = integer(1);
+    let mut t = working(wp);
+    let mut u = working(wp);
+
+    if !eta && n.is_none() {
+        // Γ(1 - s) (-μ)^(s-1); the relative error of μ is amplified |s - 1| times
+        mpfr_gamma(&mut t.value, &exact_sub(&one.value, s).value, MPFR_RNDN);
+        mpfr_neg(&mut u.value, &mu.value, MPFR_RNDN);
+        mpfr_pow(&mut u.value, &u.value, &exact_sub(s, &one.value).value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+        sum.add(&t, abs_s.ceil() as u64 + 5);
+    }
+
+    // q = μ^k / k! has the relative error of 3k roundings
+    let mut q = working(wp);
+    mpfr_set_ui(&mut q.value, 1, MPFR_RNDN);
+    let mut k: c_ulong = 0;
+    loop {
+        let kr = 3 * k as u64;
+        if n.map_or(false, |n| !eta && k == n as c_ulong - 1) {
+            // H_{n-1} has the relative error of n roundings, ln(-μ) of 6 ones since
+            // |ln(-μ)| > 1/4
+            let n = n.unwrap() as c_ulong;
+            mpfr_set_ui(&mut u.value, 0, MPFR_RNDN);
+            for i in 1..n {
+                mpfr_set_ui(&mut t.value, 1, MPFR_RNDN);
+                mpfr_div_ui(&mut t.value, &t.value, i, MPFR_RNDN);
+                mpfr_add(&mut u.value, &u.value, &t.value, MPFR_RNDN);
+            }
+            mpfr_mul(&mut t.value, &u.value, &q.value, MPFR_RNDN);
+            sum.add(&t, kr + n as u64 + 2);
+            mpfr_neg(&mut u.value, &mu.value, MPFR_RNDN);
+            mpfr_log(&mut u.value, &u.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &u.value, &q.value, MPFR_RNDN);
+            sum.sub(&t, kr + 8);
+        } else if eta {
+            let roundings = if n.map_or(false, |n| k == n as c_ulong - 1) {
+                // η(1) = ln 2
+                mpfr_const_log2(&mut t.value, MPFR_RNDN);
+                1
+            } else {
+                // η(s - k) = -expm1((1 - s + k) ln 2) ζ(s - k); the relative error of the
+                // argument of expm1 is amplified at most 1 + (1 - s + k) ln 2 times
+                let z = exact_sub(s, &integer(k as c_long).value);
+                mpfr_zeta(&mut t.value, &z.value, MPFR_RNDN);
+                mpfr_const_log2(&mut u.value, MPFR_RNDN);
+                mpfr_mul(&mut u.value, &u.value, &exact_sub(&one.value, &z.value).value,
+                         MPFR_RNDN);
+                mpfr_expm1(&mut u.value, &u.value, MPFR_RNDN);
+                mpfr_neg(&mut u.value, &u.value, MPFR_RNDN);
+                mpfr_mul(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+                2 * (k as u64 + abs_s.ceil() as u64) + 7
+            };
+            mpfr_mul(&mut t.value, &t.value, &q.value, MPFR_RNDN);
+            sum.sub(&t, kr + roundings + 1);
+        } else {
+            mpfr_zeta(&mut t.value, &exact_sub(s, &integer(k as c_long).value).value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &q.value, MPFR_RNDN);
+            sum.add(&t, kr + 2);
+        }
+
+        k += 1;
+        mpfr_mul(&mut q.value, &q.value, &mu.value, MPFR_RNDN);
+        mpfr_div_ui(&mut q.value, &q.value, k, MPFR_RNDN);
+        if k >= k0 {
+            let rest = log_series_rest(s, k, &mu, eta);
+            if rest < exp_of(&sum.value) - wp - 4 || k >= k0 + 4 * wp as c_ulong {
+                sum.add_error(rest);
+                break;
+            }
+        }
+    }
+    sum
+}
+
+/// `Li_s(x) = Σ_{k>=1} x^k / k^s` for `|x| <= 1/2`. After `k >= 3|s|` the terms decrease at
+/// least by 3/4 each, so the rest is below four times the first omitted term.
+unsafe fn polylog_direct(x: mpfr_srcptr, s: mpfr_srcptr, wp: i64) -> Sum {
+    let abs_s = abs_f64(s);
+    let mut sum = Sum::new(wp);
+    // p = x^k has the relative error of 2k roundings
+    let mut p = working(wp);
+    mpfr_set(&mut p.value, x, MPFR_RNDN);
+    let (mut t, mut i) = (working(wp), working(64));
+    let mut k: c_ulong = 1;
+    loop {
+        mpfr_set_ui(&mut i.value, k, MPFR_RNDN);
+        mpfr_pow(&mut t.value, &i.value, s, MPFR_RNDN);
+        mpfr_div(&mut t.value, &p.value, &t.value, MPFR_RNDN);
+        if k as f64 >= 3.0 * abs_s + 1.0 &&
+           (exp_of(&t) < exp_of(&sum.value) - wp - 4 || k as i64 > 4 * wp + 3 * abs_s as i64) {
+            sum.add_error(exp_of(&t) + 2);
+            break;
+        }
+        sum.add(&t, 2 * k as u64 + 2);
+        k += 1;
+        mpfr_mul(&mut p.value, &p.value, x, MPFR_RNDN);
+    }
+    sum
+}
+
+/// The inversion formula for `x = -z < -2` and integer `n >= 3`:
+///
+/// `Li_n(-z) = -(-1)^n Li_n(-1/z) - L^n / n! - 2 Σ_{k=1..n/2} η(2k) L^(n-2k) / (n-2k)!`
+///
+/// where `L = ln z`.
+unsafe fn polylog_inversion(x: mpfr_srcptr, n: c_ulong, wp: i64) -> Approx {
+    let mut sum = Sum::new(wp);
+    let mut t = working(wp);
+
+    // The error of 1/x changes the result by less than twice as much
+    let mut y = working(wp);
+    mpfr_ui_div(&mut y.value, 1, x, MPFR_RNDN);
+    let inner = polylog_direct(&y.value, &integer(n as c_long).value, wp);
+    sum.add_error(inner.err());
+    sum.add_error(exp_of(&y) + 1 - wp);
+    if n % 2 == 0 {
+        sum.sub(&inner.value, 0);
+    } else {
+        sum.add(&inner.value, 0);
+    }
+
+    // L has the relative error of 2 roundings since L > 1/2, and p = L^j / j! of 4j ones
+    let mut l = working(wp);
+    mpfr_neg(&mut l.value, x, MPFR_RNDN);
+    mpfr_log(&mut l.value, &l.value, MPFR_RNDN);
+    let mut p = working(wp);
+    let mut z = working(wp);
+    mpfr_set_ui(&mut p.value, 1, MPFR_RNDN);
+    for j in 0..n + 1 {
+        if j == n {
+            sum.sub(&p, 4 * j as u64);
+        } else if (n - j) % 2 == 0 {
+            // η(m) = (1 - 2^(1-m)) ζ(m)
+            let m = n - j;
+            mpfr_zeta_ui(&mut z.value, m, MPFR_RNDN);
+            mpfr_set_ui_2exp(&mut t.value, 1, 1 - m as mpfr_exp_t, MPFR_RNDN);
+            mpfr_ui_sub(&mut t.value, 1, &t.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &z.value, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &t.value, &p.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+            sum.sub(&t, 4 * j as u64 + 4);
+        }
+        mpfr_mul(&mut p.value, &p.value, &l.value, MPFR_RNDN);
+        mpfr_div_ui(&mut p.value, &p.value, j + 1, MPFR_RNDN);
+    }
+    sum.approx()
+}
+
+/// Jonquière's inversion formula for `x = -e^μ < -2` and non-integer `s`:
+///
+/// `Li_s(-e^μ) = 2 Γ(σ) / (2π)^σ Re(i^σ ζ(σ, 1/2 - ic))`, where `σ = 1 - s` and `c = μ / 2π`.
+///
+/// The Hurwitz zeta function of the complex argument is summed with the Euler-Maclaurin
+/// formula as in `hurwitz_series`. With `ρ_k = |1/2 + k - ic|` and `ψ_k = π/2 + atan(c /
+/// (k + 1/2))`, the real parts of the terms of `i^σ ζ(σ, 1/2 - ic)` are
+///
+/// `Σ_{k<N} ρ_k^-σ cos(σψ_k) - ρ_N^s sin(sψ_N) / s + ρ_N^-σ cos(σψ_N) / 2
+///  + Σ_{j=1..M} 2 ζ(2j) c_j ρ_N^s sin((σ + 2j - 1)ψ_N)`
+///
+/// where `c_j = (σ)_{2j-1} / (2π ρ_N)^2j`. Since `|1/2 + t - ic| >= t + 1/2`, the rest is
+/// below `4 |(σ)_{2M-1}| / (2π)^2M (N + 1/2)^(s-2M)`, which is `4 |c_M| ρ_N^s` times
+/// `(ρ_N / (N + 1/2))^(2M-s)`; `N >= c √M` keeps the latter factor small.
+///
+/// The rounding errors of the terms are bounded relative to their moduli rather than to
+/// their values, so their sum is accumulated separately.
+unsafe fn polylog_jonquiere(x: mpfr_srcptr, s: mpfr_srcptr, wp: i64) -> Approx {
+    let one = integer(1);
+    let sigma = exact_sub(&one.value, s);
+    let abs_sigma = abs_f64(&sigma.value);
+    let abs_s = abs_f64(s);
+    let m = cmp::max(wp / 5 + 5, (abs_s / 2.0).ceil() as i64 + 1);
+
+    let mut pi = working(wp);
+    mpfr_const_pi(&mut pi.value, MPFR_RNDN);
+    // c = ln(-x) / 2π has the relative error of 4 roundings
+    let mut c = working(wp);
+    let mut t = working(wp);
+    mpfr_neg(&mut c.value, x, MPFR_RNDN);
+    mpfr_log(&mut c.value, &c.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut t.value, &pi.value, 1, MPFR_RNDN);
+    mpfr_div(&mut c.value, &c.value, &t.value, MPFR_RNDN);
+    let c_up = mpfr_get_d(&c.value, MPFR_RNDU);
+    let n = (abs_sigma + 2.0 * m as f64 + 1.0).max(c_up * (m as f64).sqrt()).ceil() as c_ulong;
+
+    let mut sum = Sum::new(wp);
+    // An upper bound of the sum of the moduli of the terms times their rounding errors
+    let mut errors = working(64);
+    mpfr_set_ui(&mut errors.value, 0, MPFR_RNDN);
+    let mut e = working(64);
+    let mut minus_half_sigma = working(mpfr_get_prec(&sigma.value) as i64);
+    mpfr_div_2ui(&mut minus_half_sigma.value, &sigma.value, 1, MPFR_RNDN);
+    mpfr_neg(&mut minus_half_sigma.value, &minus_half_sigma.value, MPFR_RNDN);
+
+    // ρ_k^-σ has the relative error of 6|σ| + 1 roundings and σψ_k the absolute error of
+    // 18|σ| ones, so the term is within 24|σ| + 3 roundings of its modulus
+    let q = (48.0 * abs_sigma).ceil() as c_ulong + 6;
+    let (mut r2, mut psi, mut p) = (working(wp), working(wp), working(wp));
+    for k in 0..n + 1 {
+        jonquiere_point(k, &c, &pi, &mut r2, &mut psi);
+        mpfr_pow(&mut p.value, &r2.value, &minus_half_sigma.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &sigma.value, &psi.value, MPFR_RNDN);
+        mpfr_cos(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &p.value, MPFR_RNDN);
+        if k == n {
+            mpfr_div_2ui(&mut t.value, &t.value, 1, MPFR_RNDN);
+        }
+        sum.add(&t, 0);
+        mpfr_mul_ui(&mut e.value, &p.value, q, MPFR_RNDU);
+        mpfr_add(&mut errors.value, &errors.value, &e.value, MPFR_RNDU);
+    }
+
+    // ρ_N^s = ρ_N^-σ ρ_N has the relative error of 6|σ| + 9 roundings and sψ_N the absolute
+    // error of 18|s| ones, which with the rest give 6|σ| + 18|s| + 12 roundings
+    let mut rho_s = working(wp);
+    mpfr_sqrt(&mut rho_s.value, &r2.value, MPFR_RNDN);
+    mpfr_mul(&mut rho_s.value, &rho_s.value, &p.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, s, &psi.value, MPFR_RNDN);
+    mpfr_sin(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &rho_s.value, MPFR_RNDN);
+    mpfr_div(&mut t.value, &t.value, s, MPFR_RNDN);
+    sum.sub(&t, 0);
+    let q = (12.0 * abs_sigma + 36.0 * abs_s).ceil() as c_ulong + 24;
+    mpfr_mul_ui(&mut e.value, &rho_s.value, q, MPFR_RNDU);
+    mpfr_div(&mut e.value, &e.value, s, MPFR_RNDA);
+    mpfr_abs(&mut e.value, &e.value, MPFR_RNDN);
+    mpfr_add(&mut errors.value, &errors.value, &e.value, MPFR_RNDU);
+
+    // (2π ρ_N)² has the relative error of 16 roundings, so c_j has the one of 21j ones
+    let mut w = working(wp);
+    mpfr_mul_2ui(&mut w.value, &pi.value, 1, MPFR_RNDN);
+    mpfr_sqr(&mut w.value, &w.value, MPFR_RNDN);
+    mpfr_mul(&mut w.value, &w.value, &r2.value, MPFR_RNDN);
+    let mut cj = working(wp);
+    mpfr_div(&mut cj.value, &sigma.value, &w.value, MPFR_RNDN);
+    let (mut z, mut u) = (working(wp), working(wp));
+    for j in 1..(m as c_ulong + 1) {
+        if j > 1 {
+            // c_j = c_{j-1} (σ + 2j - 3) (σ + 2j - 2) / (2π ρ_N)²
+            mpfr_add_ui(&mut u.value, &sigma.value, 2 * j - 3, MPFR_RNDN);
+            mpfr_mul(&mut cj.value, &cj.value, &u.value, MPFR_RNDN);
+            mpfr_add_ui(&mut u.value, &sigma.value, 2 * j - 2, MPFR_RNDN);
+            mpfr_mul(&mut cj.value, &cj.value, &u.value, MPFR_RNDN);
+            mpfr_div(&mut cj.value, &cj.value, &w.value, MPFR_RNDN);
+        }
+        mpfr_zeta_ui(&mut z.value, 2 * j, MPFR_RNDN);
+        mpfr_mul(&mut z.value, &z.value, &cj.value, MPFR_RNDN);
+        mpfr_mul(&mut z.value, &z.value, &rho_s.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut z.value, &z.value, 1, MPFR_RNDN);
+        mpfr_add_ui(&mut u.value, &sigma.value, 2 * j - 1, MPFR_RNDN);
+        mpfr_mul(&mut u.value, &u.value, &psi.value, MPFR_RNDN);
+        mpfr_sin(&mut u.value, &u.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &z.value, &u.value, MPFR_RNDN);
+        sum.add(&t, 0);
+        // The relative error of the modulus and the absolute one of (σ + 2j - 1)ψ_N give
+        // 62j + 27|σ| + 14 roundings
+        let q = (54.0 * abs_sigma).ceil() as c_ulong + 124 * j + 28;
+        mpfr_mul_ui(&mut e.value, &z.value, q, MPFR_RNDU);
+        mpfr_abs(&mut e.value, &e.value, MPFR_RNDN);
+        mpfr_add(&mut errors.value, &errors.value, &e.value, MPFR_RNDU);
+    }
+    // log2 (ρ_N / (N + 1/2))^(2M-s)
+    let d = (m as f64 - mpfr_get_d(s, MPFR_RNDD) / 2.0) *
+            (1.0 + (c_up / (n as f64 + 0.5)).powi(2)).log2();
+    sum.add_error(exp_of(&cj) + exp_of(&rho_s) + d.ceil() as i64 + 4);
+    sum.add_error(exp_of(&errors) - wp);
+
+    // 2 Γ(σ) (2π)^-σ has the relative error of |σ| + 4 roundings, the product one more
+    let mut f = working(wp);
+    mpfr_gamma(&mut f.value, &sigma.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut t.value, &pi.value, 1, MPFR_RNDN);
+    mpfr_neg(&mut u.value, &sigma.value, MPFR_RNDN);
+    mpfr_pow(&mut t.value, &t.value, &u.value, MPFR_RNDN);
+    mpfr_mul(&mut f.value, &f.value, &t.value, MPFR_RNDN);
+    mpfr_mul_2ui(&mut f.value, &f.value, 1, MPFR_RNDN);
+    let es = sum.err();
+    let mut r = sum.value;
+    mpfr_mul(&mut r.value, &r.value, &f.value, MPFR_RNDN);
+    let q = 2 * abs_sigma.ceil() as u64 + 8;
+    let err = cmp::max(es + exp_of(&f), exp_of(&r) + log2_ceil(q) - wp) + 1;
+    Approx::Within(r, err)
+}
+
+/// Sets `r2 = ρ_k²` and `psi = ψ_k` for `polylog_jonquiere`, with the relative error of
+/// 12 roundings and the absolute error of 11 ones respectively.
+unsafe fn jonquiere_point(k: c_ulong, c: &BigFloat, pi: &BigFloat, r2: &mut BigFloat,
+                          psi: &mut BigFloat) {
+    let mut u = working(66);
+    mpfr_set_ui_2exp(&mut u.value, 2 * k + 1, -1, MPFR_RNDN);
+    let mut t = working(mpfr_get_prec(&r2.value) as i64);
+    mpfr_sqr(&mut r2.value, &u.value, MPFR_RNDN);
+    mpfr_sqr(&mut t.value, &c.value, MPFR_RNDN);
+    mpfr_add(&mut r2.value, &r2.value, &t.value, MPFR_RNDN);
+    mpfr_div(&mut t.value, &c.value, &u.value, MPFR_RNDN);
+    mpfr_atan(&mut psi.value, &t.value, MPFR_RNDN);
+    mpfr_div_2ui(&mut t.value, &pi.value, 1, MPFR_RNDN);
+    mpfr_add(&mut psi.value, &psi.value, &t.value, MPFR_RNDN);
+}
+
+/// The Clausen function `Cl_2(x) = Σ_{k>=1} sin(kx) / k^2`.
+///
+/// The argument is reduced to `θ` in `[-π, π]`, and then
+/// `Cl_2(θ) = θ - θ ln|θ| + Σ_{k>=1} ζ(2k) / (k (2k + 1)) (θ / 2π)^2k θ`. The terms decrease
+/// at least four times each, so the rest is below the first omitted `(θ / 2π)^2k θ`.
+pub unsafe fn clausen(rop: mpfr_ptr, x: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(x) != 0 || mpfr_inf_p(x) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_zero_p(x) != 0 {
+        return mpfr_set(rop, x, rnd);
+    }
+    round_with(rop, rnd, |wp| {
+        let mut sum = Sum::new(wp);
+        let mut th;
+        if mpfr_cmpabs(x, &integer(3).value) <= 0 {
+            th = working(mpfr_get_prec(x) as i64);
+            mpfr_set(&mut th.value, x, MPFR_RNDN);
+        } else {
+            // θ = x - 2πm is within 2^(EXP(x) + 3 - wq), and |Cl_2'(θ)| = |ln|2 sin(θ/2)||
+            // is less than |ln|θ|| + 1
+            let wq = wp + mpfr_get_exp(x) as i64 + 8;
+            th = working(wq);
+            let mut m = working(wq);
+            mpfr_const_pi(&mut m.value, MPFR_RNDN);
+            mpfr_mul_2ui(&mut m.value, &m.value, 1, MPFR_RNDN);
+            mpfr_div(&mut th.value, x, &m.value, MPFR_RNDN);
+            mpfr_rint(&mut th.value, &th.value, MPFR_RNDN);
+            mpfr_mul(&mut th.value, &th.value, &m.value, MPFR_RNDN);
+            mpfr_sub(&mut th.value, x, &th.value, MPFR_RNDN);
+            if mpfr_zero_p(&th.value) != 0 {
+                // Not exact, so it leads to another iteration
+                return Approx::Within(th, 0);
+            }
+            let mut l = working(16);
+            mpfr_abs(&mut l.value, &th.value, MPFR_RNDN);
+            mpfr_log(&mut l.value, &l.value, MPFR_RNDN);
+            let dl = cmp::max(exp_of(&l), 1) + 1;
+            sum.add_error(mpfr_get_exp(x) as i64 + 3 - wq + dl);
+        }
+
+        let mut t = working(wp);
+        mpfr_set(&mut t.value, &th.value, MPFR_RNDN);
+        sum.add(&t, 1);
+        mpfr_abs(&mut t.value, &th.value, MPFR_RNDN);
+        mpfr_log(&mut t.value, &t.value, MPFR_RNDN);
+        mpfr_mul(&mut t.value, &t.value, &th.value, MPFR_RNDN);
+        sum.sub(&t, 2);
+
+        // r = (θ / 2π)² has the relative error of 5 roundings, p = r^k θ of 6k + 1 ones
+        let mut r = working(wp);
+        mpfr_const_pi(&mut r.value, MPFR_RNDN);
+        mpfr_mul_2ui(&mut r.value, &r.value, 1, MPFR_RNDN);
+        mpfr_div(&mut r.value, &th.value, &r.value, MPFR_RNDN);
+        mpfr_sqr(&mut r.value, &r.value, MPFR_RNDN);
+        let mut p = working(wp);
+        mpfr_set(&mut p.value, &th.value, MPFR_RNDN);
+        let mut z = working(wp);
+        let mut k: c_ulong = 1;
+        loop {
+            mpfr_mul(&mut p.value, &p.value, &r.value, MPFR_RNDN);
+            mpfr_zeta_ui(&mut z.value, 2 * k, MPFR_RNDN);
+            mpfr_mul(&mut t.value, &p.value, &z.value, MPFR_RNDN);
+            mpfr_div_ui(&mut t.value, &t.value, k * (2 * k + 1), MPFR_RNDN);
+            sum.add(&t, 6 * k as u64 + 4);
+            let rest = exp_of(&p) + exp_of(&r);
+            if rest < exp_of(&sum.value) - wp - 4 || k as i64 >= wp {
+                sum.add_error(rest);
+                break;
+            }
+            k += 1;
+        }
+        sum.approx()
+    })
+}
+
+/// The Dirichlet eta function `η(s) = (1 - 2^(1-s)) ζ(s)`, the alternating zeta function.
+pub unsafe fn dirichlet_eta(rop: mpfr_ptr, s: mpfr_srcptr, rnd: mpfr_rnd_t) -> c_int {
+    if mpfr_nan_p(s) != 0 {
+        return set_nan(rop);
+    }
+    if mpfr_inf_p(s) != 0 {
+        return if mpfr_sgn(s) > 0 { mpfr_set_ui(rop, 1, rnd) } else { set_nan(rop) };
+    }
+    if mpfr_cmp_ui(s, 1) == 0 {
+        return mpfr_const_log2(rop, rnd);
+    }
+    match small_integer(s) {
+        Some(0) => mpfr_set_ui_2exp(rop, 1, -1, rnd),
+        Some(n) if n < 0 && negated(n) <= EXACT_ORDER_MAX => {
+            // η(-n) = -E_n(0) / 2
+            let mut e = euler_at_zero(negated(n));
+            mpfr_neg(&mut e.value, &e.value, MPFR_RNDN);
+            mpfr_div_2ui(rop, &e.value, 1, rnd)
+        }
+        Some(n) if n < 0 => {
+            let n = negated(n);
+            if n % 2 == 0 {
+                mpfr_set_zero(rop, 1);
+                return 0;
+            }
+            // -E_n(0) / 2 has a denominator dividing 2^(n+1)
+            let den_exp = bits(n).saturating_add(2);
+            let den = || {
+                let mut d = working(2);
+                mpfr_set_ui_2exp(&mut d.value, 1, (n + 1) as mpfr_exp_t, MPFR_RNDN);
+                d
+            };
+            round_rational(rop, rnd, den_exp, den, |wp| eta_approx(s, wp))
+        }
+        _ => round_with(rop, rnd, |wp| eta_approx(s, wp))
+    }
+}
+
+/// Approximates `η(s) = -expm1((1 - s) ln 2) ζ(s)`. The relative error of the argument of
+/// expm1 (2 roundings) is amplified at most `1 + |1 - s| ln 2` times.
+unsafe fn eta_approx(s: mpfr_srcptr, wp: i64) -> Approx {
+    let mut t = working(wp);
+    let mut z = working(wp);
+    mpfr_const_log2(&mut t.value, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &exact_sub(&integer(1).value, s).value, MPFR_RNDN);
+    let a = abs_f64(&t.value).ceil() as u64;
+    mpfr_expm1(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_neg(&mut t.value, &t.value, MPFR_RNDN);
+    mpfr_zeta(&mut z.value, s, MPFR_RNDN);
+    mpfr_mul(&mut t.value, &t.value, &z.value, MPFR_RNDN);
+    let err = exp_of(&t) + log2_ceil(2 * a + 6) - wp + 1;
+    Approx::Within(t, err)
+}
+
+/// Computes the value of the Euler polynomial `E_n(0)` exactly from the recurrence
+/// `E_n(0) = -(1/2) Σ_{k<n} C(n, k) E_k(0)`. These are dyadic numbers below `n!` in
+/// magnitude with denominators not greater than `2^n`.
+unsafe fn euler_at_zero(n: c_ulong) -> BigFloat {
+    let p = (n as i64 + 1) * (log2_ceil(n as u64 + 1) + 3) + 64;
+    let mut values: Vec<BigFloat> = Vec::with_capacity(n as usize + 1);
+    let mut e0 = working(p);
+    mpfr_set_ui(&mut e0.value, 1, MPFR_RNDN);
+    values.push(e0);
+    let (mut c, mut t) = (working(p), working(p));
+    for i in 1..n + 1 {
+        let mut e = working(p);
+        mpfr_set_ui(&mut e.value, 0, MPFR_RNDN);
+        mpfr_set_ui(&mut c.value, 1, MPFR_RNDN);
+        for k in 0..i {
+            mpfr_mul(&mut t.value, &c.value, &values[k as usize].value, MPFR_RNDN);
+            mpfr_add(&mut e.value, &e.value, &t.value, MPFR_RNDN);
+            // C(i, k + 1) = C(i, k) (i - k) / (k + 1)
+            mpfr_mul_ui(&mut c.value, &c.value, i - k, MPFR_RNDN);
+            mpfr_div_ui(&mut c.value, &c.value, k + 1, MPFR_RNDN);
+        }
+        mpfr_div_2ui(&mut e.value, &e.value, 1, MPFR_RNDN);
+        mpfr_neg(&mut e.value, &e.value, MPFR_RNDN);
+        values.push(e);
+    }
+    values.pop().unwrap()
+}
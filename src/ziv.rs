@@ -0,0 +1,394 @@
+// Correct rounding of the functions which are not provided by MPFR, using Ziv's strategy:
+// the value is approximated at some working precision together with a bound of the error,
+// and the working precision is increased until the approximation determines the correctly
+// rounded result.
+//
+// Implementations must recognize the arguments for which the result is exactly representable
+// (and return `Approx::Exact` for them). Otherwise the working precision grows up to a limit,
+// and a result which is still not determined at the limit is NaN with the erange flag set
+// rather than a value which may be rounded incorrectly.
+
+use std::cmp::{self, Ordering};
+
+use libc::{c_int, c_long};
+
+use mpfr_sys::*;
+
+use BigFloat;
+use precision::ToPrecision;
+
+/// An approximation of the value of a function.
+pub enum Approx {
+    /// The value itself.
+    Exact(BigFloat),
+    /// A value which differs from the exact one by less than `2^err`.
+    Within(BigFloat, i64),
+    /// A positive value below half of the smallest positive number.
+    Underflow,
+    /// A number representable in the target precision which is greater than the exact value
+    /// by less than a quarter of the distance to the next smaller such number.
+    Below(BigFloat)
+}
+
+/// Creates a new number with the given working precision.
+#[inline]
+pub fn working(wp: i64) -> BigFloat {
+    BigFloat::fresh_with_prec(wp.bits())
+}
+
+/// Returns the exponent of `x` as used in error bounds, which is `i64::MIN / 2` for zero
+/// (so it never contributes to a maximum) and the exponent itself for regular numbers.
+#[inline]
+pub unsafe fn exp_of(x: &BigFloat) -> i64 {
+    if mpfr_regular_p(&x.value) != 0 {
+        mpfr_get_exp(&x.value) as i64
+    } else {
+        ::std::i64::MIN / 2
+    }
+}
+
+/// Returns the number of bits which is enough to bound an error of `n` roundings.
+#[inline]
+pub fn log2_ceil(n: u64) -> i64 {
+    (64 - n.saturating_sub(1).leading_zeros()) as i64
+}
+
+/// Returns the precision which is enough to hold `x + y` (or `x - y`) exactly.
+pub unsafe fn exact_add_prec(x: mpfr_srcptr, y: mpfr_srcptr) -> mpfr_prec_t {
+    let (px, py) = (mpfr_get_prec(x), mpfr_get_prec(y));
+    let p = cmp::max(px, py);
+    if mpfr_regular_p(x) != 0 && mpfr_regular_p(y) != 0 {
+        let (ex, ey) = (mpfr_get_exp(x), mpfr_get_exp(y));
+        let ulp = cmp::min(ex - px, ey - py);
+        cmp::max(p, cmp::max(ex, ey) + 1 - ulp)
+    } else {
+        p
+    }
+}
+
+/// Computes `x + y` exactly.
+pub unsafe fn exact_add(x: mpfr_srcptr, y: mpfr_srcptr) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(exact_add_prec(x, y).bits());
+    mpfr_add(&mut r.value, x, y, MPFR_RNDN);
+    r
+}
+
+/// Computes `x - y` exactly.
+pub unsafe fn exact_sub(x: mpfr_srcptr, y: mpfr_srcptr) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec(exact_add_prec(x, y).bits());
+    mpfr_sub(&mut r.value, x, y, MPFR_RNDN);
+    r
+}
+
+/// Computes `x * y` exactly.
+pub unsafe fn exact_mul(x: mpfr_srcptr, y: mpfr_srcptr) -> BigFloat {
+    let mut r = BigFloat::fresh_with_prec((mpfr_get_prec(x) + mpfr_get_prec(y)).bits());
+    mpfr_mul(&mut r.value, x, y, MPFR_RNDN);
+    r
+}
+
+/// Creates a small integer, exactly.
+pub unsafe fn integer(n: c_long) -> BigFloat {
+    let mut r = working(64);
+    mpfr_set_si(&mut r.value, n, MPFR_RNDN);
+    r
+}
+
+/// Copies `x` with its precision.
+pub unsafe fn copy(x: mpfr_srcptr) -> BigFloat {
+    let mut r = working(mpfr_get_prec(x) as i64);
+    mpfr_set(&mut r.value, x, MPFR_RNDN);
+    r
+}
+
+/// Sets `rop` to NaN and returns the ternary value of it.
+pub unsafe fn set_nan(rop: mpfr_ptr) -> c_int {
+    mpfr_set_nan(rop);
+    0
+}
+
+/// Returns the rounding mode which rounds `-x` as the given one rounds `x`.
+#[inline]
+pub fn flip(rnd: mpfr_rnd_t) -> mpfr_rnd_t {
+    match rnd {
+        MPFR_RNDU => MPFR_RNDD,
+        MPFR_RNDD => MPFR_RNDU,
+        _ => rnd
+    }
+}
+
+/// Returns an approximation which is never accurate enough, so that the working precision
+/// is increased.
+pub fn retry(wp: i64) -> Approx {
+    let mut r = working(wp);
+    unsafe {
+        mpfr_set_ui(&mut r.value, 1, MPFR_RNDN);
+    }
+    Approx::Within(r, 1)
+}
+
+/// Calls `f` with the widest exponent range, so that the intermediate results neither
+/// overflow nor underflow, and restores the current range afterwards.
+pub unsafe fn with_extended_range<F, T>(f: F) -> T where F: FnOnce() -> T {
+    let (emin, emax) = (mpfr_get_emin(), mpfr_get_emax());
+    mpfr_set_emin(mpfr_get_emin_min());
+    mpfr_set_emax(mpfr_get_emax_max());
+    let r = f();
+    mpfr_set_emin(emin);
+    mpfr_set_emax(emax);
+    r
+}
+
+/// Stores the result of `f`, computed in the widest exponent range, into `rop` and rounds it
+/// to the current range.
+pub unsafe fn extended<F>(rop: mpfr_ptr, rnd: mpfr_rnd_t, f: F) -> c_int
+    where F: FnOnce() -> c_int
+{
+    let t = with_extended_range(f);
+    mpfr_check_range(rop, t, rnd)
+}
+
+/// Returns the largest working precision used for the target precision `prec`.
+#[inline]
+fn max_working_prec(prec: i64) -> i64 {
+    cmp::max(16 * prec, 1 << 16)
+}
+
+/// Stores the correctly rounded value approximated by `f` into `rop` and returns
+/// the ternary value.
+///
+/// `f` is called with increasing working precisions, which always exceed the precision of
+/// `rop` by at least 16 bits. NaN and infinite approximations are stored as they are, zero
+/// approximations which are not exact always lead to another iteration.
+///
+/// If the approximation at the largest working precision still does not determine the
+/// result, `rop` is set to NaN and the erange flag is raised.
+pub unsafe fn round_with<F>(rop: mpfr_ptr, rnd: mpfr_rnd_t, mut f: F) -> c_int
+    where F: FnMut(i64) -> Approx
+{
+    let prec = mpfr_get_prec(rop) as i64;
+    // One more bit is needed to find out the direction of rounding to nearest
+    let target = prec + if rnd == MPFR_RNDN { 1 } else { 0 };
+    let max_wp = max_working_prec(prec);
+    let mut wp = prec + 2 * log2_ceil(prec as u64) + 16;
+
+    loop {
+        match f(wp) {
+            Approx::Exact(v) => return mpfr_set(rop, &v.value, rnd),
+            Approx::Underflow => return mpfr_set_ui_2exp(rop, 1, mpfr_get_emin() - 3, rnd),
+            Approx::Below(v) => {
+                mpfr_set(rop, &v.value, MPFR_RNDN);
+                let down = rnd == MPFR_RNDD || rnd == MPFR_RNDZ && mpfr_sgn(rop) > 0 ||
+                           rnd == MPFR_RNDA && mpfr_sgn(rop) < 0;
+                return if down {
+                    mpfr_nextbelow(rop);
+                    -1
+                } else {
+                    1
+                };
+            }
+            Approx::Within(v, err) => {
+                if mpfr_nan_p(&v.value) != 0 || mpfr_inf_p(&v.value) != 0 {
+                    return mpfr_set(rop, &v.value, rnd);
+                }
+                if mpfr_zero_p(&v.value) == 0 {
+                    let correct = mpfr_get_exp(&v.value) as i64 - err;
+                    if correct > target &&
+                       mpfr_can_round(&v.value, correct as mpfr_exp_t, MPFR_RNDN, MPFR_RNDZ,
+                                      target as mpfr_prec_t) != 0 {
+                        return mpfr_set(rop, &v.value, rnd);
+                    }
+                }
+                if wp >= max_wp {
+                    mpfr_set_nan(rop);
+                    mpfr_set_erangeflag();
+                    return 0;
+                }
+            }
+        }
+        wp = cmp::min(wp + cmp::max(wp / 2, 32), max_wp);
+    }
+}
+
+/// A sum of approximate terms together with a bound of its error.
+pub struct Sum {
+    pub value: BigFloat,
+    wp: i64,
+    // The rounding errors are below `roundings * 2^(max_exp - wp)`
+    max_exp: i64,
+    roundings: u64,
+    // The other errors are below `2^other`
+    other: i64
+}
+
+impl Sum {
+    pub fn new(wp: i64) -> Sum {
+        let mut value = working(wp);
+        unsafe {
+            mpfr_set_ui(&mut value.value, 0, MPFR_RNDN);
+        }
+        Sum {
+            value: value,
+            wp: wp,
+            max_exp: ::std::i64::MIN / 2,
+            roundings: 0,
+            other: ::std::i64::MIN / 2
+        }
+    }
+
+    /// Adds a term whose relative error is at most `roundings * 2^-wp`.
+    pub unsafe fn add(&mut self, t: &BigFloat, roundings: u64) {
+        mpfr_add(&mut self.value.value, &self.value.value, &t.value, MPFR_RNDN);
+        self.max_exp = cmp::max(self.max_exp, cmp::max(exp_of(t), exp_of(&self.value)));
+        self.roundings = self.roundings.saturating_add(roundings + 1);
+    }
+
+    /// Subtracts a term whose relative error is at most `roundings * 2^-wp`.
+    pub unsafe fn sub(&mut self, t: &BigFloat, roundings: u64) {
+        mpfr_sub(&mut self.value.value, &self.value.value, &t.value, MPFR_RNDN);
+        self.max_exp = cmp::max(self.max_exp, cmp::max(exp_of(t), exp_of(&self.value)));
+        self.roundings = self.roundings.saturating_add(roundings + 1);
+    }
+
+    /// Accounts for an additional error below `2^err`, e.g. the rest of a series.
+    pub fn add_error(&mut self, err: i64) {
+        self.other = cmp::max(self.other, err) + 1;
+    }
+
+    /// Returns `e` such that the sum differs from the exact one by less than `2^e`.
+    pub fn err(&self) -> i64 {
+        let rounding = self.max_exp + log2_ceil(self.roundings) - self.wp;
+        cmp::max(rounding, self.other) + 1
+    }
+
+    pub fn approx(self) -> Approx {
+        let err = self.err();
+        Approx::Within(self.value, err)
+    }
+}
+
+/// The exponential of a sum of logarithms.
+pub unsafe fn exp_approx(l: Sum, wp: i64) -> Approx {
+    let e = l.err();
+    // An absolute error below 1/8 of the logarithm is a relative one below 2^(e + 1)
+    if e >= -2 {
+        return retry(wp);
+    }
+    let mut r = l.value;
+    mpfr_exp(&mut r.value, &r.value, MPFR_RNDN);
+    if mpfr_zero_p(&r.value) != 0 {
+        return Approx::Underflow;
+    }
+    let err = exp_of(&r) + cmp::max(e + 1, -wp) + 1;
+    Approx::Within(r, err)
+}
+
+/// Splits an approximation into the value and the exponent of the bound of the error, or
+/// returns it as it is if it is not a plain one.
+pub fn split(a: Approx) -> Result<(BigFloat, i64), Approx> {
+    match a {
+        Approx::Exact(v) => Ok((v, ::std::i64::MIN / 2)),
+        Approx::Within(v, err) => Ok((v, err)),
+        a => Err(a)
+    }
+}
+
+/// The product of two approximations given with the exponents of the bounds of their errors.
+pub unsafe fn mul_approx(a: &BigFloat, ea: i64, b: &BigFloat, eb: i64, wp: i64) -> (BigFloat, i64) {
+    let mut r = working(wp);
+    mpfr_mul(&mut r.value, &a.value, &b.value, MPFR_RNDN);
+    let err = cmp::max(cmp::max(exp_of(a) + eb, exp_of(b) + ea), cmp::max(ea + eb, exp_of(&r) - wp));
+    (r, err + 2)
+}
+
+// The inverse functions find the root of `f(w) = x` with Newton's iteration at the working
+// precision, and then enclose it in an interval whose ends give certain signs of `f(a) - x`,
+// which bounds the error without analyzing the convergence of the iteration
+
+/// Runs Newton's iteration starting from `w`, where `step` returns the correction
+/// `f(w) / f'(w)` for the current `w` (or `None` if it cannot be computed), keeping `w`
+/// strictly inside `(lo, hi)`. Returns the last correction.
+pub unsafe fn newton<F>(w: &mut BigFloat, wp: i64, lo: Option<&BigFloat>, hi: Option<&BigFloat>,
+                        mut step: F) -> Option<BigFloat>
+    where F: FnMut(&BigFloat) -> Option<BigFloat>
+{
+    let mut t = working(wp);
+    let mut iterations = 2 * log2_ceil(wp as u64) + 40;
+    loop {
+        let d = match step(w) {
+            Some(d) => d,
+            None => return None
+        };
+        mpfr_sub(&mut t.value, &w.value, &d.value, MPFR_RNDN);
+        let bound = match (lo, hi) {
+            (Some(b), _) if mpfr_cmp(&t.value, &b.value) <= 0 => Some(b),
+            (_, Some(b)) if mpfr_cmp(&t.value, &b.value) >= 0 => Some(b),
+            _ => None
+        };
+        let converged = match bound {
+            // Go halfway to the bound instead of leaving the domain
+            Some(b) => {
+                mpfr_add(&mut w.value, &w.value, &b.value, MPFR_RNDN);
+                mpfr_div_2ui(&mut w.value, &w.value, 1, MPFR_RNDN);
+                false
+            }
+            None => {
+                mpfr_set(&mut w.value, &t.value, MPFR_RNDN);
+                mpfr_zero_p(&d.value) != 0 || exp_of(&d) < exp_of(w) - wp
+            }
+        };
+        iterations -= 1;
+        if converged || iterations == 0 {
+            return Some(d);
+        }
+    }
+}
+
+/// Looks for an interval `[w - e, w + e]` containing the root of a monotonic function,
+/// starting from `e` about twice the last Newton correction `d`, where `sign` returns
+/// the sign of `f(a) - x` if it is certain. Returns the exponent of the bound of the error
+/// of `w`.
+pub unsafe fn enclose<S>(w: &BigFloat, d: &BigFloat, wp: i64, mut sign: S) -> Option<i64>
+    where S: FnMut(&BigFloat) -> Option<Ordering>
+{
+    if mpfr_regular_p(&w.value) == 0 || mpfr_number_p(&d.value) == 0 {
+        return None;
+    }
+    let mut e = working(wp);
+    let min_e = exp_of(w) - wp + 2;
+    mpfr_mul_2ui(&mut e.value, &d.value, 1, MPFR_RNDN);
+    mpfr_abs(&mut e.value, &e.value, MPFR_RNDN);
+    if exp_of(&e) < min_e {
+        mpfr_set_ui_2exp(&mut e.value, 1, min_e as mpfr_exp_t, MPFR_RNDN);
+    }
+    let mut a = working(wp);
+    for _ in 0..wp {
+        mpfr_sub(&mut a.value, &w.value, &e.value, MPFR_RNDD);
+        let sign_lo = sign(&a);
+        mpfr_add(&mut a.value, &w.value, &e.value, MPFR_RNDU);
+        let sign_hi = sign(&a);
+        match (sign_lo, sign_hi) {
+            (Some(l), Some(h)) if l != h => {
+                // Both w and the root are in the interval of length 2e
+                return Some(exp_of(&e) + 2);
+            }
+            _ => {
+                mpfr_mul_2ui(&mut e.value, &e.value, 1, MPFR_RNDN);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the sign of `a - b` if it is certain, given the exponents of the bounds of
+/// the errors of both.
+pub unsafe fn certain_sign(a: &BigFloat, ea: i64, b: mpfr_srcptr, eb: i64, wp: i64)
+                           -> Option<Ordering> {
+    let mut d = working(wp);
+    mpfr_sub(&mut d.value, &a.value, b, MPFR_RNDN);
+    if mpfr_zero_p(&d.value) == 0 && exp_of(&d) - 1 > cmp::max(ea, eb) + 2 {
+        Some(if mpfr_sgn(&d.value) > 0 { Ordering::Greater } else { Ordering::Less })
+    } else {
+        None
+    }
+}
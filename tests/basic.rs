@@ -1,9 +1,49 @@
 extern crate mpfr;
 
-use mpfr::BigFloat;
+use std::cmp::Ordering;
+
+use mpfr::{BigFloat, RoundingMode};
 use mpfr::traits::*;
 use mpfr::format::{flags, FormatOptions, Format};
 
+/// A number of the default precision.
+fn f(x: f64) -> BigFloat {
+    BigFloat::new().from(x)
+}
+
+/// Checks that `a` is within the relative error `1e-15` of `b`.
+fn close(a: BigFloat, b: f64) -> bool {
+    (a - f(b)).abs() <= f(b.abs() * 1e-15)
+}
+
+/// A number of 200 bits.
+fn f200(x: f64) -> BigFloat {
+    BigFloat::new().with_prec(200.bits()).from(x)
+}
+
+/// Checks that `x` is negative, telling -0 from +0 by the sign of `1 / x`.
+fn is_negative(x: &BigFloat) -> bool {
+    f(1.0) / x < f(0.0)
+}
+
+/// Checks the results of `op` at 200 bits in the directed rounding modes and to nearest,
+/// together with their ternary values, against `exact`, an irrational value given with more
+/// digits than needed.
+fn check_rounding<F>(op: F, exact: &str) where F: Fn(RoundingMode) -> (BigFloat, Ordering) {
+    let value = BigFloat::new().with_prec(400.bits()).from(exact);
+    let (down, t) = op(RoundingMode::Downwards);
+    let (up, u) = op(RoundingMode::Upwards);
+    let (near, v) = op(RoundingMode::ToNearest);
+    assert_eq!(200, down.prec().bits());
+    assert!(down < value && value < up);
+    assert_eq!((Ordering::Less, Ordering::Greater), (t, u));
+    let mut next = down.clone();
+    next.nextabove();
+    assert_eq!(next, up);
+    assert_eq!(near, BigFloat::new().with_prec(200.bits()).from(exact));
+    assert_eq!(if near == down { Ordering::Less } else { Ordering::Greater }, v);
+}
+
 #[test]
 fn test_basic_conversions() {
     let f = BigFloat::new().from::<f64>(12345.67);
@@ -79,3 +119,931 @@ fn test_division() {
 }
 
 
+#[test]
+fn test_compound_assignment() {
+    let mut x = BigFloat::new().from(12345f64);
+    x += BigFloat::new().from(54322u16);
+    x -= &BigFloat::new().from(2i32);
+    x *= 2u32;
+    x /= -5i32;
+    x += 0.5f64;
+    let zz = BigFloat::new().from(-26665.5f64);
+    assert_eq!(x, zz);
+
+    let mut x = BigFloat::new().from(10u32);
+    x %= BigFloat::new().from(3u32);
+    assert_eq!(x, BigFloat::new().from(1u32));
+}
+
+#[test]
+fn test_compound_assignment_keeps_precision() {
+    let mut x = BigFloat::new().with_prec(16.bits()).from(1u32);
+    let y = BigFloat::new().with_prec(128.bits()).from(3u32);
+    x /= &y;
+    assert_eq!(16, x.prec().bits());
+}
+
+#[test]
+fn test_primitive_lhs() {
+    let x = BigFloat::new().from(4u32);
+
+    assert_eq!(3u32 * &x, BigFloat::new().from(12u32));
+    assert_eq!(0.5f64 + &x, BigFloat::new().from(4.5f64));
+    assert_eq!(2.0f64 - &x, BigFloat::new().from(-2i32));
+    assert_eq!(-6i32 - &x, BigFloat::new().from(-10i32));
+    assert_eq!(1u32 / &x, BigFloat::new().from(0.25f64));
+    assert_eq!(-2i32 / x.clone(), BigFloat::new().from(-0.5f64));
+    // integer types have an inherent `pow`, so the trait has to be named explicitly
+    assert_eq!(Pow::pow(2u32, &x), BigFloat::new().from(16u32));
+    assert_eq!(0.5f64.pow(&x), BigFloat::new().from(0.0625f64));
+    assert_eq!(Pow::pow(-3i32, x), BigFloat::new().from(81u32));
+}
+
+#[test]
+fn test_all_primitive_widths() {
+    let x = BigFloat::new().from(10u32);
+
+    assert_eq!(&x + 5i8, BigFloat::new().from(15u32));
+    assert_eq!(&x - 5u16, BigFloat::new().from(5u32));
+    assert_eq!(&x * 3usize, BigFloat::new().from(30u32));
+    assert_eq!(&x / 4isize, BigFloat::new().from(2.5f64));
+    assert_eq!(&x + 0.5f32, BigFloat::new().from(10.5f64));
+    assert_eq!(2i64 * &x, BigFloat::new().from(20u32));
+    assert_eq!(x.clone().pow(2u8), BigFloat::new().from(100u32));
+    assert_eq!(&x % 3u64, BigFloat::new().from(1u32));
+}
+
+#[test]
+fn test_wide_integers_are_exact() {
+    let big = u64::max_value();
+    let x = BigFloat::new().with_prec(128.bits()).from(big);
+    assert_eq!(x.clone() - big, BigFloat::new().from(0u32));
+
+    let big = i128::min_value();
+    let x = BigFloat::new().with_prec(128.bits()).from(big);
+    let mut y = BigFloat::new().with_prec(128.bits()).from(1u32);
+    y *= 1u64 << 63;
+    y *= 1u64 << 63;
+    y *= 2u32;
+    assert_eq!(x, -y);
+
+    let z = BigFloat::new().with_prec(128.bits()).from(0u32) + u128::max_value();
+    assert_eq!(z - u128::max_value(), BigFloat::new().from(0u32));
+}
+
+#[test]
+fn test_explicit_rounding() {
+    let one = BigFloat::new().with_prec(16.bits()).from(1u32);
+    let three = BigFloat::new().from(3u32);
+
+    let (down, o) = one.div_round(&three, RoundingMode::Downwards);
+    assert_eq!(Ordering::Less, o);
+    let (up, o) = one.div_round(&three, RoundingMode::Upwards);
+    assert_eq!(Ordering::Greater, o);
+    assert!(down < up);
+    assert_eq!(16, up.prec().bits());
+
+    let (two, o) = one.add_round(&one, RoundingMode::Downwards);
+    assert_eq!(Ordering::Equal, o);
+    assert_eq!(two, BigFloat::new().from(2u32));
+
+    let (r, o) = BigFloat::new().from(4u32).sqrt_round(RoundingMode::Upwards);
+    assert_eq!(Ordering::Equal, o);
+    assert_eq!(r, BigFloat::new().from(2u32));
+
+    let (_, o) = (&three).log_round(RoundingMode::Upwards);
+    assert_eq!(Ordering::Greater, o);
+
+    let mut pi = BigFloat::new().fresh();
+    assert_eq!(Ordering::Less, pi.set_to_const_pi_round(RoundingMode::TowardsZero));
+}
+
+#[test]
+fn test_assign_operations() {
+    let a = BigFloat::new().with_prec(64.bits()).from(1u32);
+    let b = BigFloat::new().with_prec(64.bits()).from(3u32);
+
+    let mut dst = BigFloat::new().with_prec(16.bits()).fresh();
+    dst.assign_div(&a, &b);
+    assert_eq!(16, dst.prec().bits());
+
+    let mut expected = &a / &b;
+    expected.set_prec_round(16.bits());
+    assert_eq!(dst, expected);
+
+    dst.assign_pow(&b, &b);
+    assert_eq!(dst, BigFloat::new().from(27u32));
+
+    dst.assign_sqrt(&BigFloat::new().from(16u32));
+    assert_eq!(dst, BigFloat::new().from(4u32));
+
+    dst.assign_root(&BigFloat::new().from(27u32), 3);
+    assert_eq!(dst, b);
+    assert_eq!(16, dst.prec().bits());
+}
+
+#[test]
+fn test_fused_multiply_add() {
+    // x = 1 + 2^-15, so x * x = 1 + 2^-14 + 2^-30 does not fit into 16 bits
+    let x = BigFloat::new().with_prec(16.bits()).from(1.000030517578125f64);
+    let y = BigFloat::new().with_prec(16.bits()).from(1.00006103515625f64);
+    let one = BigFloat::new().with_prec(16.bits()).from(1u32);
+    let tiny = BigFloat::new().from(1.0f64 / 1073741824.0);
+
+    assert_eq!(&x * &x - &y, BigFloat::new().from(0u32));
+    assert_eq!((&x).fma(&x, &(-&y)), tiny);
+    assert_eq!((&x).fms(&x, &y), tiny);
+    assert_eq!((&x).fmms(&x, &y, &one), tiny);
+    assert_eq!(x.clone().fmma(&x, &(-&y), &one), tiny);
+
+    let mut dst = BigFloat::new().with_prec(16.bits()).fresh();
+    dst.assign_fms(&x, &x, &y);
+    assert_eq!(dst, tiny);
+
+    // 2^(emax - 1) for the default maximal exponent 2^30 - 1, so that both products overflow
+    // while the results do not
+    let big = &one << 1073741822;
+    let three = BigFloat::new().from(3u32);
+    let four = BigFloat::new().from(4u32);
+    assert_eq!((&big).fmma(&four, &(-&big), &four), BigFloat::new().from(0u32));
+    assert_eq!((&big).fmms(&four, &big, &three), big);
+}
+
+#[test]
+fn test_sum_and_product() {
+    let terms = vec![
+        BigFloat::new().with_prec(53.bits()).from(1e30f64),
+        BigFloat::new().with_prec(53.bits()).from(1u32),
+        BigFloat::new().with_prec(53.bits()).from(-1e30f64)
+    ];
+
+    let naive = terms.iter().fold(BigFloat::new().with_prec(53.bits()).from(0u32), |a, t| a + t);
+    assert_eq!(naive, BigFloat::new().from(0u32));
+
+    let s: BigFloat = terms.iter().sum();
+    assert_eq!(s, BigFloat::new().from(1u32));
+    assert_eq!(53, s.prec().bits());
+
+    let refs: Vec<&BigFloat> = terms.iter().collect();
+    let s = BigFloat::sum(&refs, 16.bits(), RoundingMode::ToNearest);
+    assert_eq!(s, BigFloat::new().from(1u32));
+    assert_eq!(16, s.prec().bits());
+
+    let s: BigFloat = terms.into_iter().sum();
+    assert_eq!(s, BigFloat::new().from(1u32));
+
+    let factors = vec![BigFloat::new().from(2u32), BigFloat::new().from(3u32), BigFloat::new().from(4u32)];
+    let p: BigFloat = factors.iter().product();
+    assert_eq!(p, BigFloat::new().from(24u32));
+    let p: BigFloat = factors.into_iter().product();
+    assert_eq!(p, BigFloat::new().from(24u32));
+
+    // (1 + 2^-52)^2 * 10 = 10 + 5 * 2^-50 + 10 * 2^-104 is nearest to 10 + 3 * 2^-49, while
+    // rounding the first product to 1 + 2^-51 leads to 10 + 2^-48
+    let e = BigFloat::new().with_prec(53.bits()).from(1.0f64 + ::std::f64::EPSILON);
+    let factors = vec![e.clone(), e, BigFloat::new().with_prec(53.bits()).from(10u32)];
+    let p: BigFloat = factors.iter().product();
+    assert_eq!(p, BigFloat::new().from(10.0f64 + 3.0 / 562949953421312.0));
+    assert_eq!(53, p.prec().bits());
+}
+
+#[test]
+fn test_dot_product() {
+    let a = vec![
+        BigFloat::new().from(1e20f64),
+        BigFloat::new().from(3u32),
+        BigFloat::new().from(-1e20f64)
+    ];
+    let b = vec![
+        BigFloat::new().from(1e20f64),
+        BigFloat::new().from(0.5f64),
+        BigFloat::new().from(1e20f64)
+    ];
+
+    let r = BigFloat::dot(&a, &b, 53.bits());
+    assert_eq!(r, BigFloat::new().from(1.5f64));
+    assert_eq!(53, r.prec().bits());
+
+    // Products beyond the default maximal exponent 2^30 - 1 which cancel each other
+    let big = BigFloat::new().from(1u32) << 1073741822;
+    let a = vec![big.clone(), BigFloat::new().from(3u32), -&big];
+    let four = BigFloat::new().from(4u32);
+    let b = vec![four.clone(), BigFloat::new().from(0.5f64), four];
+    assert_eq!(BigFloat::dot(&a, &b, 53.bits()), BigFloat::new().from(1.5f64));
+}
+
+#[test]
+fn test_shifts() {
+    use mpfr::Flags;
+
+    let x = BigFloat::new().from(3u32);
+    assert_eq!(&x << 4, BigFloat::new().from(48u32));
+    assert_eq!(x.mul_2exp(-1), BigFloat::new().from(1.5f64));
+    assert_eq!(x.div_2exp(2), BigFloat::new().from(0.75f64));
+
+    let mut y = x.clone();
+    y <<= 5;
+    y >>= 6;
+    assert_eq!(y, BigFloat::new().from(1.5f64));
+
+    Flags::Overflow.clear();
+    let z = &x << i32::max_value();
+    assert!(z.is_inf());
+    assert!(Flags::Overflow.is_set());
+
+    Flags::Underflow.clear();
+    let z = x >> i32::max_value();
+    assert!(z.is_zero());
+    assert!(Flags::Underflow.is_set());
+}
+
+#[test]
+fn test_remainder_family() {
+    let f = |x: i32| BigFloat::new().from(x);
+
+    assert_eq!(f(7).fmod(&f(2)), f(1));
+    assert_eq!(f(-7).fmod(&f(2)), f(-1));
+    assert_eq!(f(7).remainder(&f(2)), f(-1));
+    assert_eq!(f(7) % f(2), f(-1));
+    assert_eq!(f(7) % f(2), f(7).remainder(f(2)));
+
+    let (r, q) = f(7).remquo(&f(2));
+    assert_eq!(r, f(-1));
+    assert_eq!(q & 7, 4);
+
+    assert_eq!((&f(7)).div_euclid(f(2)), f(3));
+    assert_eq!((&f(-7)).div_euclid(&f(2)), f(-4));
+    assert_eq!(f(-7).div_euclid(f(-2)), f(4));
+    assert_eq!(f(7).rem_euclid(&f(2)), f(1));
+    assert_eq!(f(-7).rem_euclid(&f(2)), f(1));
+    assert_eq!(f(-7).rem_euclid(f(-2)), f(1));
+}
+
+#[test]
+fn test_div_euclid_large_quotient() {
+    // the quotient 2^61 + 1 does not fit into 8 bits, so it must be rounded only once
+    let x = BigFloat::new().with_prec(64.bits()).from((1u64 << 62) + 3);
+    let y = BigFloat::new().with_prec(8.bits()).from(2u32);
+    let q = RoundingMode::Upwards.use_in(|| (&x).div_euclid(y));
+    assert_eq!(8, q.prec().bits());
+    assert_eq!(q, BigFloat::new().from((1u64 << 61) + (1u64 << 54)));
+
+    // the quotient 2^60 + 129 is nearer to 2^60 + 256 in 53 bits, while the quotient
+    // rounded down to 55 bits is the midpoint 2^60 + 128
+    let x = BigFloat::new().with_prec(70.bits()).from(1u64 << 60) + 129.5f64;
+    assert_eq!((&x).div_euclid(f(1.0)), BigFloat::new().from((1u64 << 60) + 256));
+
+    // 2^1000000000 = 1 (mod 3), computed without expanding all the bits
+    let x = f(1.0).mul_2exp(1000000000);
+    assert_eq!((&x).rem_euclid(f(3.0)), f(1.0));
+    assert_eq!((-&x).rem_euclid(f(3.0)), f(2.0));
+    assert_eq!((&x).div_euclid(f(3.0)), &x / f(3.0));
+}
+
+#[test]
+fn test_integer_rounding() {
+    let x = f(-2.5);
+
+    assert_eq!((&x).floor(), f(-3.0));
+    assert_eq!((&x).ceil(), f(-2.0));
+    assert_eq!((&x).round(), f(-3.0));
+    assert_eq!((&x).round_even(), f(-2.0));
+    assert_eq!(f(3.5).round_even(), f(4.0));
+    assert_eq!((&x).trunc(), f(-2.0));
+    assert_eq!(RoundingMode::Upwards.use_in(|| (&x).rint()), f(-2.0));
+    assert_eq!((&x).frac(), f(-0.5));
+    assert_eq!((&x).modf(), (f(-2.0), f(-0.5)));
+
+    // the ternary value of the rounding functions compares the result with the exact
+    // integer, not with the argument
+    assert_eq!(f(6.75).round_round(RoundingMode::Downwards), (f(7.0), Ordering::Equal));
+    assert_eq!(f(6.0).floor_round(RoundingMode::Upwards), (f(6.0), Ordering::Equal));
+
+    // the integer 7 does not fit into 2 bits
+    let mut z = BigFloat::new().with_prec(2.bits()).from(0u32);
+    z.assign_round_even(&f(6.75));
+    assert_eq!(z, f(8.0));
+    RoundingMode::Downwards.use_in(|| z.assign_round_even(&f(6.75)));
+    assert_eq!(z, f(6.0));
+
+    let mut w = f(-7.25);
+    w.floor_mut();
+    assert_eq!(w, f(-8.0));
+    w.assign_frac(&f(1.25));
+    assert_eq!(w, f(0.25));
+}
+
+#[test]
+fn test_min_max_and_signs() {
+    use mpfr::Sign;
+
+    let mut nan = f(0.0);
+    nan.set_to_nan();
+    let mut neg_zero = f(0.0);
+    neg_zero.set_to_zero(Sign::Negative);
+
+    assert_eq!(f(1.0).min(f(2.0)), f(1.0));
+    assert_eq!((&f(1.0)).max(&f(2.0)), f(2.0));
+    assert_eq!(f(1.0).min(&nan), f(1.0));
+    assert!((&nan).max(&nan).is_nan());
+    assert!(is_negative(&f(0.0).min(&neg_zero)));
+    assert!(!is_negative(&(&neg_zero).max(f(0.0))));
+
+    assert_eq!(f(5.0).dim(f(3.0)), f(2.0));
+    assert_eq!(f(3.0).dim(f(5.0)), f(0.0));
+    assert!(f(3.0).dim(&nan).is_nan());
+
+    assert_eq!(f(3.0).copysign(f(-1.0)), f(-3.0));
+    assert!(is_negative(&f(0.0).copysign(&neg_zero)));
+    assert_eq!(f(-3.0).setsign(false), f(3.0));
+    assert_eq!(f(3.0).setsign(true), f(-3.0));
+}
+
+#[test]
+fn test_next_representable() {
+    let f = |x: f64| BigFloat::new().with_prec(4.bits()).from(x);
+
+    let mut x = f(1.0);
+    x.nextabove();
+    assert_eq!(x, f(1.125));
+    x.nextbelow();
+    x.nextbelow();
+    assert_eq!(x, f(0.9375));
+    x.nexttoward(&f(10.0));
+    assert_eq!(x, f(1.0));
+    x.nexttoward(&f(1.0));
+    assert_eq!(x, f(1.0));
+
+    let mut z = f(0.0);
+    z.nextbelow();
+    assert!(z < f(0.0) && z.is_regular());
+}
+
+#[test]
+fn test_gamma_family() {
+    use mpfr::Sign;
+
+    assert_eq!(f(5.0).gamma(), f(24.0));
+    assert_eq!((&f(1.0)).lngamma(), f(0.0));
+    assert!(f(-0.5).lngamma().is_nan());
+
+    let (l, sign) = f(-0.5).lgamma();
+    assert!(match sign { Sign::Negative => true, _ => false });
+    assert!(l > f(1.2) && l < f(1.3));
+    let (l, sign) = f(2.5).lgamma();
+    assert!(match sign { Sign::Positive => true, _ => false });
+    assert_eq!(l, f(2.5).lngamma());
+
+    assert_eq!(f(1.0).digamma(), -BigFloat::new().const_euler());
+    assert_eq!(f(0.0).zeta(), f(-0.5));
+    assert_eq!(f(2.0).zeta(), BigFloat::new().zeta_ui(2));
+
+    assert_eq!(BigFloat::new().factorial(20), BigFloat::new().from(2432902008176640000u64));
+    let mut x = f(0.0);
+    x.set_to_factorial(3);
+    assert_eq!(x, f(6.0));
+
+    // orders beyond the exponent range or an unsigned long
+    assert!(BigFloat::new().factorial(1 << 40).is_inf());
+    assert_eq!(x.set_to_factorial_round(::std::u64::MAX, RoundingMode::Downwards),
+               Ordering::Less);
+    assert!(!x.is_inf() && x > f(1e300));
+    assert_eq!(x.set_to_zeta_ui_round(::std::u64::MAX, RoundingMode::Downwards),
+               Ordering::Less);
+    assert_eq!(x, f(1.0));
+    assert_eq!(x.set_to_zeta_ui_round(::std::u64::MAX, RoundingMode::Upwards),
+               Ordering::Greater);
+    assert_eq!(x, f(1.0 + 2.2204460492503131e-16));
+}
+
+#[test]
+fn test_beta_and_incomplete_gamma() {
+    let inf = f(1.0 / 0.0);
+
+    assert_eq!(f(2.0).beta(&f(3.0)), f(1.0) / f(12.0));
+    assert_eq!(f(-0.5).beta(&f(2.0)), f(-4.0));
+    assert_eq!((&f(0.5)).beta(&f(0.5)), BigFloat::new().const_pi());
+
+    // the Gamma values overflow while B does not
+    assert_eq!(f(0.5).beta(&f(1e9)), f(5.6049912170985526e-5));
+    assert_eq!(f(1e8).beta(&f(1e8)), BigFloat::new().from("2.6110109521030701e-60206003"));
+
+    // a negative value rounded in both directions
+    let x = BigFloat::new().with_prec(200.bits()).from(-0.25f64);
+    let (down, t) = (&x).beta_round(&f(0.5), RoundingMode::Downwards);
+    let (mut up, u) = (&x).beta_round(&f(0.5), RoundingMode::Upwards);
+    assert!(down < f(0.0) && t == Ordering::Less && u == Ordering::Greater);
+    up.nextbelow();
+    assert_eq!(up, down);
+
+    // the limits at the poles and the special values are those of MPFR 4
+    assert_eq!(f(-3.0).beta(&f(2.0)), f(1.0) / f(6.0));
+    assert_eq!(f(-2.0).beta(&f(2.0)), f(0.5));
+    assert!(f(-2.0).beta(&f(3.0)).is_nan() && f(-2.0).beta(&f(2.5)).is_nan());
+    let z = f(-2.5).beta(&f(0.5));
+    assert!(z.is_zero() && !is_negative(&z));
+    assert!(f(0.0).beta(&f(3.0)) == inf && f(-0.0).beta(&f(-2.5)) == -&inf);
+    assert!(f(0.0).beta(&f(-3.0)).is_nan() && f(0.0).beta(&inf).is_nan());
+    let z = (&inf).beta(&f(2.0));
+    assert!(z.is_zero() && !is_negative(&z));
+    assert!((&inf).beta(&f(-0.5)) == inf && (&inf).beta(&f(-1.5)) == -&inf);
+    let z = (-&inf).beta(&f(3.0));
+    assert!(z.is_zero() && is_negative(&z));
+    assert!((-&inf).beta(&f(2.5)).is_nan() && (&inf).beta(&-&inf).is_nan());
+
+    assert_eq!(f(1.0).gamma_inc(&f(2.0)), f(-2.0).exp());
+    assert_eq!(f(2.5).gamma_inc(&f(0.0)), f(2.5).gamma());
+    assert!(f(2.5).gamma_inc(&f(-1.0)).is_nan());
+
+    // large arguments, where the lower function cancels almost all of Gamma(a)
+    assert_eq!(f(0.5).gamma_inc(&f(4e4)), BigFloat::new().from("8.3116729424457140123e-17375"));
+    assert_eq!(f(7.0).gamma_inc(&f(5e4)), BigFloat::new().from("2.9496941090578001669e-21687"));
+    assert_eq!(f(1000.0).gamma_inc(&f(1e5)),
+               BigFloat::new().from("3.5989022354910544255e-38435"));
+    check_rounding(|rnd| f200(2.5).gamma_inc_round(&f(1e5), rnd),
+                   "1.12672048210667124051768274505066317069972877339672331954100731798544952521351483229002877e-43422");
+
+    let mut y = f(0.0);
+    y.assign_beta(&f(1.0), &f(4.0));
+    assert_eq!(y, f(0.25));
+}
+
+#[test]
+fn test_error_and_bessel_functions() {
+    assert_eq!(f(0.0).erf(), f(0.0));
+    assert_eq!((&f(0.0)).erfc(), f(1.0));
+    assert!((f(0.5).erf() + f(0.5).erfc() - f(1.0)).abs() < f(1e-15));
+    if cfg!(feature = "mpfr4") {
+        assert!((f(-1.0).eint() + f(0.21938393439552029)).abs() < f(1e-16));
+    } else {
+        assert!(f(-1.0).eint().is_nan());
+    }
+    assert_eq!(f(0.0).li2(), f(0.0));
+    assert!(f(0.0).ai() > f(0.35) && f(0.0).ai() < f(0.36));
+
+    assert_eq!(f(0.0).j0(), f(1.0));
+    assert_eq!(f(0.0).j1(), f(0.0));
+    assert_eq!(f(2.5).jn(1), f(2.5).j1());
+    assert_eq!(f(2.5).jn(-1), -f(2.5).j1());
+    assert_eq!(f(2.5).yn(0), f(2.5).y0());
+    assert_eq!((&f(2.5)).yn(1), f(2.5).y1());
+    assert!(f(0.0).y0().is_inf());
+
+    let mut x = f(0.0);
+    x.assign_jn(&f(0.0), 3);
+    assert_eq!(x, f(0.0));
+}
+
+#[test]
+fn test_two_argument_functions() {
+    let pi = BigFloat::new().const_pi();
+
+    assert_eq!(f(1.0).atan2(f(1.0)), &pi / 4u32);
+    assert_eq!((&f(0.0)).atan2(&f(-1.0)), pi);
+    assert_eq!(f(3.0).hypot(&f(4.0)), f(5.0));
+    assert_eq!((&f(3.0)).hypot(f(-4.0)), f(5.0));
+    assert_eq!(f(2.0).agm(f(2.0)), f(2.0));
+    assert!(f(-1.0).agm(f(1.0)).is_nan());
+
+    assert_eq!(f(8.0).log_base(f(2.0)), f(3.0));
+    assert_eq!(f(2.0).log_base(&f(4.0)), f(0.5));
+    assert_eq!((&f(1000.0)).log_base(f(10.0)), f(3.0));
+    assert_eq!((&f(0.125)).log_base(&f(2.0)), f(-3.0));
+    assert_eq!(f(1.0).log_base(f(5.0)), f(0.0));
+    assert_eq!(f(10.0).log_base(f(2.0)), f(10.0).log2());
+    assert!(f(-1.0).log_base(f(2.0)).is_nan());
+}
+
+#[test]
+fn test_sin_cos() {
+    let x = f(0.75);
+
+    assert_eq!((&x).sin_cos(), ((&x).sin(), (&x).cos()));
+    assert_eq!((&x).sinh_cosh(), ((&x).sinh(), (&x).cosh()));
+    assert_eq!(f(0.0).sin_cos(), (f(0.0), f(1.0)));
+
+    let (s, c) = x.sin_cos_with_prec(20.bits(), 100.bits());
+    assert_eq!(20, s.prec().bits());
+    assert_eq!(100, c.prec().bits());
+    assert_eq!(s, BigFloat::new().with_prec(20.bits()).from(0.75f64).sin());
+    assert_eq!(c, BigFloat::new().with_prec(100.bits()).from(0.75f64).cos());
+
+    let (mut sh, mut ch) = (f(0.0), f(0.0));
+    sh.assign_sinh_cosh(&mut ch, &x);
+    assert_eq!((sh, ch), ((&x).sinh(), (&x).cosh()));
+}
+
+#[test]
+fn test_trigonometry_in_pi_units() {
+    assert_eq!(f(0.5).sinpi(), f(1.0));
+    let z = f(1.0).sinpi();
+    assert!(z.is_zero() && !is_negative(&z));
+    let z = f(-1.0).sinpi();
+    assert!(z.is_zero() && is_negative(&z));
+    assert_eq!(f(1.0).cospi(), f(-1.0));
+    assert_eq!((&f(0.25)).sinpi(), f(0.5).sqrt());
+    let z = f(1.0).tanpi();
+    assert!(z.is_zero() && is_negative(&z));
+
+    // exact for huge arguments as well
+    let x = BigFloat::new().with_prec(100.bits()).from(1u64 << 60) + 0.5f64;
+    assert_eq!(x.sinpi(), f(1.0));
+    // 3 * 2^100000000 = 48 (mod 360), reduced without expanding all the bits
+    let x = f(3.0).mul_2exp(100000000);
+    assert_eq!((&x).sinu(360), f(48.0).sinu(360));
+    assert_eq!(x.tanu(360), f(48.0).tanu(360));
+
+    // degrees
+    assert_eq!(f(30.0).sinu(360), f(0.5));
+    assert_eq!(f(-150.0).sinu(360), f(-0.5));
+    assert_eq!(f(60.0).cosu(360), f(0.5));
+    let z = f(90.0).cosu(360);
+    assert!(z.is_zero() && !is_negative(&z));
+    assert_eq!(f(45.0).tanu(360), f(1.0));
+    assert_eq!(f(135.0).tanu(360), f(-1.0));
+    assert!(f(90.0).tanu(360).is_inf() && f(90.0).tanu(360) > f(0.0));
+    assert!(f(270.0).tanu(360) < f(0.0));
+    assert!((f(10.0).sinu(360) - f(0.17364817766693033)).abs() < f(1e-16));
+
+    assert_eq!(f(1.0).asinpi(), f(0.5));
+    assert_eq!(f(-1.0).acospi(), f(1.0));
+    assert_eq!(f(0.0).acospi(), f(0.5));
+    assert_eq!(f(-1.0).atanpi(), f(-0.25));
+    assert!(f(2.0).asinpi().is_nan());
+}
+
+#[test]
+fn test_inverse_reciprocal_functions() {
+    let precise = |x: f64| BigFloat::new().with_prec(300.bits()).from(x);
+    let rounded = |x: BigFloat| BigFloat::new().from(&x);
+
+    // compositions computed with a much higher precision, which round to the same values
+    // unless an argument is extremely unlucky
+    for &x in [1.0000001f64, 1.5, 3.0, 1e10, -2.5].iter() {
+        assert_eq!(f(x).acot(), rounded((precise(1.0) / precise(x)).atan()));
+        assert_eq!(f(x).asec(), rounded((precise(1.0) / precise(x)).acos()));
+        assert_eq!(f(x).acsc(), rounded((precise(1.0) / precise(x)).asin()));
+        assert_eq!(f(x).acoth(), rounded((precise(1.0) / precise(x)).atanh()));
+        assert_eq!(f(x).acsch(), rounded((precise(1.0) / precise(x)).asinh()));
+        let y = 1.0 / x.abs();
+        assert_eq!(f(y).asech(), rounded((precise(1.0) / precise(y)).acosh()));
+    }
+
+    assert_eq!(f(1.0).asec(), f(0.0));
+    assert_eq!(f(-1.0).asec(), BigFloat::new().const_pi());
+    assert_eq!((&f(1.0)).asech(), f(0.0));
+    assert!(f(0.5).asec().is_nan() && f(0.5).acoth().is_nan() && f(2.0).asech().is_nan());
+    assert!(f(-1.0).acoth().is_inf() && f(-1.0).acoth() < f(0.0));
+    let z = f(-1.0 / 0.0).acot();
+    assert!(z.is_zero() && is_negative(&z));
+    assert_eq!(f(0.0).acot(), BigFloat::new().const_pi() / 2u32);
+    assert_eq!(f(-0.0).acot(), -(BigFloat::new().const_pi() / 2u32));
+}
+
+#[test]
+fn test_constant_catalog() {
+    use mpfr::Flags;
+
+    let b = || BigFloat::new().with_prec(64.bits());
+    let consts = [
+        (b().const_e(), "2.718281828459045235360287471352662497757"),
+        (b().const_ln10(), "2.302585092994045684017991454684364207601"),
+        (b().const_sqrt2(), "1.414213562373095048801688724209698078570"),
+        (b().const_golden_ratio(), "1.618033988749894848204586834365638117720"),
+        (b().const_apery(), "1.202056903159594285399738161511449990765"),
+        (b().const_glaisher(), "1.282427129100622636875342568869791727768"),
+        (b().const_khinchin(), "2.685452001065306445309714835481795693820")
+    ];
+    for &(ref c, s) in consts.iter() {
+        assert_eq!(*c, b().from(s));
+    }
+
+    // Cached values are reused and survive clearing the cache
+    let mut x = b().fresh();
+    let first = x.set_to_const_glaisher_round(RoundingMode::Upwards);
+    let second = x.set_to_const_glaisher_round(RoundingMode::Upwards);
+    assert_eq!(first, Ordering::Greater);
+    assert_eq!(second, Ordering::Greater);
+    let mut y = b().fresh();
+    y.set_to_const_glaisher_round(RoundingMode::Downwards);
+    assert!(y < x);
+    BigFloat::free_cache();
+    y.set_to_const_glaisher_round(RoundingMode::Upwards);
+    assert_eq!(x, y);
+
+    // A cached value raises the inexact flag, and lower precisions are rounded from it
+    Flags::Inexact.clear();
+    assert_eq!(x.set_to_const_glaisher_round(RoundingMode::Upwards), Ordering::Greater);
+    assert!(Flags::Inexact.is_set());
+    let glaisher = "1.28242712910062263687534256886979172776768892732500119206374002174040";
+    let mut w = BigFloat::new().with_prec(200.bits()).fresh();
+    w.set_to_const_glaisher();
+    assert_eq!(w, BigFloat::new().with_prec(200.bits()).from(glaisher));
+    for p in 2..64 {
+        let mut w = BigFloat::new().with_prec(p.bits()).fresh();
+        w.set_to_const_glaisher();
+        assert_eq!(w, BigFloat::new().with_prec(p.bits()).from(glaisher));
+    }
+
+    let mut z = BigFloat::new().with_prec(8.bits()).fresh();
+    z.set_to_const_khinchin();
+    assert_eq!(8, z.prec().bits());
+    assert_eq!(z, BigFloat::new().with_prec(8.bits()).from(2.6875f64));
+}
+
+#[test]
+fn test_lambert_w_and_inverse_error_functions() {
+    assert!(close(f(1.0).lambert_w0(), 0.5671432904097838));
+    assert!(close(f(-0.1).lambert_w0(), -0.11183255915896297));
+    assert!(close(f(-0.1).lambert_wm1(), -3.577152063957297));
+    for &x in [1e-300, 0.5, 10.0, 1e300].iter() {
+        let w = f(x).lambert_w0();
+        assert!(close(&w * (&w).exp(), x));
+    }
+    let w = (&f(-0.3678)).lambert_wm1();
+    assert!(close(&w * (&w).exp(), -0.3678));
+    assert!(f(-0.5).lambert_w0().is_nan() && f(0.5).lambert_wm1().is_nan());
+    assert!(f(0.0).lambert_wm1().is_inf() && f(0.0).lambert_w0().is_zero());
+
+    assert!(close(f(0.5).erf_inv(), 0.4769362762044699));
+    assert!(close(f(-0.999).erf_inv(), -2.326753765513525));
+    assert!(close(f(0.1).erfc_inv(), 1.1630871536766743));
+    assert!(close(f(1.9).erfc_inv(), -1.1630871536766743));
+    assert!(close(f(1e-300).erfc_inv(), 26.209469960516124));
+    assert_eq!(f(0.25).erf_inv().erf(), f(0.25));
+    assert!(f(1.0).erf_inv().is_inf() && f(1.0).erfc_inv().is_zero());
+    assert!(f(1.5).erf_inv().is_nan() && f(-0.5).erfc_inv().is_nan());
+
+    assert!(close(f(1.0).expint_n(1), 0.21938393439552029));
+    assert!(close(f(1.0).expint_n(2), 0.14849550677592205));
+    assert!(close(f(0.01).expint_n(1), 4.037929576538114));
+    assert!(close(f(2.0).expint_n(0), (-2.0f64).exp() / 2.0));
+    assert!(close(f(500.0).expint_n(3), 1.4164335097287501e-220));
+    assert_eq!(f(0.0).expint_n(3), f(0.5));
+    assert!(f(0.0).expint_n(1).is_inf() && f(-1.0).expint_n(1).is_nan());
+    // large orders and arguments short of the asymptotic expansion
+    let e = f(1e5).expint_n(1000000);
+    assert!((e / BigFloat::new().from("3.2390477362706705151e-43436") - f(1.0)).abs() < f(1e-15));
+
+    // correct rounding at 200 bits
+    check_rounding(|rnd| f200(1.0).lambert_w0_round(rnd),
+        "0.567143290409783872999968662210355549753815787186512508135131079223045793086684566693219447");
+    check_rounding(|rnd| f200(-0.125).lambert_wm1_round(rnd),
+        "-3.26168568457648877690566236430873973172114539334780952204021807988063514676855672840946046");
+    check_rounding(|rnd| f200(0.5).erf_inv_round(rnd),
+        "0.476936276204469873381418353643130559808969749059470644703882695919383447774646733488695916");
+    check_rounding(|rnd| f200(0.125).erfc_inv_round(rnd),
+        "1.08478704006928314130699193783667332336934562375772064159377945003819307389406813266694221");
+    check_rounding(|rnd| f200(1.0).expint_n_round(2, rnd),
+        "0.148495506775922047918359994701339218414763837624859626929858188623892797185758258634937023");
+    check_rounding(|rnd| f200(300.0).expint_n_round(5, rnd),
+        "1.688024649109023861688012651804821782906864180674075299839502340053876405064238988247486e-133");
+}
+
+#[test]
+fn test_polylog_hurwitz_and_clausen() {
+    assert!(close(f(0.5).polylog_si(3), 0.5372131936080402));
+    assert!(close(f(0.9).polylog_si(3), 1.0496589501864399));
+    assert!(close(f(-5.0).polylog_si(3), -3.5375114376186075));
+    assert!(close(f(-10.0).polylog_si(4), -7.3265702480270797));
+    assert!(close(f(0.75).polylog(&f(2.5)), 0.8949966202588643));
+    assert!(close(f(-0.5).polylog(&f(1.5)), -0.42988732158057927));
+    assert!(close(f(-1.5).polylog(&f(2.5)), -1.231511579325201));
+    assert!(close(f(0.25).polylog(&f(0.5)), 0.3057349303992964));
+    assert_eq!(f(0.5).polylog(&f(3.0)), f(0.5).polylog_si(3));
+    assert!(close(f(0.3).polylog_si(1), -(0.7f64).ln()));
+    assert_eq!(f(0.5).polylog_si(0), f(1.0));
+    assert_eq!(f(0.5).polylog_si(-1), f(2.0));
+    assert_eq!(f(0.5).polylog_si(-2), f(6.0));
+    assert!(f(1.0).polylog_si(3) == f(3.0).zeta());
+    assert!(close(f(-1.0).polylog_si(3), -0.75 * 1.2020569031595942));
+    assert!(close(f(-3.0).polylog(&f(2.5)), -2.1627007120020566));
+    assert!(close(f(-1e6).polylog(&f(0.3)), -2.4449976070390915));
+    assert!(f(1.5).polylog_si(3).is_nan() && f(1.5).polylog(&f(2.5)).is_nan());
+    let minus_inf = f(-1.0 / 0.0);
+    assert!((&minus_inf).polylog(&f(0.5)) == minus_inf);
+    assert!(is_negative(&(&minus_inf).polylog(&f(-0.5))));
+    assert!(!is_negative(&(&minus_inf).polylog(&f(-1.5))));
+
+    assert!(f(2.0).hurwitz_zeta(&f(1.0)) == f(2.0).zeta());
+    assert!(close(f(2.0).hurwitz_zeta(&f(0.5)), 4.934802200544679));
+    assert!(close(f(2.5).hurwitz_zeta(&f(0.25)), 32.847451954697686));
+    assert!(close(f(-1.5).hurwitz_zeta(&f(3.0)), -3.853912326636023));
+    assert!(close(f(0.5).hurwitz_zeta(&f(2.0)), -2.460354508809587));
+    assert_eq!(f(0.0).hurwitz_zeta(&f(0.75)), f(-0.25));
+    assert_eq!(f(-1.0).hurwitz_zeta(&f(1.0)), f(-1.0) / f(12.0));
+    assert!(f(1.0).hurwitz_zeta(&f(2.0)).is_inf() && f(2.0).hurwitz_zeta(&f(0.0)).is_nan());
+    assert_eq!(f(-1000.0).hurwitz_zeta(&f(2.0)), f(-1.0));
+    assert_eq!(f(-301.0).hurwitz_zeta(&f(1.0)), f(-301.0).zeta());
+    let zero = f(-300.0).hurwitz_zeta(&f(0.5));
+    assert!(zero.is_zero() && !is_negative(&zero));
+
+    assert!(close(f(1.0).clausen(), 1.0139591323607685));
+    assert!(close(f(10.0).clausen(), -0.3907164760868021));
+    assert!(close(f(-4.0).clausen(), 0.5681439444298698));
+    let catalan = BigFloat::new().const_catalan();
+    let d = (BigFloat::new().const_pi() / 2u32).clausen() - catalan;
+    assert!(d.abs() <= f(1e-15));
+    assert!(f(0.0).clausen().is_zero() && f(1.0 / 0.0).clausen().is_nan());
+
+    assert_eq!(f(1.0).dirichlet_eta(), BigFloat::new().const_log2());
+    assert!(close(f(2.0).dirichlet_eta(), 0.8224670334241132));
+    assert!(close(f(0.5).dirichlet_eta(), 0.6048986434216304));
+    assert!(close(f(3.0).dirichlet_eta(), 0.9015426773696957));
+    assert!(close(f(-2.5).dirichlet_eta(), -0.08784112072136284));
+    assert_eq!(f(0.0).dirichlet_eta(), f(0.5));
+    assert_eq!(f(-1.0).dirichlet_eta(), f(0.25));
+    assert!(f(-2.0).dirichlet_eta().is_zero());
+    assert_eq!(f(-1.0).polylog(&f(2.5)), -f(2.5).dirichlet_eta());
+
+    // correct rounding at 200 bits
+    check_rounding(|rnd| f200(-3.0).polylog_round(&f(2.5), rnd),
+        "-2.16270071200205666229259144071920603255080851130265845879125219625490933542665513270166039");
+    check_rounding(|rnd| f200(-1000.0).polylog_round(&f(0.75), rnd),
+        "-4.60407094736548498754559454012161992642532668806958848116044914993022729782093616093222931");
+    check_rounding(|rnd| f200(-5.0).polylog_si_round(3, rnd),
+        "-3.53751143761860753567681491367060417932566305036247948126980677561193924632809000483581446");
+    check_rounding(|rnd| f200(2.5).hurwitz_zeta_round(&f(0.25), rnd),
+        "32.8474519546976858627157472568069741518228205174189081057219936262690619694208093983264131");
+    check_rounding(|rnd| f200(-1000.0).hurwitz_zeta_round(&f(2.5), rnd),
+        "-1.23384059690617347922743909948678005742186900514842808542522975184329422623652841193932245e176");
+    check_rounding(|rnd| f200(-0.5).polylog_si_round(-301, rnd),
+        "9.43517772523008573377631684677955884531053356929451737122127003936651176480583549577623097e463");
+    check_rounding(|rnd| f200(10.0).clausen_round(rnd),
+        "-0.390716476086802110433313414079873823503643301689492450896294539003555787357874769374549287");
+    check_rounding(|rnd| f200(0.5).dirichlet_eta_round(rnd),
+        "0.604898643421630370247265914235955499759762545130247380378546648082187253495060357327403957");
+}
+
+#[test]
+fn test_elliptic_integrals_and_jacobi_functions() {
+    assert!(close(f(0.5).elliptic_k(), 1.8540746773013719));
+    assert!(close(f(-3.0).elliptic_k(), 1.0782578237498216));
+    assert!(close(f(0.5).elliptic_e(), 1.3506438810476755));
+    assert!(close(f(-3.0).elliptic_e(), 2.422112055136919));
+    assert!(close(f(0.999).elliptic_e(), 1.0021707908344452));
+    assert_eq!(f(0.0).elliptic_k(), BigFloat::new().const_pi() / 2u32);
+    assert_eq!(f(1.0).elliptic_e(), f(1.0));
+    assert!(f(1.0).elliptic_k().is_inf() && f(1.5).elliptic_e().is_nan());
+
+    assert!(close(f(1.0).elliptic_f(&f(0.5)), 1.0832167728451688));
+    assert!(close(f(1.0).elliptic_e_inc(&f(0.5)), 0.92732988362444007));
+    assert!(close(f(5.0).elliptic_f(&f(0.7)), 6.735848353248832));
+    assert!(close(f(5.0).elliptic_e_inc(&f(0.7)), 3.8873948219108214));
+    assert!(close(f(2.0).elliptic_f(&f(-2.0)), 1.4243854376414235));
+    assert!(close(f(2.0).elliptic_e_inc(&f(-2.0)), 2.912907272354933));
+    assert!(close(f(0.4).elliptic_f(&f(3.0)), 0.4399655103639790));
+    assert!(close(f(0.4).elliptic_e_inc(&f(3.0)), 0.3664188172172375));
+    assert!(close(f(4.0).elliptic_e_inc(&f(1.0)), 2.756802495307928));
+    assert_eq!(f(0.75).elliptic_f(&f(0.0)), f(0.75));
+    assert!(f(1.0).elliptic_f(&f(2.0)).is_nan() && f(2.0).elliptic_f(&f(1.0)).is_nan());
+
+    assert!(close(f(0.8).jacobi_sn(&f(0.5)), 0.6909348508664388));
+    assert!(close(f(0.8).jacobi_cn(&f(0.5)), 0.7229170297192977));
+    assert!(close(f(0.8).jacobi_dn(&f(0.5)), 0.8725276591198046));
+    assert!(close(f(10.0).jacobi_sn(&f(0.3)), 0.27848731626489937));
+    assert!(close(f(10.0).jacobi_cn(&f(0.3)), -0.960439906854965));
+    assert!(close(f(10.0).jacobi_dn(&f(0.3)), 0.9882982568050356));
+    assert!(close(f(2.0).jacobi_sn(&f(4.0)), -0.2901000599068371));
+    assert!(close(f(2.0).jacobi_cn(&f(4.0)), 0.956996319346135));
+    assert!(close(f(2.0).jacobi_dn(&f(4.0)), -0.8144739535234986));
+    assert!(close(f(1.5).jacobi_cn(&f(-2.0)), -0.5227515149421756));
+    assert!(close(f(1.5).jacobi_dn(&f(-2.0)), 1.5663529957360572));
+    assert_eq!(f(0.5).jacobi_sn(&f(1.0)), f(0.5).tanh());
+    assert_eq!(f(0.5).jacobi_cn(&f(0.0)), f(0.5).cos());
+    assert_eq!(f(0.0).jacobi_dn(&f(0.5)), f(1.0));
+
+    // F(am(u, m), m) = u
+    let am = f(0.8).jacobi_sn(&f(0.5)).asin();
+    assert!(close(am.elliptic_f(&f(0.5)), 0.8));
+
+    check_rounding(|rnd| f200(0.5).elliptic_k_round(rnd),
+        "1.85407467730137191843385034719526004621759882352176690558592804505602177683811997835727186");
+    check_rounding(|rnd| f200(-3.0).elliptic_e_round(rnd),
+        "2.42211205513691904960712579909795735298847959947165020627077908811553016149063803707335024");
+    check_rounding(|rnd| f200(0.999).elliptic_e_round(rnd),
+        "1.0021707908344451675773275035267368182669215701950747132583864458936005771094661905865922");
+    check_rounding(|rnd| f200(-1e6).elliptic_e_round(rnd),
+        "1000.00439702434854808228326821355399271301636725478832958310184402288980206434264926223124");
+    check_rounding(|rnd| f200(1.0).elliptic_f_round(&f(0.5), rnd),
+        "1.08321677284516875044413245156531468784894664140531165679781872263145076078449449159234101");
+    check_rounding(|rnd| f200(2.0).elliptic_e_inc_round(&f(-2.0), rnd),
+        "2.91290727235493304432926768057629317284884584245217871007362385482863592672166131900289466");
+    check_rounding(|rnd| f200(10.0).jacobi_sn_round(&f(0.3), rnd),
+        "0.27848731626489937313692380487481544318462585157940565016194666421056521119621038538999379");
+    check_rounding(|rnd| f200(10.0).jacobi_cn_round(&f(0.3), rnd),
+        "-0.960439906854965035328766466646980295761440903271519732638401092082021141413159699895572433");
+    check_rounding(|rnd| f200(2.0).jacobi_dn_round(&f(4.0), rnd),
+        "-0.81447395352349859491963192239769706890231725105918312458164313532525341532377029225291121");
+}
+
+#[test]
+fn test_probability_distributions() {
+    use mpfr::distributions::{Continuous, Discrete, Normal, Gamma, Beta, StudentT, ChiSquared, Poisson};
+
+    let n = Normal::new(f(0.0), f(1.0));
+    assert!(close(n.cdf(&f(1.0)), 0.84134474606854295));
+    assert!(close(n.pdf(&f(0.5)), 0.35206532676429948));
+    assert!(close(n.quantile(&f(0.975)), 1.9599639845400542));
+    // Far below the range of the complement computed in f64
+    assert!(close(n.sf(&f(37.0)), 5.7255712225245768e-300));
+    assert!(close(n.cdf(&f(-37.0)), 5.7255712225245768e-300));
+    assert_eq!(n.sf(&f(37.0)), n.cdf(&f(-37.0)));
+    assert!(close(Normal::new(f(1.0), f(2.0)).cdf(&f(-1.5)), 0.10564977366685526));
+    assert_eq!(Normal::new(f(3.0), f(2.0)).cdf(&f(3.0)), f(0.5));
+    assert!(Normal::new(f(0.0), f(-1.0)).cdf(&f(0.0)).is_nan());
+
+    let g = Gamma::new(f(2.5), f(2.0));
+    assert!(close(g.cdf(&f(3.0)), 0.30001416412137249));
+    assert!(close(g.sf(&f(3.0)), 0.69998583587862751));
+    assert!(close(g.pdf(&f(3.0)), 0.15418032980376928));
+    assert!(close(g.quantile(&f(0.3)), 2.9999081327599063));
+    assert!(close(g.sf(&f(200.0)), 2.8406228986415317e-41));
+    let c = ChiSquared::new(f(4.0));
+    assert!(close(c.sf(&f(10.0)), 0.040427681994512803));
+    assert!(close(c.quantile(&f(0.95)), 9.4877290367811568));
+
+    let b = Beta::new(f(2.0), f(2.0));
+    assert_eq!(b.cdf(&f(0.25)), f(5.0 / 32.0));
+    assert_eq!(b.quantile(&f(5.0 / 32.0)), f(0.25));
+    assert_eq!(b.pdf(&f(0.5)), f(1.5));
+    let b = Beta::new(f(2.5), f(0.5));
+    assert!(close(b.cdf(&f(0.3)), 0.018927124071945654));
+    assert!(close(b.pdf(&f(0.3)), 0.16670618833028559));
+    assert!(close(Beta::new(f(2.0), f(3.0)).quantile(&f(0.1)), 0.14255931671003072));
+    assert!(close(Beta::new(f(0.5), f(0.5)).quantile(&f(0.9)), 0.9755282581475768));
+
+    let t = StudentT::new(f(3.0));
+    assert!(close(t.cdf(&f(2.0)), 0.93033701572057841));
+    assert!(close(t.pdf(&f(2.0)), 0.067509660663892904));
+    assert!(close(t.sf(&f(40.0)), 1.7190340394579264e-5));
+    assert!(close(t.quantile(&f(0.95)), 2.3533634348018239));
+    assert!(close(StudentT::new(f(10.0)).quantile(&f(1e-10)), -25.466008021697726));
+    assert_eq!(StudentT::new(f(1.0)).cdf(&f(1.0)), f(0.75));
+
+    let p = Poisson::new(f(3.5));
+    assert!(close(p.pmf(2), 0.18495897346170082));
+    assert!(close(p.cdf(4), 0.72544495330960461));
+    assert!(close(p.sf(30), 3.0280745798206685e-19));
+    assert_eq!(p.quantile(&f(0.9)), Some(6));
+    assert_eq!(p.quantile(&f(0.0)), Some(0));
+    assert_eq!(p.quantile(&f(1.0)), None);
+    // A large mean, where the sums of O(λ) terms would not finish
+    let p = Poisson::new(f(1e9));
+    assert!(close(p.cdf(999900000), 7.8261612533531643e-4));
+    assert_eq!(p.quantile(&f(0.3)), Some(999983417));
+
+    let p = Poisson::new(f200(1e9));
+    check_rounding(|rnd| p.cdf_round(1000000000, rnd),
+        "0.500008410441738992532605244530847706068703590152242662802054114011897720252899652867150065");
+    check_rounding(|rnd| p.sf_round(1000030000, rnd),
+        "0.171386699518829769173368129410025935793581831740958178626284562044546778464524593802447879");
+    check_rounding(|rnd| Gamma::new(f(2.75), f(1.0)).sf_round(&f200(300.5), rnd),
+        "4.23526918012503403285320805815625008837766400293091566273046471505497923715309027491706637e-127");
+    check_rounding(|rnd| Beta::new(f(2.5), f(3.5)).cdf_round(&f200(0.25), rnd),
+        "0.209284331863430122193284871212915209018921375250839186453475978767851515052747629494247827");
+}
+
+#[test]
+fn test_random_state() {
+    use mpfr::random::RandState;
+
+    let mut a = RandState::new();
+    a.seed(42);
+    let mut b = a.clone();
+    for _ in 0..10 {
+        assert_eq!(a.urandomb(64.bits()), b.urandomb(64.bits()));
+        assert_eq!(a.nrandom(64.bits()), b.nrandom(64.bits()));
+    }
+    let mut c = RandState::new();
+    c.seed(42);
+    let mut d = RandState::new();
+    d.seed(42);
+    assert_eq!(c.erandom(53.bits()), d.erandom(53.bits()));
+    c.seed(1 << 32);
+    d.seed(0);
+    assert!(c.urandomb(64.bits()) != d.urandomb(64.bits()));
+
+    let x = a.urandomb(100.bits());
+    assert_eq!(100, x.prec().bits());
+    assert!(x >= BigFloat::new().from(0.0) && x < BigFloat::new().from(1.0));
+    let (_, o) = a.nrandom_round(24.bits(), RoundingMode::Downwards);
+    assert_eq!(Ordering::Less, o);
+    let (y, o) = a.erandom_round(24.bits(), RoundingMode::Upwards);
+    assert_eq!(Ordering::Greater, o);
+    assert!(y > BigFloat::new().from(0.0));
+
+    let mut lc = RandState::linear_congruential(64).unwrap();
+    lc.seed(7);
+    assert!(lc.urandom(53.bits()) <= BigFloat::new().from(1.0));
+    assert!(RandState::linear_congruential(129).is_none());
+
+    let n = 10000;
+    let (mut normal, mut exponential) = (0.0, 0.0);
+    for _ in 0..n {
+        normal += a.nrandom(53.bits()).get::<f64>();
+        exponential += a.erandom(53.bits()).get::<f64>();
+    }
+    assert!((normal / n as f64).abs() < 0.05);
+    assert!((exponential / n as f64 - 1.0).abs() < 0.05);
+}
@@ -46,3 +46,39 @@ fn test_string_with_base() {
     assert_eq!(64, x.prec().bits());
     assert_eq!("43981.93", &format(2, &x)[]);
 }
+
+#[cfg(feature = "gmp")]
+#[test]
+fn test_gmp() {
+    use std::cmp::Ordering;
+    use mpfr::gmp::{Mpz, Mpq};
+    use mpfr::RoundingMode;
+
+    BigFloat::set_default_prec(64.bits());
+
+    let z = Mpz::from(-12345i64);
+    assert_eq!(BigFloat::new().from(-12345.0), z.to_big_float());
+    assert_eq!(BigFloat::new().from(-24690.0), (&z, 1).to_big_float());
+    let q = Mpq::ratio(&Mpz::from(3i64), &Mpz::from(8i64));
+    assert_eq!(BigFloat::new().from(0.375), q.to_big_float());
+
+    let x = BigFloat::new().from(2.5);
+    assert_eq!(BigFloat::new().from(2.0), x.get::<Mpz>());
+    assert_eq!(Mpq::ratio(&Mpz::from(5i64), &Mpz::from(2i64)), x.get::<Mpq>());
+    let (m, e) = BigFloat::new().from(-0.75).get_z_2exp();
+    assert_eq!(BigFloat::new().from(-0.75), (&m, e).to_big_float());
+
+    assert_eq!(BigFloat::new().from(-12342.5), x.add_z(&z));
+    assert_eq!(BigFloat::new().from(0.9375), x.mul_q(&q));
+    let third = Mpq::ratio(&Mpz::from(1i64), &Mpz::from(3i64));
+    let (down, o) = BigFloat::new().from(0.0).add_q_round(&third, RoundingMode::Downwards);
+    assert_eq!(Ordering::Less, o);
+    assert_eq!(Some(Ordering::Less), down.cmp_q(&third));
+    assert_eq!(Some(Ordering::Greater), x.cmp_z(&z));
+    assert_eq!(None, BigFloat::new().from(0.0 / 0.0).cmp_z(&z));
+    // the erange flag left by the comparison above does not make equal numbers unordered
+    assert_eq!(Some(Ordering::Equal), BigFloat::new().from(-12345.0).cmp_z(&z));
+    assert_eq!(Some(Ordering::Equal), BigFloat::new().from(0.375).cmp_q(&q));
+    assert_eq!("-12345", format!("{}", z));
+    assert_eq!("3/8", format!("{}", q));
+}